@@ -89,6 +89,14 @@ Power::Power(std::shared_ptr<HintManager> hm, std::shared_ptr<DisplayLowPower> d
 ndk::ScopedAStatus Power::setMode(Mode type, bool enabled) {
     LOG(DEBUG) << "Power setMode: " << toString(type) << " to: " << enabled;
     ATRACE_INT(toString(type).c_str(), enabled);
+    PowerHalMapping mapping;
+    if (mHintManager->GetModeMapping(toString(type), &mapping)) {
+        // LAUNCH is ignored in VR and sustained performance mode, as below
+        if (type != Mode::LAUNCH || !(mVRModeOn || mSustainedPerfModeOn)) {
+            setModeFromConfig(mapping, enabled);
+        }
+        return ndk::ScopedAStatus::ok();
+    }
     switch (type) {
         case Mode::LOW_POWER:
             mDisplayLowPower->SetDisplayLowPower(enabled);
@@ -173,11 +181,40 @@ ndk::ScopedAStatus Power::setMode(Mode type, bool enabled) {
     return ndk::ScopedAStatus::ok();
 }
 
+void Power::setModeFromConfig(const PowerHalMapping &mapping, bool enabled) {
+    if (mapping.handler == "DisplayLowPower") {
+        mDisplayLowPower->SetDisplayLowPower(enabled);
+    } else if (mapping.handler == "SustainedPerformance") {
+        mSustainedPerfModeOn = enabled;
+    } else if (mapping.handler == "VR") {
+        mVRModeOn = enabled;
+    } else if (!mapping.handler.empty()) {
+        LOG(ERROR) << "Unknown mode handler: " << mapping.handler;
+    }
+    if (!mHintManager->IsHintSupported(mapping.hint_type)) {
+        return;
+    }
+    if (!enabled) {
+        mHintManager->EndHint(mapping.hint_type);
+    } else if (mapping.duration > std::chrono::milliseconds::zero()) {
+        mHintManager->DoHint(mapping.hint_type, mapping.duration);
+    } else {
+        mHintManager->DoHint(mapping.hint_type);
+    }
+}
+
 ndk::ScopedAStatus Power::isModeSupported(Mode type, bool *_aidl_return) {
-    bool supported = mHintManager->IsHintSupported(toString(type));
-    // LOW_POWER handled insides PowerHAL specifically
-    if (type == Mode::LOW_POWER) {
-        supported = true;
+    bool supported;
+    PowerHalMapping mapping;
+    if (mHintManager->GetModeMapping(toString(type), &mapping)) {
+        supported = !mapping.handler.empty() ||
+                    mHintManager->IsHintSupported(mapping.hint_type);
+    } else {
+        supported = mHintManager->IsHintSupported(toString(type));
+        // LOW_POWER handled insides PowerHAL specifically
+        if (type == Mode::LOW_POWER) {
+            supported = true;
+        }
     }
     LOG(INFO) << "Power mode " << toString(type) << " isModeSupported: " << supported;
     *_aidl_return = supported;
@@ -187,6 +224,11 @@ ndk::ScopedAStatus Power::isModeSupported(Mode type, bool *_aidl_return) {
 ndk::ScopedAStatus Power::setBoost(Boost type, int32_t durationMs) {
     LOG(DEBUG) << "Power setBoost: " << toString(type) << " duration: " << durationMs;
     ATRACE_INT(toString(type).c_str(), durationMs);
+    PowerHalMapping mapping;
+    if (mHintManager->GetBoostMapping(toString(type), &mapping)) {
+        setBoostFromConfig(mapping, durationMs);
+        return ndk::ScopedAStatus::ok();
+    }
     switch (type) {
         case Boost::INTERACTION:
             if (mVRModeOn || mSustainedPerfModeOn) {
@@ -221,8 +263,39 @@ ndk::ScopedAStatus Power::setBoost(Boost type, int32_t durationMs) {
     return ndk::ScopedAStatus::ok();
 }
 
+void Power::setBoostFromConfig(const PowerHalMapping &mapping, int32_t durationMs) {
+    // Boosts are ignored in VR and sustained performance mode
+    if (mVRModeOn || mSustainedPerfModeOn) {
+        return;
+    }
+    if (durationMs == 0 && mapping.duration > std::chrono::milliseconds::zero()) {
+        durationMs = mapping.duration.count();
+    }
+    if (mapping.handler == "Interaction") {
+        mInteractionHandler->Acquire(durationMs);
+        return;
+    }
+    if (!mapping.handler.empty()) {
+        LOG(ERROR) << "Unknown boost handler: " << mapping.handler;
+    }
+    if (durationMs > 0) {
+        mHintManager->DoHint(mapping.hint_type, std::chrono::milliseconds(durationMs));
+    } else if (durationMs == 0) {
+        mHintManager->DoHint(mapping.hint_type);
+    } else {
+        mHintManager->EndHint(mapping.hint_type);
+    }
+}
+
 ndk::ScopedAStatus Power::isBoostSupported(Boost type, bool *_aidl_return) {
-    bool supported = mHintManager->IsHintSupported(toString(type));
+    bool supported;
+    PowerHalMapping mapping;
+    if (mHintManager->GetBoostMapping(toString(type), &mapping)) {
+        supported = !mapping.handler.empty() ||
+                    mHintManager->IsHintSupported(mapping.hint_type);
+    } else {
+        supported = mHintManager->IsHintSupported(toString(type));
+    }
     LOG(INFO) << "Power boost " << toString(type) << " isBoostSupported: " << supported;
     *_aidl_return = supported;
     return ndk::ScopedAStatus::ok();
@@ -232,7 +305,18 @@ constexpr const char *boolToString(bool b) {
     return b ? "true" : "false";
 }
 
-binder_status_t Power::dump(int fd, const char **, uint32_t) {
+binder_status_t Power::dump(int fd, const char **args, uint32_t numArgs) {
+    if (numArgs == 1 && std::string(args[0]) == "--reload") {
+        std::string diff;
+        if (!mHintManager->ReloadConfig(&diff)) {
+            diff = "Failed to reload config\n";
+        }
+        if (!::android::base::WriteStringToFd(diff, fd)) {
+            PLOG(ERROR) << "Failed to dump state to fd";
+        }
+        fsync(fd);
+        return STATUS_OK;
+    }
     std::string buf(::android::base::StringPrintf(
             "HintManager Running: %s\n"
             "VRMode: %s\n"
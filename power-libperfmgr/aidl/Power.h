@@ -37,6 +37,7 @@ using ::InteractionHandler;
 using ::aidl::android::hardware::power::Boost;
 using ::aidl::android::hardware::power::Mode;
 using ::android::perfmgr::HintManager;
+using ::android::perfmgr::PowerHalMapping;
 
 class Power : public ::aidl::android::hardware::power::BnPower {
   public:
@@ -48,6 +49,11 @@ class Power : public ::aidl::android::hardware::power::BnPower {
     binder_status_t dump(int fd, const char **args, uint32_t numArgs) override;
 
   private:
+    // Handle a Mode or Boost declared in the Modes or Boosts section of the
+    // libperfmgr JSON config.
+    void setModeFromConfig(const PowerHalMapping &mapping, bool enabled);
+    void setBoostFromConfig(const PowerHalMapping &mapping, int32_t durationMs);
+
     std::shared_ptr<HintManager> mHintManager;
     std::shared_ptr<DisplayLowPower> mDisplayLowPower;
     std::unique_ptr<InteractionHandler> mInteractionHandler;
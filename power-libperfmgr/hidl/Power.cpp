@@ -359,10 +359,22 @@ constexpr const char *boolToString(bool b) {
     return b ? "true" : "false";
 }
 
-Return<void> Power::debug(const hidl_handle &handle, const hidl_vec<hidl_string> &) {
+Return<void> Power::debug(const hidl_handle &handle, const hidl_vec<hidl_string> &args) {
     if (handle != nullptr && handle->numFds >= 1 && mReady) {
         int fd = handle->data[0];
 
+        if (args.size() == 1 && args[0] == "--reload") {
+            std::string diff;
+            if (!mHintManager->ReloadConfig(&diff)) {
+                diff = "Failed to reload config\n";
+            }
+            if (!android::base::WriteStringToFd(diff, fd)) {
+                PLOG(ERROR) << "Failed to dump state to fd";
+            }
+            fsync(fd);
+            return Void();
+        }
+
         std::string buf(android::base::StringPrintf(
                 "HintManager Running: %s\n"
                 "VRMode: %s\n"
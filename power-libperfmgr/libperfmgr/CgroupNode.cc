@@ -0,0 +1,164 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specic language governing permissions and
+ * limitations under the License.
+ */
+
+#define ATRACE_TAG (ATRACE_TAG_POWER | ATRACE_TAG_HAL)
+#define LOG_TAG "libperfmgr"
+
+#include "perfmgr/CgroupNode.h"
+
+#include <android-base/file.h>
+#include <android-base/logging.h>
+#include <android-base/stringprintf.h>
+#include <android-base/strings.h>
+#include <sys/stat.h>
+#include <utils/Trace.h>
+
+namespace android {
+namespace perfmgr {
+
+using namespace std::chrono_literals;
+
+// Interval to check again for a cgroup which is not present, e.g. not yet
+// created by init.
+constexpr std::chrono::milliseconds kCgroupRetryInterval = 5000ms;
+// Interval to retry a failed read or write
+constexpr std::chrono::milliseconds kWriteRetryInterval = 500ms;
+
+CgroupNode::CgroupNode(std::string name, std::string node_path,
+                       std::vector<RequestGroup> req_sorted,
+                       std::size_t default_val_index, bool reset_on_init)
+    : Node(std::move(name), std::move(node_path), std::move(req_sorted),
+           default_val_index, reset_on_init),
+      cgroup_path_(android::base::Dirname(node_path_)),
+      cgroup_missing_(false) {}
+
+std::chrono::milliseconds CgroupNode::Update(bool log_error) {
+    std::chrono::milliseconds expire_time;
+    std::size_t value_index = SelectValueIndex(&expire_time);
+    bool requested = HasRequests();
+
+    if (!requested && !restore_value_ && !reset_on_init_) {
+        return expire_time;
+    }
+
+    struct stat st;
+    if (stat(cgroup_path_.c_str(), &st) != 0 || !S_ISDIR(st.st_mode)) {
+        if (log_error && !cgroup_missing_) {
+            LOG(WARNING) << "Cgroup not present: " << cgroup_path_
+                         << " for node: " << name_;
+            cgroup_missing_ = true;
+        }
+        // Nothing left to restore in a removed cgroup
+        restore_value_.reset();
+        return requested ? std::min(expire_time, kCgroupRetryInterval)
+                         : expire_time;
+    }
+    if (cgroup_missing_) {
+        LOG(INFO) << "Cgroup present: " << cgroup_path_ << " for node: "
+                  << name_;
+        cgroup_missing_ = false;
+    }
+
+    if (!requested) {
+        // Restore the value found before the first request, or initialize the
+        // node with the default value.
+        const std::string& value =
+            restore_value_ ? *restore_value_
+                           : req_sorted_[default_val_index_].GetRequestValue();
+        if (!WriteValue(value, log_error)) {
+            return std::min(expire_time, kWriteRetryInterval);
+        }
+        restore_value_.reset();
+        current_val_index_ = default_val_index_;
+        reset_on_init_ = false;
+        return expire_time;
+    }
+
+    bool force_write = reset_on_init_;
+    if (!restore_value_) {
+        std::string value;
+        if (!android::base::ReadFileToString(node_path_, &value)) {
+            if (log_error) {
+                LOG(WARNING) << "Failed to read node: " << node_path_;
+            }
+            return std::min(expire_time, kWriteRetryInterval);
+        }
+        restore_value_ = android::base::Trim(value);
+        force_write = true;
+    }
+
+    // Update node only if request index changes
+    if (value_index != current_val_index_ || force_write) {
+        if (!WriteValue(req_sorted_[value_index].GetRequestValue(),
+                        log_error)) {
+            return std::min(expire_time, kWriteRetryInterval);
+        }
+        // Update current index only when succeed
+        current_val_index_ = value_index;
+        reset_on_init_ = false;
+    }
+    return expire_time;
+}
+
+void CgroupNode::InheritState(const Node& other) {
+    Node::InheritState(other);
+    restore_value_ = other.GetRestoreValue();
+    reset_on_init_ = HasRequests();
+}
+
+std::optional<std::string> CgroupNode::GetRestoreValue() const {
+    return restore_value_;
+}
+
+bool CgroupNode::WriteValue(const std::string& value, bool log_error) {
+    ATRACE_BEGIN(GetName().c_str());
+    bool ret = android::base::WriteStringToFile(value, node_path_);
+    if (!ret && log_error) {
+        LOG(WARNING) << "Failed to write to node: " << node_path_
+                     << " with value: " << value;
+    }
+    ATRACE_END();
+    return ret;
+}
+
+const std::string& CgroupNode::GetCgroupPath() const {
+    return cgroup_path_;
+}
+
+void CgroupNode::DumpToFd(int fd) const {
+    std::string node_value;
+    if (!android::base::ReadFileToString(node_path_, &node_value)) {
+        LOG(ERROR) << "Failed to read node path: " << node_path_;
+    }
+    node_value = android::base::Trim(node_value);
+    std::string buf(android::base::StringPrintf(
+        "%s\t%s\t%zu\t%s\n", name_.c_str(), node_path_.c_str(),
+        current_val_index_, node_value.c_str()));
+    if (restore_value_) {
+        buf += android::base::StringPrintf("\t\tRestore:\t%s\n",
+                                           restore_value_->c_str());
+    }
+    if (!android::base::WriteStringToFd(buf, fd)) {
+        LOG(ERROR) << "Failed to dump fd: " << fd;
+    }
+    for (std::size_t i = 0; i < req_sorted_.size(); i++) {
+        req_sorted_[i].DumpToFd(
+            fd, android::base::StringPrintf("\t\tReq%zu:\t", i));
+    }
+}
+
+}  // namespace perfmgr
+}  // namespace android
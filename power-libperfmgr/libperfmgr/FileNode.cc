@@ -27,79 +27,139 @@
 #include <android-base/strings.h>
 #include <utils/Trace.h>
 
+#include <cinttypes>
+
 namespace android {
 namespace perfmgr {
 
 FileNode::FileNode(std::string name, std::string node_path,
                    std::vector<RequestGroup> req_sorted,
                    std::size_t default_val_index, bool reset_on_init,
-                   bool hold_fd)
+                   bool hold_fd, bool verify_write, uint32_t write_retries)
     : Node(std::move(name), std::move(node_path), std::move(req_sorted),
            default_val_index, reset_on_init),
       hold_fd_(hold_fd),
+      verify_write_(verify_write),
+      write_retries_(write_retries),
       warn_timeout_(
-          android::base::GetBoolProperty("ro.debuggable", false) ? 5ms : 50ms) {
-}
+          android::base::GetBoolProperty("ro.debuggable", false) ? 5ms : 50ms),
+      failure_count_(0),
+      failed_val_index_(0),
+      failed_updates_(0) {}
 
 std::chrono::milliseconds FileNode::Update(bool log_error) {
-    std::size_t value_index = default_val_index_;
-    std::chrono::milliseconds expire_time = std::chrono::milliseconds::max();
-
-    // Find the highest outstanding request's expire time
-    for (std::size_t i = 0; i < req_sorted_.size(); i++) {
-        if (req_sorted_[i].GetExpireTime(&expire_time)) {
-            value_index = i;
-            break;
-        }
-    }
+    std::chrono::milliseconds expire_time;
+    std::size_t value_index = SelectValueIndex(&expire_time);
 
     // Update node only if request index changes
     if (value_index != current_val_index_ || reset_on_init_) {
+        // A value failing again, e.g. clamped by the kernel, is not rewritten
+        // before its retry time
+        bool failed_before =
+            failed_updates_ > 0 && failed_val_index_ == value_index;
+        auto now = std::chrono::steady_clock::now();
+        if (failed_before && now < retry_time_) {
+            return std::min(expire_time,
+                            std::chrono::ceil<std::chrono::milliseconds>(
+                                retry_time_ - now));
+        }
         ATRACE_BEGIN(GetName().c_str());
         const std::string& req_value =
             req_sorted_[value_index].GetRequestValue();
 
-        android::base::Timer t;
-        fd_.reset(TEMP_FAILURE_RETRY(
-            open(node_path_.c_str(), O_WRONLY | O_CLOEXEC | O_TRUNC)));
-
-        if (fd_ == -1 || !android::base::WriteStringToFd(req_value, fd_)) {
-            if (log_error) {
-                LOG(WARNING) << "Failed to write to node: " << node_path_
-                             << " with value: " << req_value << ", fd: " << fd_;
-            }
+        bool written = false;
+        for (uint32_t i = 0; i <= write_retries_ && !written; i++) {
+            // Log the failure once per value
+            written = WriteValue(req_value, value_index,
+                                 log_error && !failed_before &&
+                                     i == write_retries_);
+        }
+        if (!written && !log_error) {
             // Retry in 500ms or sooner
-            expire_time = std::min(expire_time, std::chrono::milliseconds(500));
-        } else {
-            // For regular file system, we need fsync
-            fsync(fd_);
-            // Some dev node requires file to remain open during the entire hint
-            // duration e.g. /dev/cpu_dma_latency, so fd_ is intentionally kept
-            // open during any requested value other than default one. If
-            // request a default value, node will write the value and then
-            // release the fd.
-            if ((!hold_fd_) || value_index == default_val_index_) {
-                fd_.reset();
-            }
-            auto duration = t.duration();
-            if (duration > warn_timeout_) {
-                LOG(WARNING) << "Slow writing to file: '" << node_path_
-                             << "' with value: '" << req_value
-                             << "' took: " << duration.count() << " ms";
+            expire_time = std::min(expire_time, kRetryDelay);
+        } else if (!written) {
+            failure_count_++;
+            if (!failed_before) {
+                failed_val_index_ = value_index;
+                failed_updates_ = 0;
             }
+            // Retry in 500ms, backing off up to 16s while the value fails
+            std::chrono::milliseconds retry_delay =
+                kRetryDelay *
+                (1 << std::min(failed_updates_, kMaxRetryBackoff));
+            failed_updates_++;
+            retry_time_ = now + retry_delay;
+            expire_time = std::min(expire_time, retry_delay);
+        } else {
             // Update current index only when succeed
             current_val_index_ = value_index;
             reset_on_init_ = false;
+            failed_updates_ = 0;
         }
         ATRACE_END();
     }
     return expire_time;
 }
 
+bool FileNode::WriteValue(const std::string& value, std::size_t value_index,
+                          bool log_error) {
+    android::base::Timer t;
+    fd_.reset(TEMP_FAILURE_RETRY(
+        open(node_path_.c_str(), O_WRONLY | O_CLOEXEC | O_TRUNC)));
+
+    if (fd_ == -1 || !android::base::WriteStringToFd(value, fd_)) {
+        if (log_error) {
+            LOG(WARNING) << "Failed to write to node: " << node_path_
+                         << " with value: " << value << ", fd: " << fd_;
+        }
+        return false;
+    }
+    // For regular file system, we need fsync
+    fsync(fd_);
+    // Some dev node requires file to remain open during the entire hint
+    // duration e.g. /dev/cpu_dma_latency, so fd_ is intentionally kept open
+    // during any requested value other than default one. If request a default
+    // value, node will write the value and then release the fd.
+    if ((!hold_fd_) || value_index == default_val_index_) {
+        fd_.reset();
+    }
+    auto duration = t.duration();
+    if (duration > warn_timeout_) {
+        LOG(WARNING) << "Slow writing to file: '" << node_path_
+                     << "' with value: '" << value
+                     << "' took: " << duration.count() << " ms";
+    }
+    if (verify_write_) {
+        std::string node_value;
+        if (!android::base::ReadFileToString(node_path_, &node_value) ||
+            android::base::Trim(node_value) != value) {
+            if (log_error) {
+                LOG(WARNING) << "Failed to verify node: " << node_path_
+                             << " with value: " << value << ", read back: "
+                             << android::base::Trim(node_value);
+            }
+            return false;
+        }
+    }
+    return true;
+}
+
 bool FileNode::GetHoldFd() const {
     return hold_fd_;
 }
 
+bool FileNode::GetVerifyWrite() const {
+    return verify_write_;
+}
+
+uint32_t FileNode::GetWriteRetries() const {
+    return write_retries_;
+}
+
+uint64_t FileNode::GetFailureCount() const {
+    return failure_count_;
+}
+
 void FileNode::DumpToFd(int fd) const {
     std::string node_value;
     if (!android::base::ReadFileToString(node_path_, &node_value)) {
@@ -109,6 +169,10 @@ void FileNode::DumpToFd(int fd) const {
     std::string buf(android::base::StringPrintf(
         "%s\t%s\t%zu\t%s\n", name_.c_str(), node_path_.c_str(),
         current_val_index_, node_value.c_str()));
+    if (failure_count_ > 0) {
+        buf += android::base::StringPrintf("\t\tFailures:\t%" PRIu64 "\n",
+                                           failure_count_);
+    }
     if (!android::base::WriteStringToFd(buf, fd)) {
         LOG(ERROR) << "Failed to dump fd: " << fd;
     }
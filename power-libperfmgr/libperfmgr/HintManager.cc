@@ -20,27 +20,208 @@
 
 #include <android-base/file.h>
 #include <android-base/logging.h>
+#include <android-base/strings.h>
 #include <json/reader.h>
 #include <json/value.h>
 
 #include <algorithm>
 #include <set>
+#include <sstream>
 
+#include "perfmgr/CgroupNode.h"
 #include "perfmgr/FileNode.h"
 #include "perfmgr/PropertyNode.h"
 
 namespace android {
 namespace perfmgr {
 
+namespace {
+
+// Names of the PowerHAL Modes and Boosts, and the handlers the PowerHAL
+// implements for them.
+struct PowerHalSection {
+    std::set<std::string> names;
+    std::set<std::string> handlers;
+};
+
+const std::map<std::string, PowerHalSection> kPowerHalSections = {
+    {"Modes",
+     {{"DOUBLE_TAP_TO_WAKE", "LOW_POWER", "SUSTAINED_PERFORMANCE",
+       "FIXED_PERFORMANCE", "VR", "LAUNCH", "EXPENSIVE_RENDERING",
+       "INTERACTIVE", "DEVICE_IDLE", "DISPLAY_INACTIVE",
+       "AUDIO_STREAMING_LOW_LATENCY", "CAMERA_STREAMING_SECURE",
+       "CAMERA_STREAMING_LOW", "CAMERA_STREAMING_MID",
+       "CAMERA_STREAMING_HIGH"},
+      {"DisplayLowPower", "SustainedPerformance", "VR"}}},
+    {"Boosts",
+     {{"INTERACTION", "DISPLAY_UPDATE_IMMINENT", "ML_ACC", "AUDIO_LAUNCH",
+       "CAMERA_LAUNCH", "CAMERA_SHOT"},
+      {"Interaction"}}},
+};
+
+// Merge actions into merged, replacing any action on the same node.
+void mergeActions(const std::vector<NodeAction>& actions,
+                  std::vector<NodeAction>* merged) {
+    for (const auto& action : actions) {
+        auto it = std::find_if(merged->begin(), merged->end(),
+                               [&action](const NodeAction& m) {
+                                   return m.node_index == action.node_index;
+                               });
+        if (it == merged->end()) {
+            merged->push_back(action);
+        } else {
+            *it = action;
+        }
+    }
+}
+
+// Resolve the actions of hint_type into resolved, walking included hints
+// depth-first; visiting holds the composites on the current path.
+bool resolveActions(
+    const std::string& hint_type,
+    const std::map<std::string, std::vector<NodeAction>>& actions,
+    const std::map<std::string, CompositeHint>& composites,
+    std::map<std::string, std::vector<NodeAction>>* resolved,
+    std::set<std::string>* visiting) {
+    if (resolved->find(hint_type) != resolved->end()) {
+        return true;
+    }
+    auto composite = composites.find(hint_type);
+    if (composite == composites.end()) {
+        if (actions.find(hint_type) == actions.end()) {
+            LOG(ERROR) << "Failed to find PowerHint " << hint_type
+                       << " from Actions section";
+            return false;
+        }
+        (*resolved)[hint_type] = actions.at(hint_type);
+        return true;
+    }
+    if (!visiting->insert(hint_type).second) {
+        LOG(ERROR) << "CompositeHint " << hint_type << " includes itself";
+        return false;
+    }
+    std::vector<NodeAction> merged;
+    for (const auto& include : composite->second.includes) {
+        if (!resolveActions(include, actions, composites, resolved, visiting)) {
+            return false;
+        }
+        mergeActions(resolved->at(include), &merged);
+    }
+    if (actions.find(hint_type) != actions.end()) {
+        mergeActions(actions.at(hint_type), &merged);
+    }
+    visiting->erase(hint_type);
+    (*resolved)[hint_type] = std::move(merged);
+    return true;
+}
+
+// Describe actions by node name and value, so that actions parsed from
+// different configs can be compared.
+std::set<std::string> describeActions(
+    const std::vector<NodeAction>& actions,
+    const std::vector<std::unique_ptr<Node>>& nodes) {
+    std::set<std::string> descriptions;
+    for (const auto& action : actions) {
+        const auto& node = nodes[action.node_index];
+        descriptions.insert(node->GetName() + "=" +
+                            node->GetValues()[action.value_index] + "," +
+                            std::to_string(action.timeout_ms.count()) + "ms," +
+                            std::to_string(action.priority) + "," +
+                            ToString(action.policy));
+    }
+    return descriptions;
+}
+
+// Return the time a hint stays active, 0ms for forever.
+std::chrono::milliseconds getHintTimeout(
+    const std::vector<NodeAction>& actions) {
+    std::chrono::milliseconds timeout = std::chrono::milliseconds::zero();
+    for (const auto& action : actions) {
+        if (action.timeout_ms == std::chrono::milliseconds::zero()) {
+            return std::chrono::milliseconds::zero();
+        }
+        timeout = std::max(timeout, action.timeout_ms);
+    }
+    return timeout;
+}
+
+// Append the Modes or Boosts, named by kind, removed, changed or added from
+// old_mappings to mappings to changes.
+void diffMappings(const std::string& kind,
+                  const std::map<std::string, PowerHalMapping>& old_mappings,
+                  const std::map<std::string, PowerHalMapping>& mappings,
+                  std::vector<std::string>* changes) {
+    for (const auto& old_mapping : old_mappings) {
+        auto it = mappings.find(old_mapping.first);
+        if (it == mappings.end()) {
+            changes->emplace_back(kind + " removed: " + old_mapping.first);
+        } else if (it->second.hint_type != old_mapping.second.hint_type ||
+                   it->second.handler != old_mapping.second.handler ||
+                   it->second.duration != old_mapping.second.duration) {
+            changes->emplace_back(kind + " changed: " + old_mapping.first);
+        }
+    }
+    for (const auto& mapping : mappings) {
+        if (old_mappings.find(mapping.first) == old_mappings.end()) {
+            changes->emplace_back(kind + " added: " + mapping.first);
+        }
+    }
+}
+
+// Return when a hint done at now for timeout ends, 0ms for forever.
+ReqTime getEndTime(ReqTime now, std::chrono::milliseconds timeout) {
+    if (timeout != std::chrono::milliseconds::zero() &&
+        std::chrono::duration_cast<std::chrono::milliseconds>(
+            ReqTime::max() - now) > timeout) {
+        return now + timeout;
+    }
+    return ReqTime::max();
+}
+
+int64_t toMilliseconds(ReqTime time) {
+    return std::chrono::duration_cast<std::chrono::milliseconds>(
+               time.time_since_epoch())
+        .count();
+}
+
+}  // namespace
+
+HintManager::HintManager(
+    sp<NodeLooperThread> nm,
+    const std::map<std::string, std::vector<NodeAction>>& actions,
+    const std::map<std::string, CompositeHint>& composites,
+    const std::map<std::string, std::chrono::milliseconds>& min_intervals)
+    : nm_(std::move(nm)),
+      actions_(actions),
+      composites_(composites),
+      min_intervals_(min_intervals) {
+    UpdateTrackedHints();
+}
+
+void HintManager::UpdateTrackedHints() {
+    tracked_hints_.clear();
+    for (const auto& composite : composites_) {
+        if (composite.second.auto_activate) {
+            tracked_hints_.insert(composite.second.includes.begin(),
+                                  composite.second.includes.end());
+        }
+    }
+}
+
 bool HintManager::ValidateHint(const std::string& hint_type) const {
     if (nm_.get() == nullptr) {
         LOG(ERROR) << "NodeLooperThread not present";
         return false;
     }
-    return IsHintSupported(hint_type);
+    if (actions_.find(hint_type) == actions_.end()) {
+        LOG(INFO) << "Hint type not present in actions: " << hint_type;
+        return false;
+    }
+    return true;
 }
 
 bool HintManager::IsHintSupported(const std::string& hint_type) const {
+    std::lock_guard<std::mutex> lock(lock_);
     if (actions_.find(hint_type) == actions_.end()) {
         LOG(INFO) << "Hint type not present in actions: " << hint_type;
         return false;
@@ -50,18 +231,36 @@ bool HintManager::IsHintSupported(const std::string& hint_type) const {
 
 bool HintManager::DoHint(const std::string& hint_type) {
     LOG(VERBOSE) << "Do Powerhint: " << hint_type;
-    return ValidateHint(hint_type)
-               ? nm_->Request(actions_.at(hint_type), hint_type)
-               : false;
+    std::lock_guard<std::mutex> lock(lock_);
+    if (!ValidateHint(hint_type)) {
+        return false;
+    }
+    if (IsRateLimited(hint_type, getHintTimeout(actions_.at(hint_type)))) {
+        return true;
+    }
+    RecordHintStart(hint_type, getHintTimeout(actions_.at(hint_type)));
+    if (tracked_hints_.find(hint_type) == tracked_hints_.end()) {
+        return nm_->Request(actions_.at(hint_type), hint_type);
+    }
+    return RequestTrackedHint(hint_type, std::nullopt);
 }
 
 bool HintManager::DoHint(const std::string& hint_type,
                          std::chrono::milliseconds timeout_ms_override) {
     LOG(VERBOSE) << "Do Powerhint: " << hint_type << " for "
                  << timeout_ms_override.count() << "ms";
+    std::lock_guard<std::mutex> lock(lock_);
     if (!ValidateHint(hint_type)) {
         return false;
     }
+    if (IsRateLimited(hint_type, timeout_ms_override)) {
+        return true;
+    }
+    RecordHintStart(hint_type, timeout_ms_override);
+    if (tracked_hints_.find(hint_type) != tracked_hints_.end()) {
+        return RequestTrackedHint(
+            hint_type, getEndTime(nm_->Now(), timeout_ms_override));
+    }
     std::vector<NodeAction> actions_override = actions_.at(hint_type);
     for (auto& action : actions_override) {
         action.timeout_ms = timeout_ms_override;
@@ -71,9 +270,175 @@ bool HintManager::DoHint(const std::string& hint_type,
 
 bool HintManager::EndHint(const std::string& hint_type) {
     LOG(VERBOSE) << "End Powerhint: " << hint_type;
-    return ValidateHint(hint_type)
-               ? nm_->Cancel(actions_.at(hint_type), hint_type)
-               : false;
+    std::lock_guard<std::mutex> lock(lock_);
+    if (!ValidateHint(hint_type)) {
+        return false;
+    }
+    RecordHintEnd(hint_type);
+    if (tracked_hints_.find(hint_type) == tracked_hints_.end()) {
+        return nm_->Cancel(actions_.at(hint_type), hint_type);
+    }
+    requested_hints_.erase(hint_type);
+    // Cancel the hint if active, as it is dropped from active_hints_
+    bool ret = nm_->Cancel(actions_.at(hint_type), hint_type);
+    active_hints_.erase(hint_type);
+    return UpdateCompositeHints() && ret;
+}
+
+bool HintManager::RequestTrackedHint(const std::string& hint_type,
+                                     std::optional<ReqTime> end_time) {
+    auto it = requested_hints_.find(hint_type);
+    if (it == requested_hints_.end()) {
+        requested_hints_.emplace(hint_type, end_time);
+    } else if (it->second && (!end_time || *end_time > *it->second)) {
+        it->second = end_time;
+    }
+    bool ret = true;
+    if (active_hints_.find(hint_type) != active_hints_.end()) {
+        // Extend the requests of the hint already active
+        ret = nm_->Request(GetRequestActions(hint_type, nm_->Now()), hint_type);
+    }
+    return UpdateCompositeHints() && ret;
+}
+
+std::vector<NodeAction> HintManager::GetRequestActions(
+    const std::string& hint_type, ReqTime now) const {
+    std::vector<NodeAction> actions = actions_.at(hint_type);
+    auto it = requested_hints_.find(hint_type);
+    if (it == requested_hints_.end() || !it->second) {
+        return actions;
+    }
+    std::chrono::milliseconds timeout = std::chrono::milliseconds::zero();
+    if (*it->second != ReqTime::max()) {
+        timeout = std::max(
+            std::chrono::ceil<std::chrono::milliseconds>(*it->second - now),
+            std::chrono::milliseconds(1));
+    }
+    for (auto& action : actions) {
+        action.timeout_ms = timeout;
+    }
+    return actions;
+}
+
+void HintManager::ScheduleExpire() {
+    ReqTime expire_time = ReqTime::max();
+    for (const auto& requested : requested_hints_) {
+        if (requested.second) {
+            expire_time = std::min(expire_time, *requested.second);
+        }
+    }
+    if (expire_time == expire_time_) {
+        return;
+    }
+    expire_time_ = expire_time;
+    nm_->ScheduleCallback(expire_time, [this] {
+        std::lock_guard<std::mutex> lock(lock_);
+        expire_time_ = ReqTime::max();
+        UpdateCompositeHints();
+    });
+}
+
+bool HintManager::UpdateCompositeHints() {
+    ReqTime now = nm_->Now();
+    std::set<std::string> resolved;
+    for (auto it = requested_hints_.begin(); it != requested_hints_.end();) {
+        // Drop the hints done with a timeout override that ended
+        if (it->second && *it->second <= now) {
+            it = requested_hints_.erase(it);
+            continue;
+        }
+        resolved.insert(it->first);
+        ++it;
+    }
+    for (const auto& composite : composites_) {
+        const auto& includes = composite.second.includes;
+        if (!composite.second.auto_activate ||
+            !std::all_of(includes.begin(), includes.end(),
+                         [this](const std::string& include) {
+                             return requested_hints_.count(include) > 0;
+                         })) {
+            continue;
+        }
+        resolved.insert(composite.first);
+        for (const auto& include : includes) {
+            resolved.erase(include);
+        }
+    }
+
+    bool ret = true;
+    for (const auto& hint_type : active_hints_) {
+        if (resolved.find(hint_type) == resolved.end()) {
+            LOG(VERBOSE) << "Suspend Powerhint: " << hint_type;
+            if (requested_hints_.find(hint_type) == requested_hints_.end()) {
+                RecordHintEnd(hint_type);
+            }
+            ret = nm_->Cancel(actions_.at(hint_type), hint_type) && ret;
+        }
+    }
+    for (const auto& hint_type : resolved) {
+        if (active_hints_.find(hint_type) == active_hints_.end()) {
+            LOG(VERBOSE) << "Activate Powerhint: " << hint_type;
+            if (requested_hints_.find(hint_type) == requested_hints_.end()) {
+                RecordHintStart(hint_type,
+                                getHintTimeout(actions_.at(hint_type)));
+            }
+            ret = nm_->Request(GetRequestActions(hint_type, now), hint_type) &&
+                  ret;
+        }
+    }
+    active_hints_ = std::move(resolved);
+    ScheduleExpire();
+    return ret;
+}
+
+void HintManager::RecordHintStart(const std::string& hint_type,
+                                  std::chrono::milliseconds timeout) {
+    ReqTime now = nm_->Now();
+    HintStats& stats = hint_stats_[hint_type];
+    ++stats.count;
+    stats.time_requested = now;
+    if (stats.time_ended <= now) {
+        // Fold the last activation and start a new one
+        stats.duration += std::chrono::duration_cast<std::chrono::milliseconds>(
+            stats.time_ended - stats.time_started);
+        stats.time_started = now;
+        stats.time_ended = now;
+    }
+    stats.time_ended = std::max(stats.time_ended, getEndTime(now, timeout));
+}
+
+bool HintManager::IsRateLimited(const std::string& hint_type,
+                                std::chrono::milliseconds timeout) {
+    auto interval = min_intervals_.find(hint_type);
+    auto stats = hint_stats_.find(hint_type);
+    if (interval == min_intervals_.end() || stats == hint_stats_.end()) {
+        return false;
+    }
+    ReqTime now = nm_->Now();
+    if (stats->second.time_ended <= now ||
+        now - stats->second.time_requested >= interval->second) {
+        return false;
+    }
+    // A call extending the active hint by more than what dropping it within
+    // the interval costs, e.g. a longer boost, is let through to be coalesced.
+    ReqTime end_time = getEndTime(now, timeout);
+    if (stats->second.time_ended != ReqTime::max() &&
+        (end_time == ReqTime::max() ||
+         end_time - stats->second.time_ended > interval->second)) {
+        return false;
+    }
+    LOG(VERBOSE) << "Rate limit Powerhint: " << hint_type;
+    ++stats->second.rate_limited;
+    return true;
+}
+
+void HintManager::RecordHintEnd(const std::string& hint_type) {
+    auto it = hint_stats_.find(hint_type);
+    if (it == hint_stats_.end()) {
+        return;
+    }
+    it->second.time_ended =
+        std::min(it->second.time_ended, nm_->Now());
 }
 
 bool HintManager::IsRunning() const {
@@ -81,6 +446,7 @@ bool HintManager::IsRunning() const {
 }
 
 std::vector<std::string> HintManager::GetHints() const {
+    std::lock_guard<std::mutex> lock(lock_);
     std::vector<std::string> hints;
     for (auto const& action : actions_) {
         hints.push_back(action.first);
@@ -88,6 +454,28 @@ std::vector<std::string> HintManager::GetHints() const {
     return hints;
 }
 
+bool HintManager::GetModeMapping(const std::string& mode,
+                                 PowerHalMapping* mapping) const {
+    std::lock_guard<std::mutex> lock(lock_);
+    auto it = modes_.find(mode);
+    if (it == modes_.end()) {
+        return false;
+    }
+    *mapping = it->second;
+    return true;
+}
+
+bool HintManager::GetBoostMapping(const std::string& boost,
+                                  PowerHalMapping* mapping) const {
+    std::lock_guard<std::mutex> lock(lock_);
+    auto it = boosts_.find(boost);
+    if (it == boosts_.end()) {
+        return false;
+    }
+    *mapping = it->second;
+    return true;
+}
+
 void HintManager::DumpToFd(int fd) {
     std::string header(
         "========== Begin perfmgr nodes ==========\n"
@@ -103,6 +491,84 @@ void HintManager::DumpToFd(int fd) {
     if (!android::base::WriteStringToFd(footer, fd)) {
         LOG(ERROR) << "Failed to dump fd: " << fd;
     }
+    std::lock_guard<std::mutex> lock(lock_);
+    if (!composites_.empty()) {
+        std::ostringstream dump_buf;
+        dump_buf << "========== Begin perfmgr composite hints ==========\n"
+                 << "PowerHint\tIncludes\tAutoActivate\tActive\n";
+        for (const auto& composite : composites_) {
+            dump_buf << composite.first << "\t"
+                     << android::base::Join(composite.second.includes, ",")
+                     << "\t" << std::boolalpha
+                     << composite.second.auto_activate << "\t"
+                     << (active_hints_.count(composite.first) > 0)
+                     << std::noboolalpha << "\n";
+        }
+        dump_buf << "==========  End perfmgr composite hints  ==========\n";
+        if (!android::base::WriteStringToFd(dump_buf.str(), fd)) {
+            LOG(ERROR) << "Failed to dump fd: " << fd;
+        }
+    }
+    if (!modes_.empty() || !boosts_.empty()) {
+        std::ostringstream dump_buf;
+        dump_buf << "========== Begin perfmgr power hal mappings ==========\n"
+                 << "Type\tName\tPowerHint\tHandler\tDuration(ms)\n";
+        for (const auto& mappings : {std::make_pair("Mode", &modes_),
+                                     std::make_pair("Boost", &boosts_)}) {
+            for (const auto& mapping : *mappings.second) {
+                dump_buf << mappings.first << "\t" << mapping.first << "\t"
+                         << mapping.second.hint_type << "\t"
+                         << mapping.second.handler << "\t"
+                         << mapping.second.duration.count() << "\n";
+            }
+        }
+        dump_buf << "==========  End perfmgr power hal mappings  ==========\n";
+        if (!android::base::WriteStringToFd(dump_buf.str(), fd)) {
+            LOG(ERROR) << "Failed to dump fd: " << fd;
+        }
+    }
+
+    std::map<std::string, std::map<std::string, uint64_t>> override_counts =
+        nm_->GetOverrideCounts();
+    std::map<std::string, uint64_t> coalesced_counts =
+        nm_->GetCoalescedCounts();
+    ReqTime now = nm_->Now();
+    std::ostringstream dump_buf;
+    dump_buf << "========== Begin perfmgr hint stats ==========\n"
+             << "PowerHint\tCount\tDuration(ms)\tLastStart(ms)\tLastEnd(ms)\t"
+             << "Overridden\tCoalesced\tRateLimited\n";
+    for (const auto& action : actions_) {
+        HintStats stats;
+        auto it = hint_stats_.find(action.first);
+        if (it != hint_stats_.end()) {
+            stats = it->second;
+        }
+        bool active = stats.time_ended > now;
+        auto duration =
+            stats.duration +
+            std::chrono::duration_cast<std::chrono::milliseconds>(
+                (active ? now : stats.time_ended) - stats.time_started);
+        std::vector<std::string> overrides;
+        for (const auto& count : override_counts[action.first]) {
+            overrides.emplace_back(count.first + ":" +
+                                   std::to_string(count.second));
+        }
+        dump_buf << action.first << "\t" << stats.count << "\t"
+                 << duration.count() << "\t"
+                 << toMilliseconds(stats.time_started) << "\t";
+        if (active) {
+            dump_buf << "-";
+        } else {
+            dump_buf << toMilliseconds(stats.time_ended);
+        }
+        dump_buf << "\t" << android::base::Join(overrides, ",") << "\t"
+                 << coalesced_counts[action.first] << "\t"
+                 << stats.rate_limited << "\n";
+    }
+    dump_buf << "==========  End perfmgr hint stats  ==========\n";
+    if (!android::base::WriteStringToFd(dump_buf.str(), fd)) {
+        LOG(ERROR) << "Failed to dump fd: " << fd;
+    }
     fsync(fd);
 }
 
@@ -110,31 +576,74 @@ bool HintManager::Start() {
     return nm_->Start();
 }
 
-std::unique_ptr<HintManager> HintManager::GetFromJSON(
-    const std::string& config_path, bool start) {
+bool HintManager::ParseConfig(
+    const std::string& config_path, std::vector<std::unique_ptr<Node>>* nodes,
+    std::map<std::string, std::vector<NodeAction>>* actions,
+    std::map<std::string, CompositeHint>* composites,
+    std::map<std::string, PowerHalMapping>* modes,
+    std::map<std::string, PowerHalMapping>* boosts,
+    std::map<std::string, std::chrono::milliseconds>* min_intervals) {
     std::string json_doc;
 
     if (!android::base::ReadFileToString(config_path, &json_doc)) {
         LOG(ERROR) << "Failed to read JSON config from " << config_path;
-        return nullptr;
+        return false;
     }
 
-    std::vector<std::unique_ptr<Node>> nodes = ParseNodes(json_doc);
-    if (nodes.empty()) {
+    *nodes = ParseNodes(json_doc);
+    if (nodes->empty()) {
         LOG(ERROR) << "Failed to parse Nodes section from " << config_path;
-        return nullptr;
+        return false;
     }
-    std::map<std::string, std::vector<NodeAction>> actions =
-        HintManager::ParseActions(json_doc, nodes);
+    *actions = HintManager::ParseActions(json_doc, *nodes);
 
-    if (actions.empty()) {
+    if (actions->empty()) {
         LOG(ERROR) << "Failed to parse Actions section from " << config_path;
+        return false;
+    }
+
+    if (!HintManager::ParseCompositeHints(json_doc, actions, composites)) {
+        LOG(ERROR) << "Failed to parse CompositeHints section from "
+                   << config_path;
+        return false;
+    }
+
+    if (!HintManager::ParsePowerHalMappings(json_doc, "Modes", *actions,
+                                            modes) ||
+        !HintManager::ParsePowerHalMappings(json_doc, "Boosts", *actions,
+                                            boosts)) {
+        LOG(ERROR) << "Failed to parse Modes and Boosts sections from "
+                   << config_path;
+        return false;
+    }
+
+    if (!HintManager::ParseRateLimits(json_doc, *actions, min_intervals)) {
+        LOG(ERROR) << "Failed to parse RateLimits section from "
+                   << config_path;
+        return false;
+    }
+    return true;
+}
+
+std::unique_ptr<HintManager> HintManager::GetFromJSON(
+    const std::string& config_path, bool start) {
+    std::vector<std::unique_ptr<Node>> nodes;
+    std::map<std::string, std::vector<NodeAction>> actions;
+    std::map<std::string, CompositeHint> composites;
+    std::map<std::string, PowerHalMapping> modes;
+    std::map<std::string, PowerHalMapping> boosts;
+    std::map<std::string, std::chrono::milliseconds> min_intervals;
+    if (!ParseConfig(config_path, &nodes, &actions, &composites, &modes,
+                     &boosts, &min_intervals)) {
         return nullptr;
     }
 
     sp<NodeLooperThread> nm = new NodeLooperThread(std::move(nodes));
-    std::unique_ptr<HintManager> hm =
-        std::make_unique<HintManager>(std::move(nm), actions);
+    std::unique_ptr<HintManager> hm = std::make_unique<HintManager>(
+        std::move(nm), actions, composites, min_intervals);
+    hm->config_path_ = config_path;
+    hm->modes_ = std::move(modes);
+    hm->boosts_ = std::move(boosts);
 
     LOG(INFO) << "Initialized HintManager from JSON config: " << config_path;
 
@@ -144,12 +653,115 @@ std::unique_ptr<HintManager> HintManager::GetFromJSON(
     return hm;
 }
 
+bool HintManager::ReloadConfig(std::string* diff) {
+    if (config_path_.empty() || nm_.get() == nullptr) {
+        LOG(ERROR) << "HintManager not initialized from JSON config";
+        return false;
+    }
+    std::vector<std::unique_ptr<Node>> nodes;
+    std::map<std::string, std::vector<NodeAction>> actions;
+    std::map<std::string, CompositeHint> composites;
+    std::map<std::string, PowerHalMapping> modes;
+    std::map<std::string, PowerHalMapping> boosts;
+    std::map<std::string, std::chrono::milliseconds> min_intervals;
+    if (!ParseConfig(config_path_, &nodes, &actions, &composites, &modes,
+                     &boosts, &min_intervals)) {
+        LOG(ERROR) << "Failed to reload JSON config: " << config_path_;
+        return false;
+    }
+
+    std::map<std::string, std::map<std::string, std::vector<NodeAction>>>
+        node_actions;
+    std::map<std::string, std::set<std::string>> descriptions;
+    for (const auto& action : actions) {
+        for (const auto& a : action.second) {
+            node_actions[nodes[a.node_index]->GetName()][action.first]
+                .push_back(a);
+        }
+        descriptions[action.first] = describeActions(action.second, nodes);
+    }
+
+    std::lock_guard<std::mutex> lock(lock_);
+    std::vector<std::string> changes;
+    std::vector<std::unique_ptr<Node>> old_nodes =
+        nm_->ReloadNodes(std::move(nodes), node_actions, &changes);
+    for (const auto& action : actions_) {
+        auto it = descriptions.find(action.first);
+        if (it == descriptions.end()) {
+            changes.emplace_back("PowerHint removed: " + action.first);
+            continue;
+        }
+        if (it->second != describeActions(action.second, old_nodes)) {
+            changes.emplace_back("PowerHint changed: " + action.first);
+        }
+        descriptions.erase(it);
+    }
+    for (const auto& description : descriptions) {
+        changes.emplace_back("PowerHint added: " + description.first);
+    }
+    diffMappings("Mode", modes_, modes, &changes);
+    diffMappings("Boost", boosts_, boosts, &changes);
+
+    actions_ = std::move(actions);
+    composites_ = std::move(composites);
+    modes_ = std::move(modes);
+    boosts_ = std::move(boosts);
+    min_intervals_ = std::move(min_intervals);
+    UpdateTrackedHints();
+    // Hints no longer tracked are left to DoHint and EndHint: a requested hint
+    // that was suspended is requested again, and a composite no longer
+    // AutoActivate is ended.
+    bool ret = true;
+    ReqTime now = nm_->Now();
+    for (auto it = requested_hints_.begin(); it != requested_hints_.end();) {
+        const std::string& hint_type = it->first;
+        if (tracked_hints_.find(hint_type) != tracked_hints_.end()) {
+            ++it;
+            continue;
+        }
+        if (actions_.find(hint_type) != actions_.end() &&
+            active_hints_.find(hint_type) == active_hints_.end() &&
+            (!it->second || *it->second > now)) {
+            ret = nm_->Request(GetRequestActions(hint_type, now), hint_type) &&
+                  ret;
+        }
+        active_hints_.erase(hint_type);
+        it = requested_hints_.erase(it);
+    }
+    for (auto it = active_hints_.begin(); it != active_hints_.end();) {
+        auto composite = composites_.find(*it);
+        if (requested_hints_.find(*it) != requested_hints_.end() ||
+            (composite != composites_.end() &&
+             composite->second.auto_activate)) {
+            ++it;
+            continue;
+        }
+        if (actions_.find(*it) != actions_.end()) {
+            ret = nm_->Cancel(actions_.at(*it), *it) && ret;
+        }
+        it = active_hints_.erase(it);
+    }
+    ret = UpdateCompositeHints() && ret;
+
+    if (changes.empty()) {
+        changes.emplace_back("No change");
+    }
+    for (const auto& change : changes) {
+        LOG(INFO) << "Reload JSON config: " << change;
+    }
+    *diff = android::base::Join(changes, "\n") + "\n";
+    LOG(INFO) << "Reloaded HintManager from JSON config: " << config_path_;
+    return ret;
+}
+
 std::vector<std::unique_ptr<Node>> HintManager::ParseNodes(
     const std::string& json_doc) {
     // function starts
     std::vector<std::unique_ptr<Node>> nodes_parsed;
     std::set<std::string> nodes_name_parsed;
     std::set<std::string> nodes_path_parsed;
+    // names of the nodes each node depends on
+    std::map<std::string, std::vector<std::string>> depends_on;
     Json::Value root;
     Json::Reader reader;
 
@@ -193,6 +805,7 @@ std::vector<std::unique_ptr<Node>> HintManager::ParseNodes(
         }
 
         bool is_file = true;
+        bool is_cgroup = false;
         std::string node_type = nodes[i]["Type"].asString();
         LOG(VERBOSE) << "Node[" << i << "]'s Type: " << node_type;
         if (node_type.empty()) {
@@ -202,9 +815,12 @@ std::vector<std::unique_ptr<Node>> HintManager::ParseNodes(
             is_file = true;
         } else if (node_type == "Property") {
             is_file = false;
+        } else if (node_type == "Cgroup") {
+            is_file = false;
+            is_cgroup = true;
         } else {
             LOG(ERROR) << "Invalid Node[" << i
-                       << "]'s Type: only File and Property supported.";
+                       << "]'s Type: only File, Property and Cgroup supported.";
             nodes_parsed.clear();
             return nodes_parsed;
         }
@@ -222,7 +838,7 @@ std::vector<std::unique_ptr<Node>> HintManager::ParseNodes(
                 nodes_parsed.clear();
                 return nodes_parsed;
             }
-            if (is_file && value.empty()) {
+            if ((is_file || is_cgroup) && value.empty()) {
                 LOG(ERROR) << "Failed to read Node[" << i << "]'s Value[" << j
                            << "]";
                 nodes_parsed.clear();
@@ -266,6 +882,20 @@ std::vector<std::unique_ptr<Node>> HintManager::ParseNodes(
         LOG(VERBOSE) << "Node[" << i << "]'s ResetOnInit: " << std::boolalpha
                      << reset << std::noboolalpha;
 
+        Json::Value deps = nodes[i]["DependsOn"];
+        for (Json::Value::ArrayIndex j = 0; j < deps.size(); ++j) {
+            std::string dep = deps[j].asString();
+            LOG(VERBOSE) << "Node[" << i << "]'s DependsOn[" << j
+                         << "]: " << dep;
+            if (dep.empty() || dep == name) {
+                LOG(ERROR) << "Failed to read Node[" << i << "]'s DependsOn["
+                           << j << "]";
+                nodes_parsed.clear();
+                return nodes_parsed;
+            }
+            depends_on[name].emplace_back(dep);
+        }
+
         if (is_file) {
             bool hold_fd = false;
             if (nodes[i]["HoldFd"].empty() || !nodes[i]["HoldFd"].isBool()) {
@@ -277,17 +907,86 @@ std::vector<std::unique_ptr<Node>> HintManager::ParseNodes(
             LOG(VERBOSE) << "Node[" << i << "]'s HoldFd: " << std::boolalpha
                          << hold_fd << std::noboolalpha;
 
+            bool verify_write = false;
+            if (nodes[i]["VerifyWrite"].empty() ||
+                !nodes[i]["VerifyWrite"].isBool()) {
+                LOG(INFO) << "Failed to read Node[" << i
+                          << "]'s VerifyWrite, set to 'false'";
+            } else {
+                verify_write = nodes[i]["VerifyWrite"].asBool();
+            }
+            LOG(VERBOSE) << "Node[" << i << "]'s VerifyWrite: "
+                         << std::boolalpha << verify_write << std::noboolalpha;
+
+            Json::UInt write_retries = 0;
+            if (!nodes[i]["WriteRetries"].empty()) {
+                if (!nodes[i]["WriteRetries"].isUInt()) {
+                    LOG(ERROR) << "Failed to read Node[" << i
+                               << "]'s WriteRetries";
+                    nodes_parsed.clear();
+                    return nodes_parsed;
+                }
+                write_retries = nodes[i]["WriteRetries"].asUInt();
+            }
+            LOG(VERBOSE) << "Node[" << i
+                         << "]'s WriteRetries: " << write_retries;
+
             nodes_parsed.emplace_back(std::make_unique<FileNode>(
                 name, path, values_parsed,
-                static_cast<std::size_t>(default_index), reset, hold_fd));
+                static_cast<std::size_t>(default_index), reset, hold_fd,
+                verify_write, write_retries));
+        } else if (is_cgroup) {
+            nodes_parsed.emplace_back(std::make_unique<CgroupNode>(
+                name, path, values_parsed,
+                static_cast<std::size_t>(default_index), reset));
         } else {
             nodes_parsed.emplace_back(std::make_unique<PropertyNode>(
                 name, path, values_parsed,
                 static_cast<std::size_t>(default_index), reset));
         }
     }
-    LOG(INFO) << nodes_parsed.size() << " Nodes parsed successfully";
-    return nodes_parsed;
+
+    for (const auto& deps : depends_on) {
+        for (const auto& dep : deps.second) {
+            if (nodes_name_parsed.find(dep) == nodes_name_parsed.end()) {
+                LOG(ERROR) << "Failed to find Node " << deps.first
+                           << "'s DependsOn from Nodes section: [" << dep
+                           << "]";
+                nodes_parsed.clear();
+                return nodes_parsed;
+            }
+        }
+    }
+
+    // Order nodes after the nodes they depend on, e.g. cpufreq min after max,
+    // and otherwise as in the config.
+    std::vector<std::unique_ptr<Node>> nodes_ordered;
+    std::set<std::string> nodes_name_ordered;
+    while (nodes_ordered.size() < nodes_parsed.size()) {
+        auto it = std::find_if(
+            nodes_parsed.begin(), nodes_parsed.end(),
+            [&](const std::unique_ptr<Node>& n) {
+                if (n == nullptr) {
+                    return false;
+                }
+                const auto& deps = depends_on[n->GetName()];
+                return std::all_of(deps.begin(), deps.end(),
+                                   [&](const std::string& dep) {
+                                       return nodes_name_ordered.find(dep) !=
+                                              nodes_name_ordered.end();
+                                   });
+            });
+        if (it == nodes_parsed.end()) {
+            LOG(ERROR) << "Nodes' DependsOn forms a cycle";
+            nodes_parsed.clear();
+            return nodes_parsed;
+        }
+        nodes_name_ordered.insert((*it)->GetName());
+        nodes_ordered.emplace_back(std::move(*it));
+    }
+
+    LOG(INFO) << nodes_ordered.size() << " Nodes parsed successfully";
+    return nodes_ordered;
 }
 
 std::map<std::string, std::vector<NodeAction>> HintManager::ParseActions(
@@ -358,9 +1057,38 @@ std::map<std::string, std::vector<NodeAction>> HintManager::ParseActions(
         }
         LOG(VERBOSE) << "Action[" << i << "]'s Duration: " << duration;
 
+        Json::Int priority = 0;
+        if (!actions[i]["Priority"].empty()) {
+            if (!actions[i]["Priority"].isInt()) {
+                LOG(ERROR) << "Failed to read Action[" << i << "]'s Priority";
+                actions_parsed.clear();
+                return actions_parsed;
+            }
+            priority = actions[i]["Priority"].asInt();
+        }
+        LOG(VERBOSE) << "Action[" << i << "]'s Priority: " << priority;
+
+        RequestPolicy policy = RequestPolicy::kMaxWins;
+        std::string policy_name = actions[i]["Policy"].asString();
+        if (policy_name.empty() || policy_name == "MaxWins") {
+            policy = RequestPolicy::kMaxWins;
+        } else if (policy_name == "MinWins") {
+            policy = RequestPolicy::kMinWins;
+        } else if (policy_name == "Exclusive") {
+            policy = RequestPolicy::kExclusive;
+        } else {
+            LOG(ERROR) << "Invalid Action[" << i << "]'s Policy: "
+                       << policy_name
+                       << ", only MaxWins, MinWins and Exclusive supported.";
+            actions_parsed.clear();
+            return actions_parsed;
+        }
+        LOG(VERBOSE) << "Action[" << i << "]'s Policy: " << ToString(policy);
+
         if (actions_parsed.find(hint_type) == actions_parsed.end()) {
             actions_parsed[hint_type] = std::vector<NodeAction>{
-                {node_index, value_index, std::chrono::milliseconds(duration)}};
+                {node_index, value_index, std::chrono::milliseconds(duration),
+                 priority, policy}};
         } else {
             for (const auto& action : actions_parsed[hint_type]) {
                 if (action.node_index == node_index) {
@@ -372,7 +1100,8 @@ std::map<std::string, std::vector<NodeAction>> HintManager::ParseActions(
                 }
             }
             actions_parsed[hint_type].emplace_back(
-                node_index, value_index, std::chrono::milliseconds(duration));
+                node_index, value_index, std::chrono::milliseconds(duration),
+                priority, policy);
         }
 
         ++total_parsed;
@@ -388,5 +1117,234 @@ std::map<std::string, std::vector<NodeAction>> HintManager::ParseActions(
     return actions_parsed;
 }
 
+bool HintManager::ParseCompositeHints(
+    const std::string& json_doc,
+    std::map<std::string, std::vector<NodeAction>>* actions,
+    std::map<std::string, CompositeHint>* composites) {
+    // function starts
+    std::map<std::string, CompositeHint> composites_parsed;
+    Json::Value root;
+    Json::Reader reader;
+
+    if (!reader.parse(json_doc, root)) {
+        LOG(ERROR) << "Failed to parse JSON config";
+        return false;
+    }
+
+    Json::Value composite_hints = root["CompositeHints"];
+    for (Json::Value::ArrayIndex i = 0; i < composite_hints.size(); ++i) {
+        const std::string& hint_type =
+            composite_hints[i]["PowerHint"].asString();
+        LOG(VERBOSE) << "CompositeHint[" << i << "]'s PowerHint: " << hint_type;
+        if (hint_type.empty()) {
+            LOG(ERROR) << "Failed to read "
+                       << "CompositeHint[" << i << "]'s PowerHint";
+            return false;
+        }
+        if (composites_parsed.find(hint_type) != composites_parsed.end()) {
+            LOG(ERROR) << "Duplicate CompositeHint[" << i << "]'s PowerHint";
+            return false;
+        }
+
+        CompositeHint composite;
+        std::set<std::string> includes_parsed;
+        Json::Value includes = composite_hints[i]["Includes"];
+        for (Json::Value::ArrayIndex j = 0; j < includes.size(); ++j) {
+            std::string include = includes[j].asString();
+            LOG(VERBOSE) << "CompositeHint[" << i << "]'s Include[" << j
+                         << "]: " << include;
+            if (include.empty() || include == hint_type) {
+                LOG(ERROR) << "Failed to read CompositeHint[" << i
+                           << "]'s Include[" << j << "]";
+                return false;
+            }
+            if (!includes_parsed.insert(include).second) {
+                LOG(ERROR) << "Duplicate include parsed in CompositeHint[" << i
+                           << "]'s Include[" << j << "]";
+                return false;
+            }
+            composite.includes.emplace_back(include);
+        }
+        if (composite.includes.empty()) {
+            LOG(ERROR) << "Failed to read CompositeHint[" << i
+                       << "]'s Includes";
+            return false;
+        }
+
+        composite.auto_activate = false;
+        if (composite_hints[i]["AutoActivate"].empty() ||
+            !composite_hints[i]["AutoActivate"].isBool()) {
+            LOG(INFO) << "Failed to read CompositeHint[" << i
+                      << "]'s AutoActivate, set to 'false'";
+        } else {
+            composite.auto_activate =
+                composite_hints[i]["AutoActivate"].asBool();
+        }
+        LOG(VERBOSE) << "CompositeHint[" << i << "]'s AutoActivate: "
+                     << std::boolalpha << composite.auto_activate
+                     << std::noboolalpha;
+
+        composites_parsed[hint_type] = std::move(composite);
+    }
+
+    std::map<std::string, std::vector<NodeAction>> resolved;
+    for (const auto& composite : composites_parsed) {
+        std::set<std::string> visiting;
+        if (!resolveActions(composite.first, *actions, composites_parsed,
+                            &resolved, &visiting)) {
+            LOG(ERROR) << "Failed to resolve CompositeHint " << composite.first;
+            return false;
+        }
+    }
+    for (auto& hint : resolved) {
+        (*actions)[hint.first] = std::move(hint.second);
+    }
+
+    LOG(INFO) << composites_parsed.size()
+              << " CompositeHints parsed successfully";
+    *composites = std::move(composites_parsed);
+    return true;
+}
+
+bool HintManager::ParsePowerHalMappings(
+    const std::string& json_doc, const std::string& section,
+    const std::map<std::string, std::vector<NodeAction>>& actions,
+    std::map<std::string, PowerHalMapping>* mappings) {
+    // function starts
+    std::map<std::string, PowerHalMapping> mappings_parsed;
+    Json::Value root;
+    Json::Reader reader;
+
+    if (!reader.parse(json_doc, root)) {
+        LOG(ERROR) << "Failed to parse JSON config";
+        return false;
+    }
+
+    auto power_hal_section = kPowerHalSections.find(section);
+    if (power_hal_section == kPowerHalSections.end()) {
+        LOG(ERROR) << "Unknown PowerHAL section " << section;
+        return false;
+    }
+    const std::set<std::string>& names = power_hal_section->second.names;
+    const std::set<std::string>& handlers = power_hal_section->second.handlers;
+
+    Json::Value entries = root[section];
+    for (Json::Value::ArrayIndex i = 0; i < entries.size(); ++i) {
+        const std::string& name = entries[i]["Name"].asString();
+        LOG(VERBOSE) << section << "[" << i << "]'s Name: " << name;
+        if (name.empty()) {
+            LOG(ERROR) << "Failed to read " << section << "[" << i
+                       << "]'s Name";
+            return false;
+        }
+        if (names.find(name) == names.end()) {
+            LOG(ERROR) << section << "[" << i << "]'s Name " << name
+                       << " is not a PowerHAL " << section;
+            return false;
+        }
+        if (mappings_parsed.find(name) != mappings_parsed.end()) {
+            LOG(ERROR) << "Duplicate " << section << "[" << i << "]'s Name";
+            return false;
+        }
+
+        PowerHalMapping mapping;
+        mapping.hint_type = entries[i]["PowerHint"].asString();
+        if (mapping.hint_type.empty()) {
+            LOG(INFO) << "Failed to read " << section << "[" << i
+                      << "]'s PowerHint, set to '" << name << "'";
+            mapping.hint_type = name;
+        }
+        LOG(VERBOSE) << section << "[" << i
+                     << "]'s PowerHint: " << mapping.hint_type;
+
+        mapping.handler = entries[i]["Handler"].asString();
+        LOG(VERBOSE) << section << "[" << i
+                     << "]'s Handler: " << mapping.handler;
+        if (!mapping.handler.empty() &&
+            handlers.find(mapping.handler) == handlers.end()) {
+            LOG(ERROR) << section << "[" << i << "]'s Handler "
+                       << mapping.handler << " is not implemented for "
+                       << section;
+            return false;
+        }
+        if (mapping.handler.empty() &&
+            actions.find(mapping.hint_type) == actions.end()) {
+            LOG(ERROR) << "Failed to find " << section << "[" << i
+                       << "]'s PowerHint " << mapping.hint_type
+                       << " from Actions section";
+            return false;
+        }
+
+        mapping.duration = std::chrono::milliseconds::zero();
+        if (!entries[i]["Duration"].empty()) {
+            if (!entries[i]["Duration"].isUInt()) {
+                LOG(ERROR) << "Failed to read " << section << "[" << i
+                           << "]'s Duration";
+                return false;
+            }
+            mapping.duration = std::chrono::milliseconds(
+                entries[i]["Duration"].asUInt());
+        }
+        LOG(VERBOSE) << section << "[" << i
+                     << "]'s Duration: " << mapping.duration.count();
+
+        mappings_parsed[name] = std::move(mapping);
+    }
+
+    LOG(INFO) << mappings_parsed.size() << " " << section
+              << " parsed successfully";
+    *mappings = std::move(mappings_parsed);
+    return true;
+}
+
+bool HintManager::ParseRateLimits(
+    const std::string& json_doc,
+    const std::map<std::string, std::vector<NodeAction>>& actions,
+    std::map<std::string, std::chrono::milliseconds>* min_intervals) {
+    // function starts
+    std::map<std::string, std::chrono::milliseconds> min_intervals_parsed;
+    Json::Value root;
+    Json::Reader reader;
+
+    if (!reader.parse(json_doc, root)) {
+        LOG(ERROR) << "Failed to parse JSON config";
+        return false;
+    }
+
+    Json::Value rate_limits = root["RateLimits"];
+    for (Json::Value::ArrayIndex i = 0; i < rate_limits.size(); ++i) {
+        const std::string& hint_type = rate_limits[i]["PowerHint"].asString();
+        LOG(VERBOSE) << "RateLimit[" << i << "]'s PowerHint: " << hint_type;
+        if (actions.find(hint_type) == actions.end()) {
+            LOG(ERROR) << "Failed to find RateLimit[" << i << "]'s PowerHint "
+                       << hint_type << " from Actions section";
+            return false;
+        }
+        if (min_intervals_parsed.find(hint_type) !=
+            min_intervals_parsed.end()) {
+            LOG(ERROR) << "Duplicate RateLimit[" << i << "]'s PowerHint";
+            return false;
+        }
+
+        if (rate_limits[i]["MinInterval"].empty() ||
+            !rate_limits[i]["MinInterval"].isUInt()) {
+            LOG(ERROR) << "Failed to read RateLimit[" << i
+                       << "]'s MinInterval";
+            return false;
+        }
+        Json::UInt min_interval = rate_limits[i]["MinInterval"].asUInt();
+        LOG(VERBOSE) << "RateLimit[" << i << "]'s MinInterval: "
+                     << min_interval;
+
+        min_intervals_parsed[hint_type] =
+            std::chrono::milliseconds(min_interval);
+    }
+
+    LOG(INFO) << min_intervals_parsed.size()
+              << " RateLimits parsed successfully";
+    *min_intervals = std::move(min_intervals_parsed);
+    return true;
+}
+
 }  // namespace perfmgr
 }  // namespace android
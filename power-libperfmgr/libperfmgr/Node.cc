@@ -23,6 +23,8 @@
 #include <android-base/stringprintf.h>
 #include <android-base/strings.h>
 
+#include <algorithm>
+
 namespace android {
 namespace perfmgr {
 
@@ -37,14 +39,31 @@ Node::Node(std::string name, std::string node_path,
       current_val_index_(default_val_index) {}
 
 bool Node::AddRequest(std::size_t value_index, const std::string& hint_type,
-                      ReqTime end_time) {
+                      ReqTime end_time, int priority, RequestPolicy policy) {
     if (value_index >= req_sorted_.size()) {
         LOG(ERROR) << "Value index out of bound: " << value_index
                    << " ,size: " << req_sorted_.size();
         return false;
     }
     // Add/Update request to the new end_time for the specific hint_type
-    req_sorted_[value_index].AddRequest(hint_type, end_time);
+    req_sorted_[value_index].AddRequest(hint_type, end_time, priority, policy);
+    return true;
+}
+
+bool Node::ExtendRequest(std::size_t value_index, const std::string& hint_type,
+                         ReqTime end_time, int priority, RequestPolicy policy) {
+    if (value_index >= req_sorted_.size()) {
+        return false;
+    }
+    const auto& requests = req_sorted_[value_index].GetRequests();
+    auto it = requests.find(hint_type);
+    if (it == requests.end() ||
+        it->second.end_time <= std::chrono::steady_clock::now() ||
+        it->second.priority != priority || it->second.policy != policy) {
+        return false;
+    }
+    // Keeps the later end time, so a request already covered is a no-op
+    req_sorted_[value_index].AddRequest(hint_type, end_time, priority, policy);
     return true;
 }
 
@@ -57,6 +76,119 @@ bool Node::RemoveRequest(const std::string& hint_type) {
     return ret;
 }
 
+std::size_t Node::SelectValueIndex(std::chrono::milliseconds* expire_time) {
+    struct ActiveRequest {
+        std::size_t value_index;
+        int priority;
+        RequestPolicy policy;
+        const std::string* hint_type;
+    };
+    std::vector<ActiveRequest> active_requests;
+    *expire_time = std::chrono::milliseconds::max();
+
+    for (std::size_t i = 0; i < req_sorted_.size(); i++) {
+        std::chrono::milliseconds group_expire_time;
+        if (!req_sorted_[i].GetExpireTime(&group_expire_time)) {
+            continue;
+        }
+        *expire_time = std::min(group_expire_time, *expire_time);
+        for (const auto& request : req_sorted_[i].GetRequests()) {
+            active_requests.push_back({i, request.second.priority,
+                                       request.second.policy, &request.first});
+        }
+    }
+    if (active_requests.empty()) {
+        overridden_hints_.clear();
+        return default_val_index_;
+    }
+
+    // Among exclusive requests of equal priority the one closest to the head
+    // of Values is folded last, so it wins.
+    std::stable_sort(active_requests.begin(), active_requests.end(),
+                     [](const ActiveRequest& a, const ActiveRequest& b) {
+                         if (a.priority != b.priority) {
+                             return a.priority < b.priority;
+                         }
+                         if (a.policy != b.policy) {
+                             return a.policy < b.policy;
+                         }
+                         return a.policy == RequestPolicy::kExclusive &&
+                                a.value_index > b.value_index;
+                     });
+
+    std::size_t value_index = active_requests.front().value_index;
+    for (const auto& request : active_requests) {
+        switch (request.policy) {
+            case RequestPolicy::kMaxWins:
+                value_index = std::min(value_index, request.value_index);
+                break;
+            case RequestPolicy::kMinWins:
+                value_index = std::max(value_index, request.value_index);
+                break;
+            case RequestPolicy::kExclusive:
+                value_index = request.value_index;
+                break;
+        }
+    }
+
+    // Count each time a hint becomes overridden
+    std::set<std::string> overridden_hints;
+    for (const auto& request : active_requests) {
+        if (request.value_index != value_index) {
+            overridden_hints.insert(*request.hint_type);
+        }
+    }
+    for (const auto& hint_type : overridden_hints) {
+        if (overridden_hints_.find(hint_type) == overridden_hints_.end()) {
+            ++override_counts_[hint_type];
+        }
+    }
+    overridden_hints_.swap(overridden_hints);
+    return value_index;
+}
+
+void Node::RemoveAllRequests() {
+    for (auto& value : req_sorted_) {
+        while (!value.GetRequests().empty()) {
+            std::string hint_type = value.GetRequests().begin()->first;
+            value.RemoveRequest(hint_type);
+        }
+    }
+}
+
+std::map<std::string, ReqTime> Node::GetRequestEndTimes() const {
+    std::map<std::string, ReqTime> end_times;
+    const auto now = std::chrono::steady_clock::now();
+    for (const auto& value : req_sorted_) {
+        for (const auto& request : value.GetRequests()) {
+            if (request.second.end_time <= now) {
+                continue;
+            }
+            auto it = end_times.find(request.first);
+            if (it == end_times.end() || it->second < request.second.end_time) {
+                end_times[request.first] = request.second.end_time;
+            }
+        }
+    }
+    return end_times;
+}
+
+void Node::InheritState(const Node& other) {
+    override_counts_ = other.override_counts_;
+    // Node is only written when the value index changes, and other node may
+    // have held an fd open, so always rewrite the resolved value once.
+    reset_on_init_ = true;
+}
+
+bool Node::HasRequests() const {
+    for (const auto& value : req_sorted_) {
+        if (!value.GetRequests().empty()) {
+            return true;
+        }
+    }
+    return false;
+}
+
 const std::string& Node::GetName() const {
     return name_;
 }
@@ -81,10 +213,22 @@ std::size_t Node::GetDefaultIndex() const {
     return default_val_index_;
 }
 
+std::size_t Node::GetCurrentIndex() const {
+    return current_val_index_;
+}
+
+const std::map<std::string, uint64_t>& Node::GetOverrideCounts() const {
+    return override_counts_;
+}
+
 bool Node::GetResetOnInit() const {
     return reset_on_init_;
 }
 
+std::optional<std::string> Node::GetRestoreValue() const {
+    return std::nullopt;
+}
+
 std::vector<std::string> Node::GetValues() const {
     std::vector<std::string> values;
     for (const auto& value : req_sorted_) {
@@ -21,6 +21,7 @@
 
 #include <android-base/file.h>
 #include <android-base/logging.h>
+#include <android-base/parseint.h>
 #include <utils/Trace.h>
 
 namespace android {
@@ -37,18 +38,20 @@ bool NodeLooperThread::Request(const std::vector<NodeAction>& actions,
     }
 
     bool ret = true;
+    bool coalesced = !actions.empty();
     ::android::AutoMutex _l(lock_);
     for (const auto& a : actions) {
         if (a.node_index >= nodes_.size()) {
             LOG(ERROR) << "Node index out of bound: " << a.node_index
                        << " ,size: " << nodes_.size();
             ret = false;
+            coalesced = false;
         } else {
             // End time set to steady time point max
             ReqTime end_time = ReqTime::max();
             // Timeout is non-zero
             if (a.timeout_ms != std::chrono::milliseconds::zero()) {
-                auto now = std::chrono::steady_clock::now();
+                auto now = Now();
                 // Overflow protection in case timeout_ms is too big to overflow
                 // time point which is unsigned integer
                 if (std::chrono::duration_cast<std::chrono::milliseconds>(
@@ -56,11 +59,23 @@ bool NodeLooperThread::Request(const std::vector<NodeAction>& actions,
                     end_time = now + a.timeout_ms;
                 }
             }
+            if (nodes_[a.node_index]->ExtendRequest(a.value_index, hint_type,
+                                                    end_time, a.priority,
+                                                    a.policy)) {
+                continue;
+            }
+            coalesced = false;
             ret = nodes_[a.node_index]->AddRequest(a.value_index, hint_type,
-                                                   end_time) &&
+                                                   end_time, a.priority,
+                                                   a.policy) &&
                   ret;
         }
     }
+    // Node values and the nearest expire time are unchanged
+    if (coalesced) {
+        ++coalesced_counts_[hint_type];
+        return ret;
+    }
     wake_cond_.signal();
     return ret;
 }
@@ -90,6 +105,96 @@ bool NodeLooperThread::Cancel(const std::vector<NodeAction>& actions,
     return ret;
 }
 
+std::vector<std::unique_ptr<Node>> NodeLooperThread::ReloadNodes(
+    std::vector<std::unique_ptr<Node>> nodes,
+    const std::map<std::string,
+                   std::map<std::string, std::vector<NodeAction>>>&
+        node_actions,
+    std::vector<std::string>* changes) {
+    ::android::AutoMutex _l(lock_);
+    std::map<std::string, Node*> new_nodes;
+    for (auto& n : nodes) {
+        new_nodes[n->GetName()] = n.get();
+    }
+
+    for (auto& old_node : nodes_) {
+        auto it = new_nodes.find(old_node->GetName());
+        if (it == new_nodes.end() ||
+            it->second->GetPath() != old_node->GetPath()) {
+            // Reset the node to its default value before dropping it
+            old_node->RemoveAllRequests();
+            old_node->Update(true);
+            changes->emplace_back("Node removed: " + old_node->GetName());
+            continue;
+        }
+        Node* new_node = it->second;
+        new_nodes.erase(it);
+        auto hint_actions = node_actions.find(new_node->GetName());
+        std::size_t dropped = 0;
+        for (const auto& end_time : old_node->GetRequestEndTimes()) {
+            const std::string& hint_type = end_time.first;
+            if (hint_actions == node_actions.end() ||
+                hint_actions->second.find(hint_type) ==
+                    hint_actions->second.end()) {
+                LOG(INFO) << "Drop request " << hint_type << " on Node["
+                          << new_node->GetName() << "]";
+                ++dropped;
+                continue;
+            }
+            for (const auto& action : hint_actions->second.at(hint_type)) {
+                new_node->AddRequest(action.value_index, hint_type,
+                                     end_time.second, action.priority,
+                                     action.policy);
+            }
+        }
+        new_node->InheritState(*old_node);
+        if (new_node->GetValues() != old_node->GetValues() ||
+            new_node->GetDefaultIndex() != old_node->GetDefaultIndex()) {
+            changes->emplace_back("Node changed: " + new_node->GetName());
+        }
+        if (dropped > 0) {
+            changes->emplace_back("Node " + new_node->GetName() + " dropped " +
+                                  std::to_string(dropped) + " requests");
+        }
+    }
+    for (const auto& n : new_nodes) {
+        changes->emplace_back("Node added: " + n.first);
+    }
+
+    nodes_.swap(nodes);
+    wake_cond_.signal();
+    return nodes;
+}
+
+void NodeLooperThread::ScheduleCallback(ReqTime time,
+                                        std::function<void()> callback) {
+    ::android::AutoMutex _l(lock_);
+    callback_time_ = time;
+    callback_ = time == ReqTime::max() ? nullptr : std::move(callback);
+    wake_cond_.signal();
+}
+
+ReqTime NodeLooperThread::Now() const {
+    return std::chrono::steady_clock::now();
+}
+
+std::map<std::string, uint64_t> NodeLooperThread::GetCoalescedCounts() {
+    ::android::AutoMutex _l(lock_);
+    return coalesced_counts_;
+}
+
+std::map<std::string, std::map<std::string, uint64_t>>
+NodeLooperThread::GetOverrideCounts() {
+    ::android::AutoMutex _l(lock_);
+    std::map<std::string, std::map<std::string, uint64_t>> counts;
+    for (auto& n : nodes_) {
+        for (const auto& count : n->GetOverrideCounts()) {
+            counts[count.first][n->GetName()] = count.second;
+        }
+    }
+    return counts;
+}
+
 void NodeLooperThread::DumpToFd(int fd) {
     ::android::AutoMutex _l(lock_);
     for (auto& n : nodes_) {
@@ -101,17 +206,55 @@ bool NodeLooperThread::threadLoop() {
     ::android::AutoMutex _l(lock_);
     std::chrono::milliseconds timeout_ms = kMaxUpdatePeriod;
 
+    if (callback_ && callback_time_ <= std::chrono::steady_clock::now()) {
+        std::function<void()> callback = std::move(callback_);
+        callback_ = nullptr;
+        callback_time_ = ReqTime::max();
+        // The callback may request and cancel hints, taking lock_
+        lock_.unlock();
+        callback();
+        lock_.lock();
+    }
+
     // Update 2 passes: some node may have dependency in other node
     // e.g. update cpufreq min to VAL while cpufreq max still set to
-    // a value lower than VAL, is expected to fail in first pass
+    // a value lower than VAL, is expected to fail in first pass. Nodes are
+    // ordered after the nodes they depend on, so only lowering both is left
+    // to the second pass, and clamped values only fail with VerifyWrite.
     ATRACE_BEGIN("update_nodes");
+    std::vector<std::size_t> value_indexes;
     for (auto& n : nodes_) {
+        value_indexes.push_back(n->GetCurrentIndex());
         n->Update(false);
     }
     for (auto& n : nodes_) {
         timeout_ms = std::min(n->Update(true), timeout_ms);
     }
     ATRACE_END();
+    if (callback_) {
+        auto now = std::chrono::steady_clock::now();
+        timeout_ms = std::min(
+            callback_time_ > now
+                ? std::chrono::ceil<std::chrono::milliseconds>(callback_time_ -
+                                                               now)
+                : std::chrono::milliseconds::zero(),
+            timeout_ms);
+    }
+
+    // Trace node value changes, as the value itself if it is an integer e.g.
+    // a frequency, otherwise as the value index.
+    if (ATRACE_ENABLED()) {
+        for (std::size_t i = 0; i < nodes_.size(); i++) {
+            std::size_t value_index = nodes_[i]->GetCurrentIndex();
+            if (value_index == value_indexes[i]) {
+                continue;
+            }
+            int64_t value = static_cast<int64_t>(value_index);
+            android::base::ParseInt(nodes_[i]->GetValues()[value_index],
+                                    &value);
+            ATRACE_INT64(nodes_[i]->GetName().c_str(), value);
+        }
+    }
 
     nsecs_t sleep_timeout_ns = std::numeric_limits<nsecs_t>::max();
     if (timeout_ms.count() < sleep_timeout_ns / 1000 / 1000) {
@@ -36,16 +36,8 @@ PropertyNode::PropertyNode(std::string name, std::string node_path,
            default_val_index, reset_on_init) {}
 
 std::chrono::milliseconds PropertyNode::Update(bool) {
-    std::size_t value_index = default_val_index_;
-    std::chrono::milliseconds expire_time = std::chrono::milliseconds::max();
-
-    // Find the highest outstanding request's expire time
-    for (std::size_t i = 0; i < req_sorted_.size(); i++) {
-        if (req_sorted_[i].GetExpireTime(&expire_time)) {
-            value_index = i;
-            break;
-        }
-    }
+    std::chrono::milliseconds expire_time;
+    std::size_t value_index = SelectValueIndex(&expire_time);
 
     // Update node only if request index changes
     if (value_index != current_val_index_ || reset_on_init_) {
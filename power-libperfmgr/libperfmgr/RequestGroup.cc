@@ -26,14 +26,30 @@
 namespace android {
 namespace perfmgr {
 
-bool RequestGroup::AddRequest(const std::string& hint_type, ReqTime end_time) {
-    if (request_map_.find(hint_type) == request_map_.end()) {
-        request_map_.emplace(hint_type, end_time);
+const char* ToString(RequestPolicy policy) {
+    switch (policy) {
+        case RequestPolicy::kMaxWins:
+            return "MaxWins";
+        case RequestPolicy::kMinWins:
+            return "MinWins";
+        case RequestPolicy::kExclusive:
+            return "Exclusive";
+    }
+    return "Unknown";
+}
+
+bool RequestGroup::AddRequest(const std::string& hint_type, ReqTime end_time,
+                              int priority, RequestPolicy policy) {
+    auto it = request_map_.find(hint_type);
+    if (it == request_map_.end()) {
+        request_map_.emplace(hint_type, Request{end_time, priority, policy});
         return true;
     } else {
-        if (request_map_[hint_type] < end_time) {
-            request_map_[hint_type] = end_time;
+        if (it->second.end_time < end_time) {
+            it->second.end_time = end_time;
         }
+        it->second.priority = priority;
+        it->second.policy = policy;
         return false;
     }
 }
@@ -42,6 +58,10 @@ bool RequestGroup::RemoveRequest(const std::string& hint_type) {
     return request_map_.erase(hint_type);
 }
 
+const std::map<std::string, Request>& RequestGroup::GetRequests() const {
+    return request_map_;
+}
+
 const std::string& RequestGroup::GetRequestValue() const {
     return request_value_;
 }
@@ -53,7 +73,7 @@ bool RequestGroup::GetExpireTime(std::chrono::milliseconds* expire_time) {
     bool active = false;
     for (auto it = request_map_.begin(); it != request_map_.end();) {
         auto duration = std::chrono::duration_cast<std::chrono::milliseconds>(
-            it->second - now);
+            it->second.end_time - now);
         if (duration <= std::chrono::milliseconds::zero()) {
             it = request_map_.erase(it);
         } else {
@@ -70,10 +90,11 @@ void RequestGroup::DumpToFd(int fd, const std::string& prefix) const {
     ReqTime now = std::chrono::steady_clock::now();
     for (auto it = request_map_.begin(); it != request_map_.end(); it++) {
         auto remaining_duration =
-            std::chrono::duration_cast<std::chrono::milliseconds>(it->second -
-                                                                  now);
+            std::chrono::duration_cast<std::chrono::milliseconds>(
+                it->second.end_time - now);
         dump_buf << prefix << it->first << "\t" << remaining_duration.count()
-                 << "\t" << request_value_ << "\n";
+                 << "\t" << request_value_ << "\t" << it->second.priority
+                 << "\t" << ToString(it->second.policy) << "\n";
     }
     if (!android::base::WriteStringToFd(dump_buf.str(), fd)) {
         LOG(ERROR) << "Failed to dump fd: " << fd;
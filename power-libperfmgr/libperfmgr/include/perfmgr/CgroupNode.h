@@ -0,0 +1,69 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specic language governing permissions and
+ * limitations under the License.
+ */
+
+#ifndef ANDROID_LIBPERFMGR_CGROUPNODE_H_
+#define ANDROID_LIBPERFMGR_CGROUPNODE_H_
+
+#include <cstddef>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "perfmgr/Node.h"
+
+namespace android {
+namespace perfmgr {
+
+// CgroupNode represents an attribute of a cgroup, e.g.
+// /dev/cpuctl/top-app/cpu.uclamp.min. The node is only written while its
+// cgroup directory exists. The attribute value found before the first request
+// is restored once all requests are gone, instead of the default value which is
+// only written on init.
+class CgroupNode : public Node {
+  public:
+    CgroupNode(std::string name, std::string node_path,
+               std::vector<RequestGroup> req_sorted,
+               std::size_t default_val_index, bool reset_on_init);
+
+    std::chrono::milliseconds Update(bool log_error) override;
+
+    // Also carry over the value to restore, so an attribute boosted by the
+    // inherited requests is not saved as the value to restore. The default
+    // value is not written again without requests.
+    void InheritState(const Node& other) override;
+
+    std::optional<std::string> GetRestoreValue() const override;
+
+    const std::string& GetCgroupPath() const;
+
+    void DumpToFd(int fd) const override;
+
+  private:
+    CgroupNode(const Node& other) = delete;
+    CgroupNode& operator=(Node const&) = delete;
+
+    bool WriteValue(const std::string& value, bool log_error);
+
+    const std::string cgroup_path_;
+    // attribute value to restore when there is no request
+    std::optional<std::string> restore_value_;
+    bool cgroup_missing_;
+};
+
+}  // namespace perfmgr
+}  // namespace android
+
+#endif  // ANDROID_LIBPERFMGR_CGROUPNODE_H_
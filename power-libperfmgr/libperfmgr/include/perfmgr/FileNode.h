@@ -20,6 +20,7 @@
 #include <android-base/unique_fd.h>
 
 #include <cstddef>
+#include <cstdint>
 #include <string>
 #include <vector>
 
@@ -28,16 +29,24 @@
 namespace android {
 namespace perfmgr {
 
-// FileNode represents file
+// FileNode represents file. With verify_write, a write is read back and fails
+// if the node holds another value, e.g. a cpufreq min clamped by max. A failed
+// write is retried write_retries times at once, then on a later Update() in
+// 500ms, doubling up to 16s while the same value keeps failing.
 class FileNode : public Node {
   public:
     FileNode(std::string name, std::string node_path,
              std::vector<RequestGroup> req_sorted, std::size_t default_val_index,
-             bool reset_on_init, bool hold_fd = false);
+             bool reset_on_init, bool hold_fd = false,
+             bool verify_write = false, uint32_t write_retries = 0);
 
     std::chrono::milliseconds Update(bool log_error) override;
 
     bool GetHoldFd() const;
+    bool GetVerifyWrite() const;
+    uint32_t GetWriteRetries() const;
+    // Return the number of Update(true) calls failing to write the value.
+    uint64_t GetFailureCount() const;
 
     void DumpToFd(int fd) const override;
 
@@ -45,9 +54,24 @@ class FileNode : public Node {
     FileNode(const Node& other) = delete;
     FileNode& operator=(Node const&) = delete;
 
+    // Return true if value is written, and read back if verify_write_.
+    bool WriteValue(const std::string& value, std::size_t value_index,
+                    bool log_error);
+
     const bool hold_fd_;
+    const bool verify_write_;
+    const uint32_t write_retries_;
     const std::chrono::milliseconds warn_timeout_;
     android::base::unique_fd fd_;
+    uint64_t failure_count_;
+    // Value failing to write in the last failed_updates_ Update() calls, and
+    // the time it is retried
+    std::size_t failed_val_index_;
+    uint32_t failed_updates_;
+    ReqTime retry_time_;
+
+    static constexpr std::chrono::milliseconds kRetryDelay{500};
+    static constexpr uint32_t kMaxRetryBackoff = 5;
 };
 
 }  // namespace perfmgr
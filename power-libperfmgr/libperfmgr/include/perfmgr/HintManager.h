@@ -20,6 +20,9 @@
 #include <cstddef>
 #include <map>
 #include <memory>
+#include <mutex>
+#include <optional>
+#include <set>
 #include <string>
 #include <utility>
 #include <vector>
@@ -29,6 +32,43 @@
 namespace android {
 namespace perfmgr {
 
+// The CompositeHint lists the hints a composite PowerHint includes. The actions
+// of the included hints are merged in order, a later hint overriding an earlier
+// one on the same node, and the composite's own actions override them all. An
+// AutoActivate composite is activated in place of its included hints while all
+// of them are requested, e.g. VR_SUSTAINED_PERFORMANCE for VR and
+// SUSTAINED_PERFORMANCE.
+struct CompositeHint {
+    std::vector<std::string> includes;
+    bool auto_activate;
+};
+
+// The HintStats records how a PowerHint has been used: the number of times it
+// was done, and its last activation, which is folded into duration when the
+// hint is done again after the activation ended.
+struct HintStats {
+    uint64_t count = 0;
+    std::chrono::milliseconds duration = std::chrono::milliseconds::zero();
+    ReqTime time_started;
+    // ReqTime::max() while the hint is active with no timeout
+    ReqTime time_ended;
+    // time of the last DoHint not rate limited
+    ReqTime time_requested;
+    // DoHint calls dropped within the hint's MinInterval
+    uint64_t rate_limited = 0;
+};
+
+// The PowerHalMapping maps a PowerHAL Mode or Boost, by its name, to the
+// PowerHint done for it. The handler names additional handling implemented by
+// the PowerHAL, e.g. "DisplayLowPower", empty for none. The duration is the
+// timeout used when the PowerHAL is not given one, 0ms for the durations of the
+// PowerHint's actions.
+struct PowerHalMapping {
+    std::string hint_type;
+    std::string handler;
+    std::chrono::milliseconds duration;
+};
+
 // HintManager is the external interface of the library to be used by PowerHAL
 // to do power hints with sysfs nodes. HintManager maintains a representation of
 // the actions that are parsed from the configuration file as a mapping from a
@@ -36,8 +76,10 @@ namespace perfmgr {
 class HintManager {
   public:
     HintManager(sp<NodeLooperThread> nm,
-                const std::map<std::string, std::vector<NodeAction>>& actions)
-        : nm_(std::move(nm)), actions_(actions) {}
+                const std::map<std::string, std::vector<NodeAction>>& actions,
+                const std::map<std::string, CompositeHint>& composites = {},
+                const std::map<std::string, std::chrono::milliseconds>&
+                    min_intervals = {});
     ~HintManager() {
         if (nm_.get() != nullptr) nm_->Stop();
     }
@@ -47,12 +89,17 @@ class HintManager {
 
     // Do hint based on hint_type which defined as PowerHint in the actions
     // section of the JSON config. Return true with valid hint_type and also
-    // NodeLooperThread::Request succeeds; otherwise return false.
+    // NodeLooperThread::Request succeeds; otherwise return false. A hint
+    // included by an AutoActivate composite stays requested until EndHint.
+    // While the hint is active, a DoHint within its MinInterval in the
+    // RateLimits section of the JSON config is dropped and returns true.
     bool DoHint(const std::string& hint_type);
 
     // Do hint with the override time for all actions defined for the given
     // hint_type.  Return true with valid hint_type and also
-    // NodeLooperThread::Request succeeds; otherwise return false.
+    // NodeLooperThread::Request succeeds; otherwise return false. A hint
+    // included by an AutoActivate composite stays requested until the override
+    // time, 0ms for forever, or EndHint.
     bool DoHint(const std::string& hint_type,
                 std::chrono::milliseconds timeout_ms_override);
 
@@ -67,6 +114,20 @@ class HintManager {
     static std::unique_ptr<HintManager> GetFromJSON(
         const std::string& config_path, bool start = true);
 
+    // Reload the JSON config file HintManager was constructed from. Requests
+    // of hints still acting on a kept node with a valid value are carried over
+    // and removed nodes are reset to their default values. Return false and
+    // keep the current config if the file fails to parse; otherwise describe
+    // the changed Nodes and PowerHints in diff.
+    bool ReloadConfig(std::string* diff);
+
+    // Look up the PowerHAL Mode or Boost declared in the Modes or Boosts
+    // section of the JSON config. Return false if it is not declared.
+    bool GetModeMapping(const std::string& mode,
+                        PowerHalMapping* mapping) const;
+    bool GetBoostMapping(const std::string& boost,
+                         PowerHalMapping* mapping) const;
+
     // Return available hints managed by HintManager
     std::vector<std::string> GetHints() const;
 
@@ -77,19 +138,99 @@ class HintManager {
     bool Start();
 
   protected:
+    // Read and parse all sections of the JSON config file.
+    static bool ParseConfig(
+        const std::string& config_path,
+        std::vector<std::unique_ptr<Node>>* nodes,
+        std::map<std::string, std::vector<NodeAction>>* actions,
+        std::map<std::string, CompositeHint>* composites,
+        std::map<std::string, PowerHalMapping>* modes,
+        std::map<std::string, PowerHalMapping>* boosts,
+        std::map<std::string, std::chrono::milliseconds>* min_intervals);
     static std::vector<std::unique_ptr<Node>> ParseNodes(
         const std::string& json_doc);
     static std::map<std::string, std::vector<NodeAction>> ParseActions(
         const std::string& json_doc,
         const std::vector<std::unique_ptr<Node>>& nodes);
+    // Parse the optional CompositeHints section into composites and add the
+    // merged actions of each composite to actions. Return false if the section
+    // is malformed, e.g. includes an unknown hint or forms a cycle.
+    static bool ParseCompositeHints(
+        const std::string& json_doc,
+        std::map<std::string, std::vector<NodeAction>>* actions,
+        std::map<std::string, CompositeHint>* composites);
+    // Parse the optional Modes or Boosts section, given as section, into
+    // mappings. Return false if the section is malformed, e.g. maps to an
+    // unknown hint without a handler.
+    static bool ParsePowerHalMappings(
+        const std::string& json_doc, const std::string& section,
+        const std::map<std::string, std::vector<NodeAction>>& actions,
+        std::map<std::string, PowerHalMapping>* mappings);
+
+    // Parse the optional RateLimits section into the MinInterval of each
+    // PowerHint. Return false if the section is malformed, e.g. limits an
+    // unknown hint.
+    static bool ParseRateLimits(
+        const std::string& json_doc,
+        const std::map<std::string, std::vector<NodeAction>>& actions,
+        std::map<std::string, std::chrono::milliseconds>* min_intervals);
 
   private:
     HintManager(HintManager const&) = delete;
     void operator=(HintManager const&) = delete;
+    // Need hold lock_.
     bool ValidateHint(const std::string& hint_type) const;
+    // Request tracked hint_type until end_time, std::nullopt until EndHint,
+    // keeping a later end time it was requested until. Need hold lock_.
+    bool RequestTrackedHint(const std::string& hint_type,
+                            std::optional<ReqTime> end_time);
+    // Drop the requested hints that ended, activate AutoActivate composites
+    // whose included hints are all requested, suspending those hints, and apply
+    // the difference to the previous state. Need hold lock_.
+    bool UpdateCompositeHints();
+
+    // Set tracked_hints_ from composites_. Need hold lock_.
+    void UpdateTrackedHints();
+    // Return the actions to request for hint_type, timed to the end time of a
+    // requested hint done with a timeout override. Need hold lock_.
+    std::vector<NodeAction> GetRequestActions(const std::string& hint_type,
+                                              ReqTime now) const;
+    // Schedule UpdateCompositeHints() on the looper thread when the first
+    // requested hint done with a timeout override ends. Need hold lock_.
+    void ScheduleExpire();
+    // Update hint_stats_ for a hint done for timeout, 0ms for forever. Need
+    // hold lock_.
+    void RecordHintStart(const std::string& hint_type,
+                         std::chrono::milliseconds timeout);
+    // Return true and count it if a DoHint of hint_type for timeout, 0ms for
+    // forever, is within its MinInterval of the last one while the hint is
+    // active, unless it extends the hint by more than MinInterval. Need hold
+    // lock_.
+    bool IsRateLimited(const std::string& hint_type,
+                       std::chrono::milliseconds timeout);
+    // Update hint_stats_ for a hint ended early. Need hold lock_.
+    void RecordHintEnd(const std::string& hint_type);
 
     sp<NodeLooperThread> nm_;
-    const std::map<std::string, std::vector<NodeAction>> actions_;
+    // path of the JSON config, empty if not constructed from one
+    std::string config_path_;
+    // lock to protect the config and the hints states below
+    mutable std::mutex lock_;
+    std::map<std::string, std::vector<NodeAction>> actions_;
+    std::map<std::string, CompositeHint> composites_;
+    std::map<std::string, PowerHalMapping> modes_;
+    std::map<std::string, PowerHalMapping> boosts_;
+    std::map<std::string, std::chrono::milliseconds> min_intervals_;
+    // hints included by an AutoActivate composite
+    std::set<std::string> tracked_hints_;
+    // tracked hints requested and not yet ended, with the end time of a
+    // DoHint with timeout override, std::nullopt until EndHint
+    std::map<std::string, std::optional<ReqTime>> requested_hints_;
+    // end time of the requested hint ending first, ReqTime::max() for none
+    ReqTime expire_time_ = ReqTime::max();
+    // tracked hints and AutoActivate composites currently applied
+    std::set<std::string> active_hints_;
+    std::map<std::string, HintStats> hint_stats_;
 };
 
 }  // namespace perfmgr
@@ -20,6 +20,9 @@
 #include <android-base/unique_fd.h>
 
 #include <cstddef>
+#include <map>
+#include <optional>
+#include <set>
 #include <string>
 #include <vector>
 
@@ -42,19 +45,41 @@ namespace perfmgr {
 // value. For each value, there may be multiple requests because different
 // powerhints may request the same value, and the requests may have different
 // expiration times. All of the in-progress powerhints for a given value are
-// collected in a RequestGroup. Node class is not thread safe so it needs
-// protection from caller e.g. NodeLooperThread.
+// collected in a RequestGroup. When requests carry different priorities or
+// policies, the value is resolved by SelectValueIndex(). Node class is not
+// thread safe so it needs protection from caller e.g. NodeLooperThread.
 class Node {
   public:
     virtual ~Node() {}
 
     // Return true if successfully add a request
     bool AddRequest(std::size_t value_index, const std::string& hint_type,
-                    ReqTime end_time);
+                    ReqTime end_time, int priority = 0,
+                    RequestPolicy policy = RequestPolicy::kMaxWins);
+
+    // Return true if hint_type has an active request on value_index with the
+    // same priority and policy, and extend it to end_time if that is later.
+    // Such a request does not change the node value, so the node needs no
+    // update.
+    bool ExtendRequest(std::size_t value_index, const std::string& hint_type,
+                       ReqTime end_time, int priority = 0,
+                       RequestPolicy policy = RequestPolicy::kMaxWins);
 
     // Return true if successfully remove a request
     bool RemoveRequest(const std::string& hint_type);
 
+    // Remove requests of all hints, e.g. before resetting a node dropped from
+    // the config to its default value.
+    void RemoveAllRequests();
+
+    // Return the latest end time of the active requests of each hint.
+    std::map<std::string, ReqTime> GetRequestEndTimes() const;
+
+    // Carry over the state of other node, which this node replaces on a config
+    // reload. The requests are rebuilt by the caller from the new actions
+    // before. The node value is rewritten on next Update().
+    virtual void InheritState(const Node& other);
+
     // Return the nearest expire time of active requests; return
     // std::chrono::milliseconds::max() if no active request on Node; update
     // node's controlled file node value and the current value index based on
@@ -65,7 +90,14 @@ class Node {
     const std::string& GetPath() const;
     std::vector<std::string> GetValues() const;
     std::size_t GetDefaultIndex() const;
+    std::size_t GetCurrentIndex() const;
+    // Return how many times each hint had a request on the node while another
+    // value was resolved.
+    const std::map<std::string, uint64_t>& GetOverrideCounts() const;
     bool GetResetOnInit() const;
+    // Return the value written back once all requests are gone instead of the
+    // default value, if the node saved one.
+    virtual std::optional<std::string> GetRestoreValue() const;
     bool GetValueIndex(const std::string& value, std::size_t* index) const;
     virtual void DumpToFd(int fd) const = 0;
 
@@ -76,6 +108,16 @@ class Node {
     Node(const Node& other) = delete;
     Node& operator=(Node const&) = delete;
 
+    // Return the value index resolved from all active requests, or the default
+    // index if there is none, and update expire_time with the nearest timeout
+    // of active requests. Requests are folded from the lowest priority to the
+    // highest; on equal priority kMaxWins is applied before kMinWins, and
+    // kExclusive last.
+    std::size_t SelectValueIndex(std::chrono::milliseconds* expire_time);
+    // Return true if any request is outstanding, expired requests are only
+    // removed by SelectValueIndex().
+    bool HasRequests() const;
+
     const std::string name_;
     const std::string node_path_;
     // request vector, one entry per possible value, sorted by priority.
@@ -84,6 +126,9 @@ class Node {
     // node will be explicitly initialized when first time called Update().
     bool reset_on_init_;
     std::size_t current_val_index_;
+    // hints overridden by the last SelectValueIndex()
+    std::set<std::string> overridden_hints_;
+    std::map<std::string, uint64_t> override_counts_;
 };
 
 }  // namespace perfmgr
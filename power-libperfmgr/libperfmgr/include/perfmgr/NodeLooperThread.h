@@ -20,7 +20,10 @@
 #include <utils/Thread.h>
 
 #include <cstddef>
+#include <functional>
+#include <map>
 #include <memory>
+#include <set>
 #include <string>
 #include <utility>
 #include <vector>
@@ -30,17 +33,23 @@
 namespace android {
 namespace perfmgr {
 
-// The NodeAction specifies the sysfs node, the value to be assigned, and the
-// timeout for this action:
+// The NodeAction specifies the sysfs node, the value to be assigned, the
+// timeout for this action, and how it is combined with other requests on the
+// same node:
 struct NodeAction {
     NodeAction(std::size_t node_index, std::size_t value_index,
-               std::chrono::milliseconds timeout_ms)
+               std::chrono::milliseconds timeout_ms, int priority = 0,
+               RequestPolicy policy = RequestPolicy::kMaxWins)
         : node_index(node_index),
           value_index(value_index),
-          timeout_ms(timeout_ms) {}
+          timeout_ms(timeout_ms),
+          priority(priority),
+          policy(policy) {}
     std::size_t node_index;
     std::size_t value_index;
     std::chrono::milliseconds timeout_ms;  // 0ms for forever
+    int priority;
+    RequestPolicy policy;
 };
 
 // The NodeLooperThread is responsible for managing each of the sysfs nodes
@@ -64,14 +73,46 @@ class NodeLooperThread : public ::android::Thread {
 
     // Return true when successfully adds request from actions for the hint_type
     // in each individual node. Return false if any of the actions has either
-    // invalid node index or value index.
-    bool Request(const std::vector<NodeAction>& actions,
-                 const std::string& hint_type);
+    // invalid node index or value index. A request only extending the active
+    // requests of hint_type is coalesced without waking up the thread.
+    virtual bool Request(const std::vector<NodeAction>& actions,
+                         const std::string& hint_type);
     // Return when successfully cancels request from actions for the hint_type
     // in each individual node. Return false if any of the actions has invalid
     // node index.
-    bool Cancel(const std::vector<NodeAction>& actions,
-                const std::string& hint_type);
+    virtual bool Cancel(const std::vector<NodeAction>& actions,
+                        const std::string& hint_type);
+
+    // Replace the nodes with the ones parsed from a reloaded config and return
+    // the replaced nodes. A node is kept when its name and path are unchanged,
+    // and the active requests of the hints that node_actions still lists on it
+    // are rebuilt from the new actions, keeping their end time. node_actions
+    // maps node name -> hint_type -> actions. Removed nodes are reset to their
+    // default values. The changes are appended to changes.
+    std::vector<std::unique_ptr<Node>> ReloadNodes(
+        std::vector<std::unique_ptr<Node>> nodes,
+        const std::map<std::string,
+                       std::map<std::string, std::vector<NodeAction>>>&
+            node_actions,
+        std::vector<std::string>* changes);
+
+    // Run callback on the looper thread at time, in place of the callback
+    // scheduled before; ReqTime::max() for none. The callback runs without
+    // lock_ held, so it can Request() and Cancel().
+    virtual void ScheduleCallback(ReqTime time,
+                                  std::function<void()> callback);
+
+    // Return the current time, which requests are timed from. The config
+    // verifier overrides the requests, the callback and the time to simulate
+    // hints on host.
+    virtual ReqTime Now() const;
+
+    // Return how many times each hint was overridden on each node, as
+    // hint_type -> node name -> count.
+    std::map<std::string, std::map<std::string, uint64_t>> GetOverrideCounts();
+
+    // Return how many requests of each hint were coalesced.
+    std::map<std::string, uint64_t> GetCoalescedCounts();
 
     // Dump all nodes to fd
     void DumpToFd(int fd);
@@ -94,7 +135,14 @@ class NodeLooperThread : public ::android::Thread {
     // class for waking up threadloop.
     ::android::Condition wake_cond_;
 
-    // lock to protect nodes_
+    // requests coalesced by hint_type
+    std::map<std::string, uint64_t> coalesced_counts_;
+
+    // callback scheduled to run at callback_time_
+    std::function<void()> callback_;
+    ReqTime callback_time_ = ReqTime::max();
+
+    // lock to protect nodes_, coalesced_counts_ and the callback
     ::android::Mutex lock_;
 };
 
@@ -27,13 +27,30 @@ namespace perfmgr {
 
 using ReqTime = std::chrono::time_point<std::chrono::steady_clock>;
 
+// The RequestPolicy decides how a request is combined with the requests of
+// lower priority on the same Node. kMaxWins keeps the value closest to the head
+// of the Node's Values, kMinWins keeps the value closest to the tail (e.g. to
+// cap a boost), and kExclusive overrides every lower priority request.
+enum class RequestPolicy { kMaxWins, kMinWins, kExclusive };
+
+// Return the name of the policy as used in the JSON config.
+const char* ToString(RequestPolicy policy);
+
+// The Request holds the expiration time of a hint on a RequestGroup, together
+// with the priority and policy of the action that issued it.
+struct Request {
+    ReqTime end_time;
+    int priority;
+    RequestPolicy policy;
+};
+
 // The RequestGroup type represents the set of requests for a given value on a
 // particular sysfs node, and the interface is simple: there is a function to
 // add requests, a function to remove requests, and a function to check for the
 // next expiration time if there is an outstanding request, and a function to
 // check the requested value. There may only be one request per PowerHint, so
-// the representation is simple: a map from PowerHint to the expiration time for
-// that hint.
+// the representation is simple: a map from PowerHint to the expiration time,
+// priority and policy for that hint.
 class RequestGroup {
   public:
     RequestGroup(std::string request_value)  // NOLINT(runtime/explicit)
@@ -48,17 +65,22 @@ class RequestGroup {
     const std::string& GetRequestValue() const;
     // Return true for adding request, false for extending expire time of
     // existing active request on given hint_type.
-    bool AddRequest(const std::string& hint_type, ReqTime end_time);
+    bool AddRequest(const std::string& hint_type, ReqTime end_time,
+                    int priority = 0,
+                    RequestPolicy policy = RequestPolicy::kMaxWins);
     // Return true for removing request, false if request is not active on given
     // hint_type. If request exits and the new end_time is less than the active
     // time, expire time will not be updated; also returns false.
     bool RemoveRequest(const std::string& hint_type);
+    // Return the outstanding requests; expired requests are only removed by
+    // GetExpireTime().
+    const std::map<std::string, Request>& GetRequests() const;
     // Dump internal status to fd
     void DumpToFd(int fd, const std::string& prefix) const;
 
   private:
     const std::string request_value_;
-    std::map<std::string, ReqTime> request_map_;
+    std::map<std::string, Request> request_map_;
 };
 
 }  // namespace perfmgr
@@ -0,0 +1,169 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specic language governing permissions and
+ * limitations under the License.
+ */
+
+#include <android-base/file.h>
+#include <android-base/stringprintf.h>
+#include <gtest/gtest.h>
+#include <sys/stat.h>
+
+#include <algorithm>
+#include <thread>
+
+#include "perfmgr/CgroupNode.h"
+
+namespace android {
+namespace perfmgr {
+
+using namespace std::chrono_literals;
+
+constexpr double kTIMING_TOLERANCE_MS = std::chrono::milliseconds(25).count();
+
+static inline void _VerifyPathValue(const std::string& path,
+                                    const std::string& value) {
+    std::string s;
+    EXPECT_TRUE(android::base::ReadFileToString(path, &s)) << strerror(errno);
+    EXPECT_EQ(value, s);
+}
+
+static inline const std::string _InitAttribute(const TemporaryDir& cgroup,
+                                               const std::string& value) {
+    std::string path = std::string(cgroup.path) + "/cpu.uclamp.min";
+    EXPECT_TRUE(android::base::WriteStringToFile(value, path))
+        << strerror(errno);
+    return path;
+}
+
+// Test init with no default value
+TEST(CgroupNodeTest, NoInitDefaultTest) {
+    TemporaryDir cgroup;
+    std::string path = _InitAttribute(cgroup, "12");
+    CgroupNode t("t", path, {{"value0"}, {"value1"}, {"value2"}}, 1, false);
+    t.Update(false);
+    _VerifyPathValue(path, "12");
+    EXPECT_EQ(cgroup.path, t.GetCgroupPath());
+}
+
+// Test init with default value
+TEST(CgroupNodeTest, InitDefaultTest) {
+    TemporaryDir cgroup;
+    std::string path = _InitAttribute(cgroup, "12");
+    CgroupNode t("t", path, {{"value0"}, {"value1"}, {"value2"}}, 1, true);
+    t.Update(false);
+    _VerifyPathValue(path, "value1");
+}
+
+// Test DumpToFd
+TEST(CgroupNodeTest, DumpToFdTest) {
+    TemporaryDir cgroup;
+    std::string path = _InitAttribute(cgroup, "12");
+    CgroupNode t("test_dump", path, {{"value0"}, {"value1"}, {"value2"}}, 1,
+                 false);
+    EXPECT_TRUE(t.AddRequest(0, "INTERACTION", ReqTime::max()));
+    t.Update(false);
+    TemporaryFile dumptf;
+    t.DumpToFd(dumptf.fd);
+    fsync(dumptf.fd);
+    std::string buf(android::base::StringPrintf(
+        "test_dump\t%s\t0\tvalue0\n\t\tRestore:\t12\n", path.c_str()));
+    std::string s;
+    EXPECT_TRUE(android::base::ReadFileToString(dumptf.path, &s))
+        << strerror(errno);
+    EXPECT_EQ(0u, s.find(buf));
+}
+
+// Test the value before the first request is restored after the last one
+TEST(CgroupNodeTest, RestoreValueTest) {
+    TemporaryDir cgroup;
+    std::string path = _InitAttribute(cgroup, "12");
+    CgroupNode t("t", path, {{"value0"}, {"value1"}, {"value2"}}, 2, false);
+    auto start = std::chrono::steady_clock::now();
+    EXPECT_TRUE(t.AddRequest(1, "INTERACTION", start + 500ms));
+    std::chrono::milliseconds expire_time = t.Update(true);
+    _VerifyPathValue(path, "value1");
+    EXPECT_NEAR(std::chrono::milliseconds(500).count(), expire_time.count(),
+                kTIMING_TOLERANCE_MS);
+    EXPECT_TRUE(t.AddRequest(0, "LAUNCH", start + 200ms));
+    expire_time = t.Update(true);
+    _VerifyPathValue(path, "value0");
+    t.RemoveRequest("LAUNCH");
+    expire_time = t.Update(true);
+    _VerifyPathValue(path, "value1");
+    t.RemoveRequest("INTERACTION");
+    expire_time = t.Update(true);
+    _VerifyPathValue(path, "12");
+    EXPECT_EQ(std::chrono::milliseconds::max(), expire_time);
+    // Requesting the default value still writes it
+    EXPECT_TRUE(t.AddRequest(2, "INTERACTION", start + 500ms));
+    t.Update(true);
+    _VerifyPathValue(path, "value2");
+}
+
+// Test a node replacing another on reload keeps the value to restore
+TEST(CgroupNodeTest, InheritStateTest) {
+    TemporaryDir cgroup;
+    std::string path = _InitAttribute(cgroup, "12");
+    CgroupNode old_node("t", path, {{"value0"}, {"value1"}, {"value2"}}, 2,
+                        false);
+    EXPECT_TRUE(old_node.AddRequest(1, "INTERACTION", ReqTime::max()));
+    old_node.Update(true);
+    _VerifyPathValue(path, "value1");
+    CgroupNode t("t", path, {{"value0"}, {"value1"}, {"value2"}}, 2, true);
+    EXPECT_TRUE(t.AddRequest(0, "INTERACTION", ReqTime::max()));
+    t.InheritState(old_node);
+    EXPECT_EQ("12", t.GetRestoreValue().value_or(""));
+    t.Update(true);
+    _VerifyPathValue(path, "value0");
+    t.RemoveRequest("INTERACTION");
+    t.Update(true);
+    _VerifyPathValue(path, "12");
+
+    // Without requests the default value is not written on reload
+    CgroupNode idle("t", path, {{"value0"}, {"value1"}, {"value2"}}, 2, true);
+    idle.InheritState(t);
+    EXPECT_FALSE(idle.GetResetOnInit());
+    idle.Update(true);
+    _VerifyPathValue(path, "12");
+}
+
+// Test missing cgroup is retried
+TEST(CgroupNodeTest, MissingCgroupTest) {
+    std::string cgroup_path;
+    std::string path;
+    {
+        TemporaryDir cgroup;
+        cgroup_path = cgroup.path;
+        path = std::string(cgroup.path) + "/cpu.uclamp.min";
+    }
+    CgroupNode t("t", path, {{"value0"}, {"value1"}, {"value2"}}, 2, true);
+    EXPECT_EQ(std::chrono::milliseconds::max(), t.Update(true));
+    EXPECT_TRUE(t.AddRequest(1, "INTERACTION", ReqTime::max()));
+    std::chrono::milliseconds expire_time = t.Update(true);
+    EXPECT_GE(std::chrono::milliseconds(5000).count(), expire_time.count());
+    // Cgroup created later
+    ASSERT_EQ(0, mkdir(cgroup_path.c_str(), 0755)) << strerror(errno);
+    ASSERT_TRUE(android::base::WriteStringToFile("12", path))
+        << strerror(errno);
+    t.Update(true);
+    _VerifyPathValue(path, "value1");
+    t.RemoveRequest("INTERACTION");
+    t.Update(true);
+    _VerifyPathValue(path, "12");
+    unlink(path.c_str());
+    rmdir(cgroup_path.c_str());
+}
+
+}  // namespace perfmgr
+}  // namespace android
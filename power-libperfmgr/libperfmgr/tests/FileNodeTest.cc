@@ -72,6 +72,35 @@ TEST(FileNodeTest, DumpToFdTest) {
     _VerifyPathValue(dumptf.path, buf);
 }
 
+// Test write verification and failure count, /dev/null reads back nothing
+TEST(FileNodeTest, VerifyWriteTest) {
+    FileNode t("t", "/dev/null", {{"value0"}, {"value1"}, {"value2"}}, 1, true,
+               false, false, 2);
+    EXPECT_EQ(std::chrono::milliseconds::max(), t.Update(true));
+    EXPECT_EQ(0u, t.GetFailureCount());
+    FileNode t2("t2", "/dev/null", {{"value0"}, {"value1"}, {"value2"}}, 1,
+                true, false, true, 2);
+    EXPECT_TRUE(t2.GetVerifyWrite());
+    EXPECT_EQ(2u, t2.GetWriteRetries());
+    // Expected failure in the first pass is not counted
+    EXPECT_EQ(std::chrono::milliseconds(500), t2.Update(false));
+    EXPECT_EQ(0u, t2.GetFailureCount());
+    EXPECT_EQ(std::chrono::milliseconds(500), t2.Update(true));
+    EXPECT_EQ(1u, t2.GetFailureCount());
+    EXPECT_TRUE(t2.GetResetOnInit());
+    TemporaryFile dumptf;
+    t2.DumpToFd(dumptf.fd);
+    fsync(dumptf.fd);
+    _VerifyPathValue(dumptf.path, "t2\t/dev/null\t1\t\n\t\tFailures:\t1\n");
+    // Value failing again is not rewritten before its retry time
+    EXPECT_GE(std::chrono::milliseconds(500), t2.Update(true));
+    EXPECT_EQ(1u, t2.GetFailureCount());
+    std::this_thread::sleep_for(500ms + kSLEEP_TOLERANCE_MS);
+    // Then retried with the delay doubled
+    EXPECT_EQ(std::chrono::milliseconds(1000), t2.Update(true));
+    EXPECT_EQ(2u, t2.GetFailureCount());
+}
+
 // Test GetValueIndex
 TEST(FileNodeTest, GetValueIndexTest) {
     TemporaryFile tf;
@@ -186,6 +215,44 @@ TEST(FileNodeTest, RemoveRequestTest) {
     EXPECT_EQ(std::chrono::milliseconds::max(), expire_time);
 }
 
+// Test add request with priority and policy
+TEST(FileNodeTest, AddRequestPolicyTest) {
+    TemporaryFile tf;
+    FileNode t("t", tf.path, {{"value0"}, {"value1"}, {"value2"}, {"value3"}},
+               3, true);
+    auto start = std::chrono::steady_clock::now();
+    EXPECT_TRUE(t.AddRequest(0, "INTERACTION", start + 500ms));
+    t.Update(true);
+    _VerifyPathValue(tf.path, "value0");
+    // Higher priority MinWins request caps the boost
+    EXPECT_TRUE(t.AddRequest(2, "LOW_POWER", ReqTime::max(), 1,
+                             RequestPolicy::kMinWins));
+    t.Update(true);
+    _VerifyPathValue(tf.path, "value2");
+    // Lower priority request cannot lift the cap either
+    EXPECT_TRUE(t.AddRequest(1, "LAUNCH", start + 500ms));
+    t.Update(true);
+    _VerifyPathValue(tf.path, "value2");
+    // Exclusive request overrides everything of lower or equal priority
+    EXPECT_TRUE(t.AddRequest(1, "SUSTAINED", ReqTime::max(), 1,
+                             RequestPolicy::kExclusive));
+    t.Update(true);
+    _VerifyPathValue(tf.path, "value1");
+    // Higher priority MaxWins request still boosts on top of it
+    EXPECT_TRUE(t.AddRequest(0, "CAMERA", start + 500ms, 2));
+    t.Update(true);
+    _VerifyPathValue(tf.path, "value0");
+    t.RemoveRequest("CAMERA");
+    t.RemoveRequest("SUSTAINED");
+    t.Update(true);
+    _VerifyPathValue(tf.path, "value2");
+    t.RemoveRequest("LOW_POWER");
+    std::chrono::milliseconds expire_time = t.Update(true);
+    _VerifyPathValue(tf.path, "value0");
+    EXPECT_NEAR(std::chrono::milliseconds(500).count(), expire_time.count(),
+                kTIMING_TOLERANCE_MS);
+}
+
 // Test add request with holding fd
 TEST(FileNodeTest, AddRequestTestHoldFdOverride) {
     TemporaryFile tf;
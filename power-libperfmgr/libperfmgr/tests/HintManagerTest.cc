@@ -233,6 +233,11 @@ TEST_F(HintManagerTest, HintSupportedTest) {
 
 // Test DumpToFd
 TEST_F(HintManagerTest, DumpToFdTest) {
+    constexpr char kHintStatsDump[] =
+        "========== Begin perfmgr hint stats ==========\nPowerHint\tCount\t"
+        "Duration(ms)\tLastStart(ms)\tLastEnd(ms)\tOverridden\tCoalesced\t"
+        "RateLimited\nINTERACTION\t0\t0\t0\t0\t\t0\t0\nLAUNCH\t0\t0\t0\t0\t\t0"
+        "\t0\n==========  End perfmgr hint stats  ==========\n";
     HintManager hm(nm_, actions_);
     TemporaryFile dumptf;
     hm.DumpToFd(dumptf.fd);
@@ -242,7 +247,8 @@ TEST_F(HintManagerTest, DumpToFdTest) {
                 "Path\tCurrent Index\tCurrent Value\nn0\t"
              << files_[0]->path << "\t2\t\nn1\t" << files_[1]->path
              << "\t2\t\nn2\tvendor.pwhal.mode\t2\t\n==========  End perfmgr "
-                "nodes  ==========\n";
+                "nodes  ==========\n"
+             << kHintStatsDump;
     _VerifyPathValue(dumptf.path, dump_buf.str());
     TemporaryFile dumptf_started;
     EXPECT_TRUE(hm.Start());
@@ -256,7 +262,8 @@ TEST_F(HintManagerTest, DumpToFdTest) {
                 "Path\tCurrent Index\tCurrent Value\nn0\t"
              << files_[0]->path << "\t2\t\nn1\t" << files_[1]->path
              << "\t2\tn1_value2\nn2\tvendor.pwhal.mode\t2\tn2_value2\n========="
-                "=  End perfmgr nodes  ==========\n";
+                "=  End perfmgr nodes  ==========\n"
+             << kHintStatsDump;
     _VerifyPathValue(dumptf_started.path, dump_buf.str());
 }
 
@@ -308,6 +315,91 @@ TEST_F(HintManagerTest, HintTest) {
     _VerifyPropertyValue(prop_, "n2_value2");
 }
 
+// Test hint stats in DumpToFd
+TEST_F(HintManagerTest, HintStatsTest) {
+    HintManager hm(nm_, actions_);
+    EXPECT_TRUE(hm.Start());
+    EXPECT_TRUE(hm.DoHint("INTERACTION"));
+    EXPECT_TRUE(hm.DoHint("LAUNCH"));
+    std::this_thread::sleep_for(100ms);
+    EXPECT_TRUE(hm.EndHint("LAUNCH"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    EXPECT_TRUE(hm.DoHint("LAUNCH"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    TemporaryFile dumptf;
+    hm.DumpToFd(dumptf.fd);
+    fsync(dumptf.fd);
+    std::string dump;
+    EXPECT_TRUE(android::base::ReadFileToString(dumptf.path, &dump));
+    // "INTERACTION" is overridden by "LAUNCH" on all nodes once per "LAUNCH"
+    EXPECT_NE(std::string::npos, dump.find("\nINTERACTION\t1\t"));
+    EXPECT_NE(std::string::npos,
+              dump.find("\t-\tn0:2,n1:2,n2:2\t0\t0\nLAUNCH\t2\t"));
+    EXPECT_NE(std::string::npos,
+              dump.find("\t-\t\t0\t0\n==========  End perfmgr hint stats"));
+}
+
+// Test AutoActivate composite hint with dummy actions
+TEST_F(HintManagerTest, CompositeHintTest) {
+    // "BOOST" includes "INTERACTION" and "LAUNCH"
+    // Node0, value1, forever
+    // Node1, value2, forever
+    // Node2, value1, forever
+    actions_["BOOST"] = {{0, 1, 0ms}, {1, 2, 0ms}, {2, 1, 0ms}};
+    std::map<std::string, CompositeHint> composites{
+        {"BOOST", {{"INTERACTION", "LAUNCH"}, true}}};
+    HintManager hm(nm_, actions_, composites);
+    EXPECT_TRUE(hm.Start());
+    EXPECT_TRUE(hm.DoHint("INTERACTION"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0]->path, "n0_value1");
+    _VerifyPathValue(files_[1]->path, "n1_value1");
+    _VerifyPropertyValue(prop_, "n2_value1");
+    // "BOOST" replaces "INTERACTION" and "LAUNCH"
+    EXPECT_TRUE(hm.DoHint("LAUNCH"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0]->path, "n0_value1");
+    _VerifyPathValue(files_[1]->path, "n1_value2");
+    _VerifyPropertyValue(prop_, "n2_value1");
+    // "INTERACTION" is back
+    EXPECT_TRUE(hm.EndHint("LAUNCH"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0]->path, "n0_value1");
+    _VerifyPathValue(files_[1]->path, "n1_value1");
+    _VerifyPropertyValue(prop_, "n2_value1");
+    EXPECT_TRUE(hm.EndHint("INTERACTION"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0]->path, "n0_value2");
+    _VerifyPathValue(files_[1]->path, "n1_value2");
+    _VerifyPropertyValue(prop_, "n2_value2");
+}
+
+// Test AutoActivate composite hint activated by a hint done with a timeout
+TEST_F(HintManagerTest, CompositeHintTimeoutTest) {
+    actions_["BOOST"] = {{0, 1, 0ms}, {1, 2, 0ms}, {2, 1, 0ms}};
+    std::map<std::string, CompositeHint> composites{
+        {"BOOST", {{"INTERACTION", "LAUNCH"}, true}}};
+    HintManager hm(nm_, actions_, composites);
+    EXPECT_TRUE(hm.Start());
+    EXPECT_TRUE(hm.DoHint("INTERACTION"));
+    // "BOOST" replaces "INTERACTION" and "LAUNCH" until "LAUNCH" times out
+    EXPECT_TRUE(hm.DoHint("LAUNCH", 200ms));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0]->path, "n0_value1");
+    _VerifyPathValue(files_[1]->path, "n1_value2");
+    _VerifyPropertyValue(prop_, "n2_value1");
+    // "INTERACTION" is back
+    std::this_thread::sleep_for(200ms);
+    _VerifyPathValue(files_[0]->path, "n0_value1");
+    _VerifyPathValue(files_[1]->path, "n1_value1");
+    _VerifyPropertyValue(prop_, "n2_value1");
+    EXPECT_TRUE(hm.EndHint("INTERACTION"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0]->path, "n0_value2");
+    _VerifyPathValue(files_[1]->path, "n1_value2");
+    _VerifyPropertyValue(prop_, "n2_value2");
+}
+
 // Test parsing nodes
 TEST_F(HintManagerTest, ParseNodesTest) {
     std::vector<std::unique_ptr<Node>> nodes =
@@ -432,6 +524,45 @@ TEST_F(HintManagerTest, ParsePropertyNodesEmptyValueTest) {
     EXPECT_FALSE(nodes[2]->GetResetOnInit());
 }
 
+// Test parsing nodes with write verification and dependencies
+TEST_F(HintManagerTest, ParseNodesDependsOnTest) {
+    std::string from = "\"DefaultIndex\":2,\"ResetOnInit\":true";
+    size_t start_pos = json_doc_.find(from);
+    json_doc_.replace(start_pos, from.length(),
+                      from +
+                          ",\"DependsOn\":[\"ModeProperty\"],"
+                          "\"VerifyWrite\":true,\"WriteRetries\":2");
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    EXPECT_EQ(3u, nodes.size());
+    EXPECT_EQ("CPUCluster1MinFreq", nodes[0]->GetName());
+    EXPECT_EQ("ModeProperty", nodes[1]->GetName());
+    EXPECT_EQ("CPUCluster0MinFreq", nodes[2]->GetName());
+    // no dynamic_cast intentionally in Android
+    FileNode* node = reinterpret_cast<FileNode*>(nodes[0].get());
+    EXPECT_FALSE(node->GetVerifyWrite());
+    EXPECT_EQ(0u, node->GetWriteRetries());
+    node = reinterpret_cast<FileNode*>(nodes[2].get());
+    EXPECT_TRUE(node->GetVerifyWrite());
+    EXPECT_EQ(2u, node->GetWriteRetries());
+}
+
+// Test parsing nodes with unknown or cyclic dependencies
+TEST_F(HintManagerTest, ParseNodesBadDependsOnTest) {
+    std::string from = "\"DefaultIndex\":2,\"ResetOnInit\":true";
+    std::string json_doc = json_doc_;
+    json_doc.replace(json_doc.find(from), from.length(),
+                     from + ",\"DependsOn\":[\"NoSuchNode\"]");
+    EXPECT_EQ(0u, HintManager::ParseNodes(json_doc).size());
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.find(from), from.length(),
+                     from + ",\"DependsOn\":[\"ModeProperty\"]");
+    from = "\"Type\":\"Property\"";
+    json_doc.replace(json_doc.find(from), from.length(),
+                     from + ",\"DependsOn\":[\"CPUCluster0MinFreq\"]");
+    EXPECT_EQ(0u, HintManager::ParseNodes(json_doc).size());
+}
+
 // Test parsing invalid json for nodes
 TEST_F(HintManagerTest, ParseBadFileNodesTest) {
     std::vector<std::unique_ptr<Node>> nodes =
@@ -480,6 +611,39 @@ TEST_F(HintManagerTest, ParseActionsTest) {
               actions["LAUNCH"][2].timeout_ms.count());
 }
 
+// Test parsing actions with priority and policy
+TEST_F(HintManagerTest, ParseActionsPolicyTest) {
+    std::string from = "\"Value\":\"LOW\",";
+    size_t start_pos = json_doc_.find(from);
+    json_doc_.replace(
+        start_pos, from.length(),
+        "\"Value\":\"LOW\",\"Priority\":1,\"Policy\":\"MinWins\",");
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    EXPECT_EQ(2u, actions.size());
+
+    EXPECT_EQ(0, actions["INTERACTION"][0].priority);
+    EXPECT_EQ(RequestPolicy::kMaxWins, actions["INTERACTION"][0].policy);
+    EXPECT_EQ(1, actions["INTERACTION"][1].priority);
+    EXPECT_EQ(RequestPolicy::kMinWins, actions["INTERACTION"][1].policy);
+}
+
+// Test parsing actions with invalid policy
+TEST_F(HintManagerTest, ParseActionsBadPolicyTest) {
+    std::string from = "\"Value\":\"LOW\",";
+    size_t start_pos = json_doc_.find(from);
+    json_doc_.replace(start_pos, from.length(),
+                      "\"Value\":\"LOW\",\"Policy\":\"Latest\",");
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    EXPECT_EQ(3u, nodes.size());
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    EXPECT_EQ(0u, actions.size());
+}
+
 // Test parsing actions with duplicate File node
 TEST_F(HintManagerTest, ParseActionDuplicateFileNodeTest) {
     std::string from = "\"Node\":\"CPUCluster0MinFreq\"";
@@ -521,6 +685,216 @@ TEST_F(HintManagerTest, ParseBadActionsTest) {
     EXPECT_EQ(0u, actions.size());
 }
 
+// Test parsing composite hints
+TEST_F(HintManagerTest, ParseCompositeHintsTest) {
+    json_doc_.replace(json_doc_.rfind("]}"), 2,
+                      "],\"CompositeHints\":[{\"PowerHint\":\"BOOST\","
+                      "\"Includes\":[\"INTERACTION\",\"LAUNCH\"],"
+                      "\"AutoActivate\":true}]}");
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    std::map<std::string, CompositeHint> composites;
+    EXPECT_TRUE(
+        HintManager::ParseCompositeHints(json_doc_, &actions, &composites));
+    EXPECT_EQ(1u, composites.size());
+    EXPECT_EQ(2u, composites["BOOST"].includes.size());
+    EXPECT_TRUE(composites["BOOST"].auto_activate);
+    EXPECT_EQ(3u, actions.size());
+
+    // "LAUNCH" overrides "INTERACTION" on Node1 and Node2
+    EXPECT_EQ(3u, actions["BOOST"].size());
+    EXPECT_EQ(1u, actions["BOOST"][0].node_index);
+    EXPECT_EQ(0u, actions["BOOST"][0].value_index);
+    EXPECT_EQ(std::chrono::milliseconds(2000).count(),
+              actions["BOOST"][0].timeout_ms.count());
+
+    EXPECT_EQ(2u, actions["BOOST"][1].node_index);
+    EXPECT_EQ(0u, actions["BOOST"][1].value_index);
+    EXPECT_EQ(std::chrono::milliseconds(500).count(),
+              actions["BOOST"][1].timeout_ms.count());
+
+    EXPECT_EQ(0u, actions["BOOST"][2].node_index);
+    EXPECT_EQ(1u, actions["BOOST"][2].value_index);
+    EXPECT_EQ(std::chrono::milliseconds(500).count(),
+              actions["BOOST"][2].timeout_ms.count());
+}
+
+// Test parsing composite hints including unknown hints or forming a cycle
+TEST_F(HintManagerTest, ParseBadCompositeHintsTest) {
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    std::map<std::string, CompositeHint> composites;
+    std::string json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"CompositeHints\":[{\"PowerHint\":\"BOOST\","
+                     "\"Includes\":[\"INTERACTION\",\"NO_SUCH_HINT\"]}]}");
+    EXPECT_FALSE(
+        HintManager::ParseCompositeHints(json_doc, &actions, &composites));
+    EXPECT_EQ(0u, composites.size());
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"CompositeHints\":[{\"PowerHint\":\"A\","
+                     "\"Includes\":[\"B\"]},{\"PowerHint\":\"B\","
+                     "\"Includes\":[\"LAUNCH\",\"A\"]}]}");
+    EXPECT_FALSE(
+        HintManager::ParseCompositeHints(json_doc, &actions, &composites));
+    EXPECT_EQ(0u, composites.size());
+    EXPECT_EQ(2u, actions.size());
+}
+
+// Test parsing the mapping of PowerHAL modes and boosts to hints
+TEST_F(HintManagerTest, ParsePowerHalMappingsTest) {
+    json_doc_.replace(json_doc_.rfind("]}"), 2,
+                      "],\"Modes\":[{\"Name\":\"LAUNCH\",\"Duration\":5000},"
+                      "{\"Name\":\"LOW_POWER\",\"Handler\":"
+                      "\"DisplayLowPower\"}],\"Boosts\":[{\"Name\":"
+                      "\"CAMERA_LAUNCH\",\"PowerHint\":\"LAUNCH\"}]}");
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    std::map<std::string, PowerHalMapping> modes;
+    std::map<std::string, PowerHalMapping> boosts;
+    EXPECT_TRUE(HintManager::ParsePowerHalMappings(json_doc_, "Modes", actions,
+                                                   &modes));
+    EXPECT_TRUE(HintManager::ParsePowerHalMappings(json_doc_, "Boosts",
+                                                   actions, &boosts));
+    EXPECT_EQ(2u, modes.size());
+    EXPECT_EQ("LAUNCH", modes["LAUNCH"].hint_type);
+    EXPECT_EQ("", modes["LAUNCH"].handler);
+    EXPECT_EQ(5000, modes["LAUNCH"].duration.count());
+    EXPECT_EQ("LOW_POWER", modes["LOW_POWER"].hint_type);
+    EXPECT_EQ("DisplayLowPower", modes["LOW_POWER"].handler);
+    EXPECT_EQ(0, modes["LOW_POWER"].duration.count());
+    EXPECT_EQ(1u, boosts.size());
+    EXPECT_EQ("LAUNCH", boosts["CAMERA_LAUNCH"].hint_type);
+}
+
+// Test parsing mappings to unknown hints, names or handlers or with bad
+// durations
+TEST_F(HintManagerTest, ParseBadPowerHalMappingsTest) {
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    std::map<std::string, PowerHalMapping> modes;
+    std::string json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Modes\":[{\"Name\":\"VR\",\"PowerHint\":"
+                     "\"NO_SUCH_HINT\"}]}");
+    EXPECT_FALSE(
+        HintManager::ParsePowerHalMappings(json_doc, "Modes", actions, &modes));
+    // Name not in the PowerHAL Modes
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Modes\":[{\"Name\":\"LAUNCHH\",\"PowerHint\":"
+                     "\"LAUNCH\"}]}");
+    EXPECT_FALSE(
+        HintManager::ParsePowerHalMappings(json_doc, "Modes", actions, &modes));
+    // Boost name in Modes
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Modes\":[{\"Name\":\"INTERACTION\",\"PowerHint\":"
+                     "\"LAUNCH\"}]}");
+    EXPECT_FALSE(
+        HintManager::ParsePowerHalMappings(json_doc, "Modes", actions, &modes));
+    // Misspelled handler
+    std::map<std::string, PowerHalMapping> boosts;
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Boosts\":[{\"Name\":\"INTERACTION\",\"Handler\":"
+                     "\"Interactoin\"}]}");
+    EXPECT_FALSE(HintManager::ParsePowerHalMappings(json_doc, "Boosts", actions,
+                                                    &boosts));
+    // Mode handler in Boosts
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Boosts\":[{\"Name\":\"INTERACTION\",\"Handler\":"
+                     "\"VR\"}]}");
+    EXPECT_FALSE(HintManager::ParsePowerHalMappings(json_doc, "Boosts", actions,
+                                                    &boosts));
+    EXPECT_EQ(0u, boosts.size());
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Modes\":[{\"Name\":\"LAUNCH\",\"Duration\":-1}]}");
+    EXPECT_FALSE(
+        HintManager::ParsePowerHalMappings(json_doc, "Modes", actions, &modes));
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"Modes\":[{\"Name\":\"LAUNCH\"},"
+                     "{\"Name\":\"LAUNCH\"}]}");
+    EXPECT_FALSE(
+        HintManager::ParsePowerHalMappings(json_doc, "Modes", actions, &modes));
+    EXPECT_EQ(0u, modes.size());
+}
+
+// Test parsing minimum re-trigger intervals of hints
+TEST_F(HintManagerTest, ParseRateLimitsTest) {
+    std::vector<std::unique_ptr<Node>> nodes =
+        HintManager::ParseNodes(json_doc_);
+    std::map<std::string, std::vector<NodeAction>> actions =
+        HintManager::ParseActions(json_doc_, nodes);
+    std::map<std::string, std::chrono::milliseconds> min_intervals;
+    std::string json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"RateLimits\":[{\"PowerHint\":\"INTERACTION\","
+                     "\"MinInterval\":100}]}");
+    EXPECT_TRUE(
+        HintManager::ParseRateLimits(json_doc, actions, &min_intervals));
+    EXPECT_EQ(1u, min_intervals.size());
+    EXPECT_EQ(100, min_intervals["INTERACTION"].count());
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"RateLimits\":[{\"PowerHint\":\"NO_SUCH_HINT\","
+                     "\"MinInterval\":100}]}");
+    EXPECT_FALSE(
+        HintManager::ParseRateLimits(json_doc, actions, &min_intervals));
+    json_doc = json_doc_;
+    json_doc.replace(json_doc.rfind("]}"), 2,
+                     "],\"RateLimits\":[{\"PowerHint\":\"INTERACTION\"}]}");
+    EXPECT_FALSE(
+        HintManager::ParseRateLimits(json_doc, actions, &min_intervals));
+    EXPECT_EQ(1u, min_intervals.size());
+}
+
+// Test dropping hints done again within their minimum re-trigger interval
+TEST_F(HintManagerTest, RateLimitTest) {
+    json_doc_.replace(json_doc_.rfind("]}"), 2,
+                      "],\"RateLimits\":[{\"PowerHint\":\"INTERACTION\","
+                      "\"MinInterval\":200}]}");
+    TemporaryFile json_file;
+    ASSERT_TRUE(android::base::WriteStringToFile(json_doc_, json_file.path))
+        << strerror(errno);
+    std::unique_ptr<HintManager> hm = HintManager::GetFromJSON(json_file.path);
+    EXPECT_NE(nullptr, hm.get());
+    EXPECT_TRUE(hm->DoHint("INTERACTION"));
+    EXPECT_TRUE(hm->DoHint("INTERACTION"));
+    EXPECT_TRUE(hm->DoHint("INTERACTION", 300ms));
+    std::this_thread::sleep_for(200ms + kSLEEP_TOLERANCE_MS);
+    EXPECT_TRUE(hm->DoHint("INTERACTION"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    TemporaryFile dumptf;
+    hm->DumpToFd(dumptf.fd);
+    fsync(dumptf.fd);
+    std::string dump;
+    EXPECT_TRUE(android::base::ReadFileToString(dumptf.path, &dump));
+    // The last "INTERACTION" extends the active requests
+    EXPECT_NE(std::string::npos, dump.find("\nINTERACTION\t2\t"));
+    EXPECT_NE(std::string::npos, dump.find("\t1\t2\nLAUNCH\t0\t"));
+    // A longer timeout within the interval is not dropped
+    EXPECT_TRUE(hm->DoHint("INTERACTION", 5000ms));
+    TemporaryFile dumptf2;
+    hm->DumpToFd(dumptf2.fd);
+    fsync(dumptf2.fd);
+    EXPECT_TRUE(android::base::ReadFileToString(dumptf2.path, &dump));
+    EXPECT_NE(std::string::npos, dump.find("\nINTERACTION\t3\t"));
+    EXPECT_NE(std::string::npos, dump.find("\t2\nLAUNCH\t0\t"));
+}
+
 // Test hint/cancel/expire with json config
 TEST_F(HintManagerTest, GetFromJSONTest) {
     TemporaryFile json_file;
@@ -571,5 +945,58 @@ TEST_F(HintManagerTest, GetFromJSONTest) {
     _VerifyPropertyValue(prop_, "NONE");
 }
 
+// Test reloading json config with kept requests
+TEST_F(HintManagerTest, ReloadConfigTest) {
+    TemporaryFile json_file;
+    ASSERT_TRUE(android::base::WriteStringToFile(json_doc_, json_file.path))
+        << strerror(errno);
+    std::unique_ptr<HintManager> hm = HintManager::GetFromJSON(json_file.path);
+    EXPECT_NE(nullptr, hm.get());
+    EXPECT_TRUE(hm->DoHint("LAUNCH", 0ms));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0 + 2]->path, "1134000");
+    _VerifyPathValue(files_[1 + 2]->path, "1512000");
+    _VerifyPropertyValue(prop_, "HIGH");
+    // Add a value to Node0 and change the Duration of INTERACTION
+    std::string from = "\"384000\"],\"DefaultIndex\":2";
+    size_t start_pos = json_doc_.find(from);
+    json_doc_.replace(start_pos, from.length(),
+                      "\"1000000\",\"384000\"],\"DefaultIndex\":3");
+    from = "\"Duration\":800";
+    start_pos = json_doc_.find(from);
+    json_doc_.replace(start_pos, from.length(), "\"Duration\":1000");
+    // Retune the value of LAUNCH on ModeProperty
+    from = "\"Value\":\"HIGH\"";
+    start_pos = json_doc_.find(from);
+    json_doc_.replace(start_pos, from.length(), "\"Value\":\"LOW\"");
+    ASSERT_TRUE(android::base::WriteStringToFile(json_doc_, json_file.path))
+        << strerror(errno);
+    std::string diff;
+    EXPECT_TRUE(hm->ReloadConfig(&diff));
+    EXPECT_EQ(
+        "Node changed: CPUCluster0MinFreq\nPowerHint changed: INTERACTION\n"
+        "PowerHint changed: LAUNCH\n",
+        diff);
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    // "LAUNCH" requests are kept, with the retuned value applied
+    _VerifyPathValue(files_[0 + 2]->path, "1134000");
+    _VerifyPathValue(files_[1 + 2]->path, "1512000");
+    _VerifyPropertyValue(prop_, "LOW");
+    EXPECT_TRUE(hm->EndHint("LAUNCH"));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0 + 2]->path, "384000");
+    _VerifyPathValue(files_[1 + 2]->path, "384000");
+    _VerifyPropertyValue(prop_, "NONE");
+    // Invalid config is not applied
+    ASSERT_TRUE(
+        android::base::WriteStringToFile("invalid json", json_file.path))
+        << strerror(errno);
+    EXPECT_FALSE(hm->ReloadConfig(&diff));
+    EXPECT_TRUE(hm->IsHintSupported("LAUNCH"));
+    EXPECT_TRUE(hm->DoHint("LAUNCH", 0ms));
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[0 + 2]->path, "1134000");
+}
+
 }  // namespace perfmgr
 }  // namespace android
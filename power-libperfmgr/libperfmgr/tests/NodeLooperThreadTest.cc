@@ -191,5 +191,37 @@ TEST_F(NodeLooperThreadTest, MultipleRequest) {
     EXPECT_FALSE(th->isRunning());
 }
 
+// Test request extending the active request coalesced
+TEST_F(NodeLooperThreadTest, CoalesceRequest) {
+    sp<NodeLooperThread> th = new NodeLooperThread(std::move(nodes_));
+    EXPECT_TRUE(th->Start());
+    EXPECT_TRUE(th->isRunning());
+    std::vector<NodeAction> actions{{0, 0, 200ms}, {1, 1, 400ms}};
+    EXPECT_TRUE(th->Request(actions, "LAUNCH"));
+    std::this_thread::sleep_for(100ms);
+    _VerifyPathValue(files_[0]->path, "n0_value0");
+    _VerifyPathValue(files_[1]->path, "n1_value1");
+    EXPECT_TRUE(th->Request(actions, "LAUNCH"));
+    EXPECT_EQ(1u, th->GetCoalescedCounts()["LAUNCH"]);
+    std::this_thread::sleep_for(150ms);
+    // "LAUNCH" node0 extended
+    _VerifyPathValue(files_[0]->path, "n0_value0");
+    std::this_thread::sleep_for(100ms);
+    _VerifyPathValue(files_[0]->path, "n0_value2");
+    _VerifyPathValue(files_[1]->path, "n1_value1");
+    // Request covered by the longer active one is coalesced
+    std::vector<NodeAction> actions_shorter{{1, 1, 10ms}};
+    EXPECT_TRUE(th->Request(actions_shorter, "LAUNCH"));
+    EXPECT_EQ(2u, th->GetCoalescedCounts()["LAUNCH"]);
+    // Another value is not coalesced
+    std::vector<NodeAction> actions_value{{1, 0, 400ms}};
+    EXPECT_TRUE(th->Request(actions_value, "LAUNCH"));
+    EXPECT_EQ(2u, th->GetCoalescedCounts()["LAUNCH"]);
+    std::this_thread::sleep_for(kSLEEP_TOLERANCE_MS);
+    _VerifyPathValue(files_[1]->path, "n1_value0");
+    th->Stop();
+    EXPECT_FALSE(th->isRunning());
+}
+
 }  // namespace perfmgr
 }  // namespace android
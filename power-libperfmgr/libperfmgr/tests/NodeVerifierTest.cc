@@ -0,0 +1,153 @@
+/*
+ * Copyright (C) 2018 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specic language governing permissions and
+ * limitations under the License.
+ */
+
+#include <android-base/file.h>
+#include <gtest/gtest.h>
+
+#include <sstream>
+
+#include "../tools/NodeVerifier.h"
+
+namespace android {
+namespace perfmgr {
+
+// JSON_CONFIG
+// {
+//     "Nodes": [
+//         {
+//             "Name": "n0",
+//             "Path": "/sys/fake/n0",
+//             "Values": ["high", "mid", "low"],
+//             "DefaultIndex": 2
+//         },
+//         {
+//             "Name": "n1",
+//             "Path": "/sys/fake/n1",
+//             "Values": ["high", "mid", "low"],
+//             "DefaultIndex": 2
+//         }
+//     ],
+//     "Actions": [
+//         {"PowerHint": "VR", "Node": "n0", "Value": "mid", "Duration": 0},
+//         {"PowerHint": "SUSTAINED_PERFORMANCE", "Node": "n1", "Value": "mid",
+//          "Duration": 0},
+//         {"PowerHint": "VR_SUSTAINED_PERFORMANCE", "Node": "n0",
+//          "Value": "high", "Duration": 0},
+//         {"PowerHint": "INTERACTION", "Node": "n1", "Value": "high",
+//          "Duration": 100}
+//     ],
+//     "CompositeHints": [
+//         {
+//             "PowerHint": "VR_SUSTAINED_PERFORMANCE",
+//             "Includes": ["VR", "SUSTAINED_PERFORMANCE"],
+//             "AutoActivate": true
+//         }
+//     ],
+//     "RateLimits": [{"PowerHint": "INTERACTION", "MinInterval": 50}]
+// }
+constexpr char kJSON_RAW[] =
+    "{\"Nodes\":[{\"Name\":\"n0\",\"Path\":\"/sys/fake/n0\",\"Values\":["
+    "\"high\",\"mid\",\"low\"],\"DefaultIndex\":2},{\"Name\":\"n1\",\"Path\":"
+    "\"/sys/fake/n1\",\"Values\":[\"high\",\"mid\",\"low\"],\"DefaultIndex\":"
+    "2}],\"Actions\":[{\"PowerHint\":\"VR\",\"Node\":\"n0\",\"Value\":\"mid\","
+    "\"Duration\":0},{\"PowerHint\":\"SUSTAINED_PERFORMANCE\",\"Node\":\"n1\","
+    "\"Value\":\"mid\",\"Duration\":0},{\"PowerHint\":"
+    "\"VR_SUSTAINED_PERFORMANCE\",\"Node\":\"n0\",\"Value\":\"high\","
+    "\"Duration\":0},{\"PowerHint\":\"INTERACTION\",\"Node\":\"n1\",\"Value\":"
+    "\"high\",\"Duration\":100}],\"CompositeHints\":[{\"PowerHint\":"
+    "\"VR_SUSTAINED_PERFORMANCE\",\"Includes\":[\"VR\","
+    "\"SUSTAINED_PERFORMANCE\"],\"AutoActivate\":true}],\"RateLimits\":[{"
+    "\"PowerHint\":\"INTERACTION\",\"MinInterval\":50}]}";
+
+class NodeVerifierTest : public ::testing::Test {
+  protected:
+    virtual void SetUp() {
+        ASSERT_TRUE(android::base::WriteStringToFile(kJSON_RAW, config_.path))
+            << strerror(errno);
+    }
+
+    bool Simulate(const std::string& script, std::string* output) {
+        TemporaryFile script_file;
+        EXPECT_TRUE(android::base::WriteStringToFile(script, script_file.path))
+            << strerror(errno);
+        std::ostringstream out;
+        bool ret = NodeVerifier::SimulateConfig(config_.path, script_file.path,
+                                                &out);
+        *output = out.str();
+        return ret;
+    }
+
+    TemporaryFile config_;
+};
+
+// Test validating a config
+TEST_F(NodeVerifierTest, VerifyConfigTest) {
+    EXPECT_TRUE(NodeVerifier::VerifyConfig(config_.path));
+}
+
+// Test validating configs with unknown nodes or values out of range
+TEST_F(NodeVerifierTest, VerifyBadConfigTest) {
+    std::string json_doc = kJSON_RAW;
+    std::string from = "\"Node\":\"n1\",\"Value\":\"high\"";
+    json_doc.replace(json_doc.find(from), from.length(),
+                     "\"Node\":\"n2\",\"Value\":\"high\"");
+    ASSERT_TRUE(android::base::WriteStringToFile(json_doc, config_.path));
+    EXPECT_FALSE(NodeVerifier::VerifyConfig(config_.path));
+    json_doc = kJSON_RAW;
+    json_doc.replace(json_doc.find(from), from.length(),
+                     "\"Node\":\"n1\",\"Value\":\"max\"");
+    ASSERT_TRUE(android::base::WriteStringToFile(json_doc, config_.path));
+    EXPECT_FALSE(NodeVerifier::VerifyConfig(config_.path));
+}
+
+// Test simulating composite hints, rate limits and timeouts
+TEST_F(NodeVerifierTest, SimulateConfigTest) {
+    std::string output;
+    EXPECT_TRUE(Simulate(
+        "0 DoHint VR\n"
+        "# VR_SUSTAINED_PERFORMANCE replaces VR and SUSTAINED_PERFORMANCE\n"
+        "10 DoHint SUSTAINED_PERFORMANCE\n"
+        "20 EndHint VR\n"
+        "30 DoHint INTERACTION\n"
+        "# Rate limited, INTERACTION still ends at 130\n"
+        "40 DoHint INTERACTION\n"
+        "# VR_SUSTAINED_PERFORMANCE until VR times out at 300\n"
+        "200 DoHint VR 100\n",
+        &output));
+    EXPECT_EQ(
+        "0\tn0\tlow\n"
+        "0\tn1\tlow\n"
+        "0\tn0\tmid\n"
+        "10\tn0\thigh\n"
+        "10\tn1\tmid\n"
+        "20\tn0\tlow\n"
+        "30\tn1\thigh\n"
+        "130\tn1\tmid\n"
+        "200\tn0\thigh\n"
+        "300\tn0\tlow\n",
+        output);
+}
+
+// Test simulating scripts with unknown hints or out of order lines
+TEST_F(NodeVerifierTest, SimulateBadScriptTest) {
+    std::string output;
+    EXPECT_FALSE(Simulate("0 DoHint NO_SUCH_HINT\n", &output));
+    EXPECT_FALSE(Simulate("10 DoHint VR\n0 EndHint VR\n", &output));
+    EXPECT_FALSE(Simulate("0 SetHint VR\n", &output));
+}
+
+}  // namespace perfmgr
+}  // namespace android
@@ -127,6 +127,20 @@ TEST(RequestGroupTest, AddRequestTestMultiple) {
     EXPECT_EQ(true, active);
 }
 
+// Test AddRequest() with priority and policy
+TEST(RequestGroupTest, AddRequestPolicyTest) {
+    RequestGroup req("");
+    auto start = std::chrono::steady_clock::now();
+    req.AddRequest("INTERACTION", start + 500ms);
+    req.AddRequest("LOW_POWER", start + 500ms, 1, RequestPolicy::kMinWins);
+    const auto& requests = req.GetRequests();
+    EXPECT_EQ(2u, requests.size());
+    EXPECT_EQ(0, requests.at("INTERACTION").priority);
+    EXPECT_EQ(RequestPolicy::kMaxWins, requests.at("INTERACTION").policy);
+    EXPECT_EQ(1, requests.at("LOW_POWER").priority);
+    EXPECT_EQ(RequestPolicy::kMinWins, requests.at("LOW_POWER").policy);
+}
+
 // Test RemoveRequest()
 TEST(RequestGroupTest, RemoveRequestTest) {
     RequestGroup req("");
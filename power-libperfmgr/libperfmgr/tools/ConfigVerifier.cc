@@ -14,52 +14,22 @@
  * limitations under the License.
  */
 
-#include <android-base/file.h>
 #include <android-base/logging.h>
 #include <getopt.h>
 #include <sys/types.h>
 #include <unistd.h>
 
+#include <iostream>
 #include <thread>
 
-#include "perfmgr/HintManager.h"
-
-namespace android {
-namespace perfmgr {
-
-class NodeVerifier : public HintManager {
-  public:
-    static bool VerifyNodes(const std::string& config_path) {
-        std::string json_doc;
-
-        if (!android::base::ReadFileToString(config_path, &json_doc)) {
-            LOG(ERROR) << "Failed to read JSON config from " << config_path;
-            return false;
-        }
-
-        std::vector<std::unique_ptr<Node>> nodes = ParseNodes(json_doc);
-        if (nodes.empty()) {
-            LOG(ERROR) << "Failed to parse Nodes section from " << config_path;
-            return false;
-        }
-
-        return true;
-    }
-
-  private:
-    NodeVerifier(sp<NodeLooperThread> nm,
-                 const std::map<std::string, std::vector<NodeAction>>& actions)
-        : HintManager(std::move(nm), actions) {}
-};
-
-}  // namespace perfmgr
-}  // namespace android
+#include "NodeVerifier.h"
 
 static void printUsage(const char* exec_name) {
     std::string usage = exec_name;
     usage =
         usage +
-        " is a command-line tool to verify Nodes in Json config are writable.\n"
+        " is a command-line tool to verify Nodes in Json config are writable,\n"
+        "or to validate and simulate Json config on host.\n"
         "Usages:\n"
         "    [su system] " +
         exec_name +
@@ -74,6 +44,13 @@ static void printUsage(const char* exec_name) {
         "       do only the specific hint\n\n"
         "   --hint_duration, -d  [duration]\n"
         "       duration in ms for each hint\n\n"
+        "   --validate, -V\n"
+        "       validate Nodes and Actions in Json config without writing\n\n"
+        "   --simulate, -s  [SCRIPT]\n"
+        "       print node values over time for the DoHint and EndHint calls\n"
+        "       in SCRIPT, one \"<time_ms> DoHint <hint> [duration_ms]\" or\n"
+        "       \"<time_ms> EndHint <hint>\" per line, without writing;\n"
+        "       composite hints and rate limits apply as on device\n\n"
         "   --help, -h\n"
         "       print this message\n\n"
         "   --verbose, -v\n"
@@ -112,6 +89,8 @@ int main(int argc, char* argv[]) {
     std::string config_path;
     std::string hint_name;
     bool exec_hint = false;
+    bool validate = false;
+    std::string script_path;
     uint64_t hint_duration = 100;
 
     while (true) {
@@ -120,13 +99,15 @@ int main(int argc, char* argv[]) {
             {"exec_hint", no_argument, nullptr, 'e'},
             {"hint_name", required_argument, nullptr, 'i'},
             {"hint_duration", required_argument, nullptr, 'd'},
+            {"validate", no_argument, nullptr, 'V'},
+            {"simulate", required_argument, nullptr, 's'},
             {"help", no_argument, nullptr, 'h'},
             {"verbose", no_argument, nullptr, 'v'},
             {0, 0, 0, 0}  // termination of the option list
         };
 
         int option_index = 0;
-        int c = getopt_long(argc, argv, "c:ei:d:hv", opts, &option_index);
+        int c = getopt_long(argc, argv, "c:ei:d:Vs:hv", opts, &option_index);
         if (c == -1) {
             break;
         }
@@ -144,6 +125,12 @@ int main(int argc, char* argv[]) {
             case 'd':
                 hint_duration = strtoul(optarg, NULL, 10);
                 break;
+            case 'V':
+                validate = true;
+                break;
+            case 's':
+                script_path = optarg;
+                break;
             case 'v':
                 android::base::SetMinimumLogSeverity(android::base::VERBOSE);
                 break;
@@ -162,6 +149,23 @@ int main(int argc, char* argv[]) {
         return 1;
     }
 
+    if (validate) {
+        if (android::perfmgr::NodeVerifier::VerifyConfig(config_path)) {
+            LOG(INFO) << "Validated JSON config";
+            return 0;
+        } else {
+            LOG(ERROR) << "Failed to validate JSON config";
+            return 1;
+        }
+    }
+
+    if (!script_path.empty()) {
+        return android::perfmgr::NodeVerifier::SimulateConfig(
+                   config_path, script_path, &std::cout)
+                   ? 0
+                   : 1;
+    }
+
     if (exec_hint) {
         execConfig(config_path, hint_name, hint_duration);
         return 0;
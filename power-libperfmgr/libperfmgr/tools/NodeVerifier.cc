@@ -0,0 +1,393 @@
+/*
+ * Copyright (C) 2018 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specic language governing permissions and
+ * limitations under the License.
+ */
+
+#include "NodeVerifier.h"
+
+#include <android-base/file.h>
+#include <android-base/logging.h>
+#include <json/reader.h>
+#include <json/value.h>
+
+#include <algorithm>
+#include <set>
+#include <sstream>
+#include <utility>
+
+namespace android {
+namespace perfmgr {
+
+namespace {
+
+// MemoryNode keeps the value of a Node in memory, so that hints can be
+// simulated without a device.
+class MemoryNode : public Node {
+  public:
+    explicit MemoryNode(const Node& node)
+        : Node(node.GetName(), node.GetPath(), ToRequestGroups(node),
+               node.GetDefaultIndex(), false) {}
+
+    std::chrono::milliseconds Update(bool) override {
+        std::chrono::milliseconds expire_time;
+        current_val_index_ = SelectValueIndex(&expire_time);
+        return expire_time;
+    }
+
+    void DumpToFd(int) const override {}
+
+  private:
+    static std::vector<RequestGroup> ToRequestGroups(const Node& node) {
+        std::vector<std::string> values = node.GetValues();
+        return std::vector<RequestGroup>(values.begin(), values.end());
+    }
+};
+
+// SimulatedLooper takes the requests of HintManager on in-memory nodes in
+// simulated time starting at 0, and converts them to time left from now
+// whenever the nodes are updated. Its thread is never started.
+class SimulatedLooper : public NodeLooperThread {
+  public:
+    SimulatedLooper(std::vector<std::unique_ptr<Node>> nodes, std::ostream* out)
+        : NodeLooperThread({}),
+          nodes_(std::move(nodes)),
+          requests_(nodes_.size()),
+          out_(out) {
+        for (const auto& node : nodes_) {
+            last_indexes_.push_back(node->GetCurrentIndex());
+        }
+    }
+
+    bool Request(const std::vector<NodeAction>& actions,
+                 const std::string& hint_type) override {
+        for (const auto& a : actions) {
+            if (a.node_index >= nodes_.size()) {
+                LOG(ERROR) << "Node index out of bound: " << a.node_index;
+                return false;
+            }
+            ReqTime end_time = ReqTime::max();
+            if (a.timeout_ms != std::chrono::milliseconds::zero()) {
+                end_time = now_ + a.timeout_ms;
+            }
+            // As Node::AddRequest, keep the later end time of the same value
+            auto& request = requests_[a.node_index][{hint_type, a.value_index}];
+            if (request.end_time > now_) {
+                end_time = std::max(end_time, request.end_time);
+            }
+            request = {end_time, a.priority, a.policy};
+        }
+        return true;
+    }
+
+    bool Cancel(const std::vector<NodeAction>& actions,
+                const std::string& hint_type) override {
+        for (const auto& a : actions) {
+            if (a.node_index >= nodes_.size()) {
+                LOG(ERROR) << "Node index out of bound: " << a.node_index;
+                return false;
+            }
+            auto& node_requests = requests_[a.node_index];
+            for (auto it = node_requests.begin(); it != node_requests.end();) {
+                it = it->first.first == hint_type ? node_requests.erase(it)
+                                                  : std::next(it);
+            }
+        }
+        return true;
+    }
+
+    void ScheduleCallback(ReqTime time,
+                          std::function<void()> callback) override {
+        callback_time_ = time;
+        callback_ = time == ReqTime::max() ? nullptr : std::move(callback);
+    }
+
+    ReqTime Now() const override { return now_; }
+
+    // Update the nodes at every expiry, and run the callback when due, up to
+    // time.
+    void AdvanceTo(ReqTime time) {
+        while (true) {
+            ReqTime next = callback_ ? callback_time_ : ReqTime::max();
+            for (const auto& node_requests : requests_) {
+                for (const auto& request : node_requests) {
+                    next = std::min(next, request.second.end_time);
+                }
+            }
+            if (next == ReqTime::max() || next > time) {
+                break;
+            }
+            now_ = next;
+            if (callback_ && callback_time_ <= now_) {
+                std::function<void()> callback = std::move(callback_);
+                callback_ = nullptr;
+                callback_time_ = ReqTime::max();
+                callback();
+            }
+            Update();
+        }
+        now_ = std::max(now_, time);
+    }
+
+    // Update the nodes from the requests not expired, printing the nodes
+    // whose value changed.
+    void Update() {
+        auto now = std::chrono::steady_clock::now();
+        for (std::size_t i = 0; i < nodes_.size(); ++i) {
+            nodes_[i]->RemoveAllRequests();
+            for (auto it = requests_[i].begin(); it != requests_[i].end();) {
+                const PendingRequest& request = it->second;
+                if (request.end_time <= now_) {
+                    it = requests_[i].erase(it);
+                    continue;
+                }
+                ReqTime end_time = ReqTime::max();
+                if (request.end_time != ReqTime::max()) {
+                    end_time = now + (request.end_time - now_);
+                }
+                nodes_[i]->AddRequest(it->first.second, it->first.first,
+                                      end_time, request.priority,
+                                      request.policy);
+                ++it;
+            }
+            nodes_[i]->Update(false);
+            if (nodes_[i]->GetCurrentIndex() != last_indexes_[i]) {
+                last_indexes_[i] = nodes_[i]->GetCurrentIndex();
+                Print(i);
+            }
+        }
+    }
+
+    // Print the values of all nodes.
+    void Print() const {
+        for (std::size_t i = 0; i < nodes_.size(); ++i) {
+            Print(i);
+        }
+    }
+
+  private:
+    struct PendingRequest {
+        // simulated time, ReqTime::max() for no timeout
+        ReqTime end_time;
+        int priority;
+        RequestPolicy policy;
+    };
+
+    void Print(std::size_t i) const {
+        *out_ << std::chrono::duration_cast<std::chrono::milliseconds>(
+                     now_.time_since_epoch())
+                     .count()
+              << "\t" << nodes_[i]->GetName() << "\t"
+              << nodes_[i]->GetValues()[last_indexes_[i]] << "\n";
+    }
+
+    std::vector<std::unique_ptr<Node>> nodes_;
+    // requests of each node by hint and value index
+    std::vector<std::map<std::pair<std::string, std::size_t>, PendingRequest>>
+        requests_;
+    std::vector<std::size_t> last_indexes_;
+    std::ostream* out_;
+    std::function<void()> callback_;
+    ReqTime callback_time_ = ReqTime::max();
+    ReqTime now_;
+};
+
+}  // namespace
+
+bool NodeVerifier::VerifyNodes(const std::string& config_path) {
+    std::string json_doc;
+
+    if (!android::base::ReadFileToString(config_path, &json_doc)) {
+        LOG(ERROR) << "Failed to read JSON config from " << config_path;
+        return false;
+    }
+
+    std::vector<std::unique_ptr<Node>> nodes = ParseNodes(json_doc);
+    if (nodes.empty()) {
+        LOG(ERROR) << "Failed to parse Nodes section from " << config_path;
+        return false;
+    }
+
+    return true;
+}
+
+bool NodeVerifier::VerifyConfig(const std::string& config_path) {
+    std::string json_doc;
+
+    if (!android::base::ReadFileToString(config_path, &json_doc)) {
+        LOG(ERROR) << "Failed to read JSON config from " << config_path;
+        return false;
+    }
+
+    std::vector<std::unique_ptr<Node>> nodes = ParseNodes(json_doc);
+    if (nodes.empty()) {
+        LOG(ERROR) << "Failed to parse Nodes section from " << config_path;
+        return false;
+    }
+
+    Json::Value root;
+    Json::Reader reader;
+    if (!reader.parse(json_doc, root)) {
+        LOG(ERROR) << "Failed to parse JSON config";
+        return false;
+    }
+
+    std::size_t errors = 0;
+    Json::Value json_nodes = root["Nodes"];
+    for (Json::Value::ArrayIndex i = 0; i < json_nodes.size(); ++i) {
+        std::string type = json_nodes[i]["Type"].asString();
+        if (type.empty() || type == "File") {
+            continue;
+        }
+        for (const auto& key : {"HoldFd", "VerifyWrite", "WriteRetries"}) {
+            if (!json_nodes[i][key].empty()) {
+                LOG(ERROR) << "Node[" << i << "]'s " << key
+                           << " is not supported by " << type << " node";
+                ++errors;
+            }
+        }
+    }
+
+    std::map<std::string, std::size_t> nodes_index;
+    std::vector<std::set<std::size_t>> reachable(nodes.size());
+    for (std::size_t i = 0; i < nodes.size(); ++i) {
+        nodes_index[nodes[i]->GetName()] = i;
+        reachable[i].insert(nodes[i]->GetDefaultIndex());
+    }
+
+    std::set<std::pair<std::string, std::string>> hint_nodes;
+    Json::Value actions = root["Actions"];
+    for (Json::Value::ArrayIndex i = 0; i < actions.size(); ++i) {
+        std::string hint_type = actions[i]["PowerHint"].asString();
+        std::string node_name = actions[i]["Node"].asString();
+        std::string value_name = actions[i]["Value"].asString();
+        auto it = nodes_index.find(node_name);
+        if (it == nodes_index.end()) {
+            LOG(ERROR) << "Action[" << i << "]'s Node " << node_name
+                       << " is not defined in Nodes section";
+            ++errors;
+            continue;
+        }
+        if (!hint_nodes.emplace(hint_type, node_name).second) {
+            LOG(ERROR) << "Action[" << i << "] duplicates PowerHint "
+                       << hint_type << " on Node " << node_name;
+            ++errors;
+        }
+        std::size_t value_index;
+        if (!nodes[it->second]->GetValueIndex(value_name, &value_index)) {
+            LOG(ERROR) << "Action[" << i << "]'s Value " << value_name
+                       << " is out of the range of Node " << node_name;
+            ++errors;
+            continue;
+        }
+        reachable[it->second].insert(value_index);
+    }
+
+    for (std::size_t i = 0; i < nodes.size(); ++i) {
+        std::vector<std::string> values = nodes[i]->GetValues();
+        for (std::size_t j = 0; j < values.size(); ++j) {
+            if (reachable[i].find(j) == reachable[i].end()) {
+                LOG(WARNING) << "Node " << nodes[i]->GetName()
+                             << "'s Value[" << j << "] " << values[j]
+                             << " is unreachable";
+            }
+        }
+    }
+
+    if (errors > 0) {
+        LOG(ERROR) << errors << " errors found in " << config_path;
+        return false;
+    }
+
+    std::map<std::string, std::vector<NodeAction>> actions_parsed;
+    std::map<std::string, CompositeHint> composites;
+    std::map<std::string, PowerHalMapping> modes;
+    std::map<std::string, PowerHalMapping> boosts;
+    std::map<std::string, std::chrono::milliseconds> min_intervals;
+    return ParseConfig(config_path, &nodes, &actions_parsed, &composites,
+                       &modes, &boosts, &min_intervals);
+}
+
+bool NodeVerifier::SimulateConfig(const std::string& config_path,
+                                  const std::string& script_path,
+                                  std::ostream* out) {
+    std::vector<std::unique_ptr<Node>> parsed_nodes;
+    std::map<std::string, std::vector<NodeAction>> actions;
+    std::map<std::string, CompositeHint> composites;
+    std::map<std::string, PowerHalMapping> modes;
+    std::map<std::string, PowerHalMapping> boosts;
+    std::map<std::string, std::chrono::milliseconds> min_intervals;
+    if (!ParseConfig(config_path, &parsed_nodes, &actions, &composites, &modes,
+                     &boosts, &min_intervals)) {
+        return false;
+    }
+
+    std::string script;
+    if (!android::base::ReadFileToString(script_path, &script)) {
+        LOG(ERROR) << "Failed to read script from " << script_path;
+        return false;
+    }
+
+    std::vector<std::unique_ptr<Node>> nodes;
+    for (const auto& node : parsed_nodes) {
+        nodes.emplace_back(std::make_unique<MemoryNode>(*node));
+    }
+    sp<SimulatedLooper> looper = new SimulatedLooper(std::move(nodes), out);
+    // The real HintManager resolves composite hints and rate limits
+    HintManager hm(looper, actions, composites, min_intervals);
+    looper->Print();
+
+    std::istringstream lines(script);
+    std::string line;
+    int64_t last_time = 0;
+    for (std::size_t n = 1; std::getline(lines, line); ++n) {
+        std::istringstream fields(line.substr(0, line.find('#')));
+        int64_t time;
+        std::string call;
+        std::string hint_type;
+        if (!(fields >> time)) {
+            continue;
+        }
+        if (!(fields >> call >> hint_type) || time < last_time ||
+            (call != "DoHint" && call != "EndHint")) {
+            LOG(ERROR) << "Failed to read script line " << n << ": " << line;
+            return false;
+        }
+        if (actions.find(hint_type) == actions.end()) {
+            LOG(ERROR) << "Script line " << n << "'s PowerHint " << hint_type
+                       << " is not defined";
+            return false;
+        }
+        last_time = time;
+        looper->AdvanceTo(ReqTime(std::chrono::milliseconds(time)));
+        int64_t duration;
+        bool ret;
+        if (call == "EndHint") {
+            ret = hm.EndHint(hint_type);
+        } else if (fields >> duration) {
+            ret = hm.DoHint(hint_type, std::chrono::milliseconds(duration));
+        } else {
+            ret = hm.DoHint(hint_type);
+        }
+        if (!ret) {
+            LOG(ERROR) << "Failed to run script line " << n << ": " << line;
+            return false;
+        }
+        looper->Update();
+    }
+    looper->AdvanceTo(ReqTime::max());
+    return true;
+}
+
+}  // namespace perfmgr
+}  // namespace android
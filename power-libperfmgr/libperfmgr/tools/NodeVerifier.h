@@ -0,0 +1,62 @@
+/*
+ * Copyright (C) 2018 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specic language governing permissions and
+ * limitations under the License.
+ */
+
+#ifndef ANDROID_LIBPERFMGR_TOOLS_NODEVERIFIER_H_
+#define ANDROID_LIBPERFMGR_TOOLS_NODEVERIFIER_H_
+
+#include <map>
+#include <ostream>
+#include <string>
+#include <vector>
+
+#include "perfmgr/HintManager.h"
+
+namespace android {
+namespace perfmgr {
+
+// NodeVerifier checks the JSON config of HintManager, on device by writing its
+// nodes, or on host by parsing and simulating it.
+class NodeVerifier : public HintManager {
+  public:
+    // Return true if the Nodes section parses.
+    static bool VerifyNodes(const std::string& config_path);
+
+    // Check the Actions section for unknown nodes, values out of the range
+    // of their node and hints acting twice on a node, and the Nodes section
+    // for File only options set on other nodes; warn about values no action
+    // requests.
+    // Then parse the whole config as HintManager does.
+    static bool VerifyConfig(const std::string& config_path);
+
+    // Run the DoHint and EndHint calls of script_path through HintManager on
+    // in-memory nodes in simulated time, and print to out the value of every
+    // node at time 0 and whenever it changes, as "<time_ms>\t<node>\t<value>".
+    // Each script line is "<time_ms> DoHint <hint> [duration_ms]" or
+    // "<time_ms> EndHint <hint>", in time order; '#' starts a comment.
+    static bool SimulateConfig(const std::string& config_path,
+                               const std::string& script_path,
+                               std::ostream* out);
+
+  private:
+    NodeVerifier(sp<NodeLooperThread> nm,
+                 const std::map<std::string, std::vector<NodeAction>>& actions)
+        : HintManager(std::move(nm), actions) {}
+};
+
+}  // namespace perfmgr
+}  // namespace android
+
+#endif  // ANDROID_LIBPERFMGR_TOOLS_NODEVERIFIER_H_
@@ -20,6 +20,7 @@
 
 #include <android-base/file.h>
 #include <android-base/logging.h>
+#include <android-base/parseint.h>
 #include <hidl/HidlTransportSupport.h>
 
 #include "Thermal.h"
@@ -52,6 +53,100 @@ Return<void> setInitFailureAndCallback(T _hidl_cb, hidl_vec<U> data) {
     return setFailureAndCallback(_hidl_cb, data, "Failure initializing thermal HAL");
 }
 
+const std::vector<std::chrono::seconds> kDefaultHistoryWindows = {
+        std::chrono::seconds(10), std::chrono::seconds(60), std::chrono::seconds(300)};
+
+int64_t toMillis(boot_clock::time_point t) {
+    return std::chrono::duration_cast<std::chrono::milliseconds>(t.time_since_epoch()).count();
+}
+
+void dumpSensorHistoryStats(const std::map<std::string, SensorHistory> &history_map,
+                            const std::vector<std::chrono::seconds> &windows,
+                            std::ostringstream *dump_buf) {
+    const auto now = boot_clock::now();
+    for (const auto &name_history_pair : history_map) {
+        *dump_buf << " Name: " << name_history_pair.first;
+        for (const auto &window : windows) {
+            SensorStats stats = name_history_pair.second.getStats(window, now);
+            *dump_buf << " [" << window.count() << "s Count: " << stats.count;
+            if (stats.count) {
+                *dump_buf << " Min: " << stats.min << " Max: " << stats.max
+                          << " Avg: " << stats.avg;
+            }
+            *dump_buf << "]";
+        }
+        *dump_buf << std::endl;
+    }
+}
+
+// Dump the readings of each monitored sensor, args are "history [csv] [window_sec...]"
+void dumpSensorHistory(const std::map<std::string, SensorHistory> &history_map,
+                       const hidl_vec<hidl_string> &args, std::ostringstream *dump_buf) {
+    bool csv = false;
+    std::vector<std::chrono::seconds> windows;
+    for (size_t i = 1; i < args.size(); ++i) {
+        int64_t window;
+        if (args[i] == "csv") {
+            csv = true;
+        } else if (android::base::ParseInt(args[i].c_str(), &window, int64_t(1))) {
+            windows.emplace_back(window);
+        } else {
+            *dump_buf << "Invalid history argument: " << args[i] << std::endl;
+            return;
+        }
+    }
+    if (windows.empty()) {
+        windows = kDefaultHistoryWindows;
+    }
+
+    if (csv) {
+        *dump_buf << "sensor,timestamp_ms,value,severity" << std::endl;
+        for (const auto &name_history_pair : history_map) {
+            for (const auto &sample : name_history_pair.second.getSamples()) {
+                *dump_buf << name_history_pair.first << "," << toMillis(sample.timestamp) << ","
+                          << sample.value << ","
+                          << android::hardware::thermal::V2_0::toString(sample.severity)
+                          << std::endl;
+            }
+        }
+        return;
+    }
+
+    *dump_buf << "SensorHistoryStats:" << std::endl;
+    dumpSensorHistoryStats(history_map, windows, dump_buf);
+    *dump_buf << "SensorHistory:" << std::endl;
+    for (const auto &name_history_pair : history_map) {
+        *dump_buf << " Name: " << name_history_pair.first << std::endl;
+        ThrottlingSeverity prev_severity = ThrottlingSeverity::NONE;
+        for (const auto &sample : name_history_pair.second.getSamples()) {
+            *dump_buf << "  " << toMillis(sample.timestamp) << "ms " << sample.value;
+            if (sample.severity != prev_severity) {
+                *dump_buf << " " << android::hardware::thermal::V2_0::toString(prev_severity)
+                          << " -> " << android::hardware::thermal::V2_0::toString(sample.severity);
+                prev_severity = sample.severity;
+            }
+            *dump_buf << std::endl;
+        }
+    }
+}
+
+void dumpCdevStats(const std::map<std::string, CdevStats> &cdev_stats_map,
+                   std::ostringstream *dump_buf) {
+    const auto now = boot_clock::now();
+    for (const auto &name_stats_pair : cdev_stats_map) {
+        const CdevStats &cdev_stats = name_stats_pair.second;
+        *dump_buf << " Name: " << name_stats_pair.first << " State: " << cdev_stats.state
+                  << " Transitions: " << cdev_stats.transitions << " LastChange: "
+                  << toMillis(now) - toMillis(cdev_stats.last_change_time) << "ms ago"
+                  << " Since: " << toMillis(now) - toMillis(cdev_stats.reset_time) << "ms ago"
+                  << " TimeInState: [";
+        for (const auto &state_time_pair : cdev_stats.time_in_state) {
+            *dump_buf << state_time_pair.first << ":" << state_time_pair.second.count() << "ms ";
+        }
+        *dump_buf << "]" << std::endl;
+    }
+}
+
 }  // namespace
 
 // On init we will spawn a thread which will continually watch for
@@ -59,9 +154,11 @@ Return<void> setInitFailureAndCallback(T _hidl_cb, hidl_vec<U> data) {
 // the thread will call notifyThrottling() else it will log the dropped
 // throttling event and do nothing.  The thread is only killed when
 // Thermal() is killed.
-Thermal::Thermal()
-    : thermal_helper_(
-          std::bind(&Thermal::sendThermalChangedCallback, this, std::placeholders::_1)) {}
+Thermal::Thermal(const std::shared_ptr<ThermalHelper> &thermal_helper)
+    : thermal_helper_(thermal_helper) {
+    thermal_helper_->addNotificationCallback(
+            std::bind(&Thermal::sendThermalChangedCallback, this, std::placeholders::_1));
+}
 
 // Methods from ::android::hardware::thermal::V1_0::IThermal.
 Return<void> Thermal::getTemperatures(getTemperatures_cb _hidl_cb) {
@@ -69,12 +166,12 @@ Return<void> Thermal::getTemperatures(getTemperatures_cb _hidl_cb) {
     status.code = ThermalStatusCode::SUCCESS;
     hidl_vec<Temperature_1_0> temperatures;
 
-    if (!thermal_helper_.isInitializedOk()) {
+    if (!thermal_helper_->isInitializedOk()) {
         LOG(ERROR) << "ThermalHAL not initialized properly.";
         return setInitFailureAndCallback(_hidl_cb, temperatures);
     }
 
-    if (!thermal_helper_.fillTemperatures(&temperatures)) {
+    if (!thermal_helper_->fillTemperatures(&temperatures)) {
         return setFailureAndCallback(_hidl_cb, temperatures, "Failed to read thermal sensors.");
     }
 
@@ -87,11 +184,11 @@ Return<void> Thermal::getCpuUsages(getCpuUsages_cb _hidl_cb) {
     status.code = ThermalStatusCode::SUCCESS;
     hidl_vec<CpuUsage> cpu_usages;
 
-    if (!thermal_helper_.isInitializedOk()) {
+    if (!thermal_helper_->isInitializedOk()) {
         return setInitFailureAndCallback(_hidl_cb, cpu_usages);
     }
 
-    if (!thermal_helper_.fillCpuUsages(&cpu_usages)) {
+    if (!thermal_helper_->fillCpuUsages(&cpu_usages)) {
         return setFailureAndCallback(_hidl_cb, cpu_usages, "Failed to get CPU usages.");
     }
 
@@ -104,7 +201,7 @@ Return<void> Thermal::getCoolingDevices(getCoolingDevices_cb _hidl_cb) {
     status.code = ThermalStatusCode::SUCCESS;
     hidl_vec<CoolingDevice_1_0> cooling_devices;
 
-    if (!thermal_helper_.isInitializedOk()) {
+    if (!thermal_helper_->isInitializedOk()) {
         return setInitFailureAndCallback(_hidl_cb, cooling_devices);
     }
     _hidl_cb(status, cooling_devices);
@@ -117,12 +214,12 @@ Return<void> Thermal::getCurrentTemperatures(bool filterType, TemperatureType_2_
     status.code = ThermalStatusCode::SUCCESS;
     hidl_vec<Temperature_2_0> temperatures;
 
-    if (!thermal_helper_.isInitializedOk()) {
+    if (!thermal_helper_->isInitializedOk()) {
         LOG(ERROR) << "ThermalHAL not initialized properly.";
         return setInitFailureAndCallback(_hidl_cb, temperatures);
     }
 
-    if (!thermal_helper_.fillCurrentTemperatures(filterType, type, &temperatures)) {
+    if (!thermal_helper_->fillCurrentTemperatures(filterType, type, &temperatures)) {
         return setFailureAndCallback(_hidl_cb, temperatures, "Failed to read thermal sensors.");
     }
 
@@ -136,12 +233,12 @@ Return<void> Thermal::getTemperatureThresholds(bool filterType, TemperatureType_
     status.code = ThermalStatusCode::SUCCESS;
     hidl_vec<TemperatureThreshold> temperatures;
 
-    if (!thermal_helper_.isInitializedOk()) {
+    if (!thermal_helper_->isInitializedOk()) {
         LOG(ERROR) << "ThermalHAL not initialized properly.";
         return setInitFailureAndCallback(_hidl_cb, temperatures);
     }
 
-    if (!thermal_helper_.fillTemperatureThresholds(filterType, type, &temperatures)) {
+    if (!thermal_helper_->fillTemperatureThresholds(filterType, type, &temperatures)) {
         return setFailureAndCallback(_hidl_cb, temperatures, "Failed to read thermal sensors.");
     }
 
@@ -155,12 +252,12 @@ Return<void> Thermal::getCurrentCoolingDevices(bool filterType, CoolingType type
     status.code = ThermalStatusCode::SUCCESS;
     hidl_vec<CoolingDevice_2_0> cooling_devices;
 
-    if (!thermal_helper_.isInitializedOk()) {
+    if (!thermal_helper_->isInitializedOk()) {
         LOG(ERROR) << "ThermalHAL not initialized properly.";
         return setInitFailureAndCallback(_hidl_cb, cooling_devices);
     }
 
-    if (!thermal_helper_.fillCurrentCoolingDevices(filterType, type, &cooling_devices)) {
+    if (!thermal_helper_->fillCurrentCoolingDevices(filterType, type, &cooling_devices)) {
         return setFailureAndCallback(_hidl_cb, cooling_devices, "Failed to read thermal sensors.");
     }
 
@@ -241,8 +338,6 @@ void Thermal::sendThermalChangedCallback(const std::vector<Temperature_2_0> &tem
                   << " Type: " << android::hardware::thermal::V2_0::toString(t.type)
                   << " Name: " << t.name << " CurrentValue: " << t.value << " ThrottlingStatus: "
                   << android::hardware::thermal::V2_0::toString(t.throttlingStatus);
-
-        thermal_helper_.sendPowerExtHint(t);
         callbacks_.erase(
             std::remove_if(callbacks_.begin(), callbacks_.end(),
                            [&](const CallbackSetting &c) {
@@ -258,18 +353,32 @@ void Thermal::sendThermalChangedCallback(const std::vector<Temperature_2_0> &tem
     }
 }
 
-Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_string> &) {
+Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_string> &args) {
     if (handle != nullptr && handle->numFds >= 1) {
         int fd = handle->data[0];
         std::ostringstream dump_buf;
 
-        if (!thermal_helper_.isInitializedOk()) {
+        if (!thermal_helper_->isInitializedOk()) {
             dump_buf << "ThermalHAL not initialized properly." << std::endl;
+        } else if (args.size() == 1 && args[0] == "reload") {
+            if (thermal_helper_->reloadConfig()) {
+                dump_buf << "Thermal config reloaded." << std::endl;
+            } else {
+                dump_buf << "Failed to reload thermal config, keep the current one." << std::endl;
+            }
+        } else if (args.size() == 1 && args[0] == "reset_cdev_stats") {
+            thermal_helper_->resetCdevStats();
+            dump_buf << "Cooling device stats reset." << std::endl;
+        } else if (args.size() >= 1 && args[0] == "history") {
+            dumpSensorHistory(thermal_helper_->GetSensorHistoryMap(), args, &dump_buf);
         } else {
+            const auto snapshot = thermal_helper_->GetThermalSnapshot();
+            const auto &config = snapshot.config;
+            const auto &status_map = snapshot.sensor_status_map;
             {
                 hidl_vec<Temperature_1_0> temperatures;
                 dump_buf << "getTemperatures:" << std::endl;
-                if (!thermal_helper_.fillTemperatures(&temperatures)) {
+                if (!thermal_helper_->fillTemperatures(&temperatures)) {
                     dump_buf << "Failed to read thermal sensors." << std::endl;
                 }
 
@@ -284,7 +393,7 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
             {
                 hidl_vec<CpuUsage> cpu_usages;
                 dump_buf << "getCpuUsages:" << std::endl;
-                if (!thermal_helper_.fillCpuUsages(&cpu_usages)) {
+                if (!thermal_helper_->fillCpuUsages(&cpu_usages)) {
                     dump_buf << "Failed to get CPU usages." << std::endl;
                 }
 
@@ -297,7 +406,7 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
             {
                 dump_buf << "getCurrentTemperatures:" << std::endl;
                 hidl_vec<Temperature_2_0> temperatures;
-                if (!thermal_helper_.fillCurrentTemperatures(false, TemperatureType_2_0::SKIN,
+                if (!thermal_helper_->fillCurrentTemperatures(false, TemperatureType_2_0::SKIN,
                                                              &temperatures)) {
                     dump_buf << "Failed to getCurrentTemperatures." << std::endl;
                 }
@@ -313,7 +422,7 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
             {
                 dump_buf << "getTemperatureThresholds:" << std::endl;
                 hidl_vec<TemperatureThreshold> temperatures;
-                if (!thermal_helper_.fillTemperatureThresholds(false, TemperatureType_2_0::SKIN,
+                if (!thermal_helper_->fillTemperatureThresholds(false, TemperatureType_2_0::SKIN,
                                                                &temperatures)) {
                     dump_buf << "Failed to getTemperatureThresholds." << std::endl;
                 }
@@ -336,7 +445,7 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
             {
                 dump_buf << "getCurrentCoolingDevices:" << std::endl;
                 hidl_vec<CoolingDevice_2_0> cooling_devices;
-                if (!thermal_helper_.fillCurrentCoolingDevices(false, CoolingType::CPU,
+                if (!thermal_helper_->fillCurrentCoolingDevices(false, CoolingType::CPU,
                                                                &cooling_devices)) {
                     dump_buf << "Failed to getCurrentCoolingDevices." << std::endl;
                 }
@@ -356,7 +465,7 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
             }
             {
                 dump_buf << "getHysteresis:" << std::endl;
-                const auto &map = thermal_helper_.GetSensorInfoMap();
+                const auto &map = config->sensor_info_map;
                 for (const auto &name_info_pair : map) {
                     dump_buf << " Name: " << name_info_pair.first;
                     dump_buf << " hotHysteresis: [";
@@ -370,18 +479,173 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
                     dump_buf << "]" << std::endl;
                 }
             }
+            {
+                dump_buf << "Profiles:" << std::endl;
+                const std::string active_profile = thermal_helper_->getActiveProfile();
+                dump_buf << " ActiveProfile: "
+                         << (active_profile.empty() ? "default" : active_profile) << std::endl;
+                const auto &map = config->sensor_info_map;
+                for (const auto &name_info_pair : map) {
+                    for (const auto &name_profile_pair : name_info_pair.second.profiles) {
+                        dump_buf << " Name: " << name_info_pair.first
+                                 << " Profile: " << name_profile_pair.first << " hotThresholds: [";
+                        for (const auto &threshold : name_profile_pair.second.hot_thresholds) {
+                            dump_buf << threshold << " ";
+                        }
+                        dump_buf << "] hotHysteresis: [";
+                        for (const auto &hysteresis : name_profile_pair.second.hot_hysteresis) {
+                            dump_buf << hysteresis << " ";
+                        }
+                        dump_buf << "]" << std::endl;
+                    }
+                }
+            }
             {
                 dump_buf << "Monitor:" << std::endl;
-                const auto &map = thermal_helper_.GetSensorInfoMap();
+                const auto &map = config->sensor_info_map;
                 for (const auto &name_info_pair : map) {
                     dump_buf << " Name: " << name_info_pair.first;
                     dump_buf << " Monitor: " << std::boolalpha << name_info_pair.second.is_monitor
                              << std::noboolalpha << std::endl;
                 }
             }
+            {
+                dump_buf << "VirtualSensorInfo:" << std::endl;
+                const auto &map = config->sensor_info_map;
+                for (const auto &name_info_pair : map) {
+                    if (name_info_pair.second.virtual_sensor_info == nullptr) {
+                        continue;
+                    }
+                    const auto &virtual_sensor_info = *name_info_pair.second.virtual_sensor_info;
+                    dump_buf << " Name: " << name_info_pair.first;
+                    dump_buf << " Formula: " << virtual_sensor_info.formula;
+                    dump_buf << " LinkedSensors: [";
+                    for (size_t i = 0; i < virtual_sensor_info.linked_sensors.size(); ++i) {
+                        dump_buf << virtual_sensor_info.linked_sensors[i] << "*"
+                                 << virtual_sensor_info.coefficients[i] << " ";
+                    }
+                    dump_buf << "] Offset: " << virtual_sensor_info.offset << std::endl;
+                }
+            }
+            {
+                dump_buf << "PIDStatus:" << std::endl;
+                const auto &map = config->sensor_info_map;
+                for (const auto &name_info_pair : map) {
+                    if (name_info_pair.second.pid_info == nullptr) {
+                        continue;
+                    }
+                    auto it = status_map.find(name_info_pair.first);
+                    if (it == status_map.end()) {
+                        continue;
+                    }
+                    const auto &pid_info = *name_info_pair.second.pid_info;
+                    const auto &sensor_status = it->second;
+                    dump_buf << " Name: " << name_info_pair.first
+                             << " TargetTemperature: " << pid_info.target_temp
+                             << " Engaged: " << std::boolalpha << sensor_status.pid_engaged
+                             << std::noboolalpha << " PowerBudget: " << sensor_status.power_budget
+                             << " ITerm: " << sensor_status.i_term << " CdevRequests: [";
+                    for (const auto &cdev_request : sensor_status.cdev_requests) {
+                        dump_buf << cdev_request.first << ":" << cdev_request.second << " ";
+                    }
+                    dump_buf << "]" << std::endl;
+                }
+            }
+            {
+                dump_buf << "Prediction:" << std::endl;
+                const auto &map = config->sensor_info_map;
+                for (const auto &name_info_pair : map) {
+                    if (name_info_pair.second.prediction_info == nullptr) {
+                        continue;
+                    }
+                    auto it = status_map.find(name_info_pair.first);
+                    if (it == status_map.end()) {
+                        continue;
+                    }
+                    const auto &prediction_info = *name_info_pair.second.prediction_info;
+                    const auto &sensor_status = it->second;
+                    dump_buf << " Name: " << name_info_pair.first
+                             << " Samples: " << prediction_info.samples
+                             << " Horizon: " << prediction_info.horizon.count() << "ms"
+                             << " Slope: " << sensor_status.slope << "/s"
+                             << " PredictedSeverity: "
+                             << android::hardware::thermal::V2_0::toString(
+                                        sensor_status.predicted_severity)
+                             << std::endl;
+                }
+            }
+            {
+                dump_buf << "SensorFault:" << std::endl;
+                const auto &map = config->sensor_info_map;
+                const auto &fault_map = snapshot.sensor_fault_map;
+                for (const auto &name_info_pair : map) {
+                    auto it = fault_map.find(name_info_pair.first);
+                    if (it == fault_map.end() || name_info_pair.second.fault_info == nullptr) {
+                        continue;
+                    }
+                    const auto &fault_info = *name_info_pair.second.fault_info;
+                    const auto &fault_status = it->second;
+                    dump_buf << " Name: " << name_info_pair.first
+                             << " ValidRange: [" << fault_info.min_value << ", "
+                             << fault_info.max_value << "]"
+                             << " MaxDeltaPerSec: " << fault_info.max_delta_per_sec
+                             << " StuckTimeout: " << fault_info.stuck_timeout.count() << "ms"
+                             << " Substitute: " << fault_info.substitute
+                             << " FallbackValue: " << fault_info.fallback_value
+                             << " Fault: " << toString(fault_status.fault)
+                             << " FaultCount: " << fault_status.fault_count
+                             << " LastValue: " << fault_status.last_value
+                             << " ReportedValue: " << fault_status.reported_value << std::endl;
+                }
+            }
+            {
+                dump_buf << "SensorTransform:" << std::endl;
+                const auto &map = config->sensor_info_map;
+                const auto transform_map = thermal_helper_->GetSensorTransformMap();
+                for (const auto &name_info_pair : map) {
+                    auto it = transform_map.find(name_info_pair.first);
+                    if (it == transform_map.end() ||
+                        name_info_pair.second.transform_info == nullptr) {
+                        continue;
+                    }
+                    const auto &transform_info = *name_info_pair.second.transform_info;
+                    dump_buf << " Name: " << name_info_pair.first
+                             << " Multiplier: " << name_info_pair.second.multiplier
+                             << " Offset: " << transform_info.offset
+                             << " LookupTable: " << transform_info.lut_raw.size() << " points"
+                             << " EMATimeConstant: " << transform_info.ema_time_constant.count()
+                             << "ms RawValue: " << it->second.raw_value
+                             << " ProcessedValue: " << it->second.processed_value << std::endl;
+                }
+            }
+            {
+                dump_buf << "Notification:" << std::endl;
+                dump_buf << " MinInterval: " << config->notification_info.min_interval.count()
+                         << "ms" << std::endl;
+                const auto &map = config->sensor_info_map;
+                size_t total_suppressed = 0;
+                for (const auto &name_info_pair : map) {
+                    auto it = status_map.find(name_info_pair.first);
+                    if (!name_info_pair.second.is_monitor || it == status_map.end()) {
+                        continue;
+                    }
+                    const auto &sensor_status = it->second;
+                    dump_buf << " Name: " << name_info_pair.first << " NotifiedSeverity: "
+                             << android::hardware::thermal::V2_0::toString(
+                                        sensor_status.notified_severity)
+                             << " MinDwellTime: [";
+                    for (const auto &dwell_time : name_info_pair.second.min_dwell_times) {
+                        dump_buf << dwell_time.count() << "ms ";
+                    }
+                    dump_buf << "] SuppressedNotifications: "
+                             << sensor_status.suppressed_notifications << std::endl;
+                    total_suppressed += sensor_status.suppressed_notifications;
+                }
+                dump_buf << " TotalSuppressedNotifications: " << total_suppressed << std::endl;
+            }
             {
                 dump_buf << "SendPowerHint:" << std::endl;
-                const auto &map = thermal_helper_.GetSensorInfoMap();
+                const auto &map = config->sensor_info_map;
                 for (const auto &name_info_pair : map) {
                     dump_buf << " Name: " << name_info_pair.first;
                     dump_buf << " SendPowerHint: " << std::boolalpha
@@ -389,13 +653,50 @@ Return<void> Thermal::debug(const hidl_handle &handle, const hidl_vec<hidl_strin
                              << std::endl;
                 }
             }
+            {
+                dump_buf << "CoolingDeviceStats:" << std::endl;
+                dumpCdevStats(thermal_helper_->GetCdevStatsMap(), &dump_buf);
+            }
+            {
+                dump_buf << "ChargerThrottling:" << std::endl;
+                const auto &map = config->sensor_info_map;
+                for (const auto &name_info_pair : map) {
+                    if (name_info_pair.second.charger_throttling_info == nullptr) {
+                        continue;
+                    }
+                    const auto &charger_throttling_info =
+                            *name_info_pair.second.charger_throttling_info;
+                    dump_buf << " Name: " << name_info_pair.first << " Nodes: [";
+                    for (const auto &node : charger_throttling_info.nodes) {
+                        dump_buf << node << " ";
+                    }
+                    dump_buf << "] CurrentLimit: [";
+                    for (const auto &current_limit : charger_throttling_info.current_limits) {
+                        dump_buf << current_limit << "mA ";
+                    }
+                    dump_buf << "]" << std::endl;
+                }
+                for (const auto &node_limit_pair : thermal_helper_->GetChargerLimitMap()) {
+                    dump_buf << " Node: " << node_limit_pair.first
+                             << " AppliedLimit: " << node_limit_pair.second.current_limit << "mA"
+                             << " Sensor: " << node_limit_pair.second.sensor << " Severity: "
+                             << android::hardware::thermal::V2_0::toString(
+                                        node_limit_pair.second.severity)
+                             << std::endl;
+                }
+            }
+            {
+                dump_buf << "SensorHistoryStats:" << std::endl;
+                dumpSensorHistoryStats(thermal_helper_->GetSensorHistoryMap(),
+                                       kDefaultHistoryWindows, &dump_buf);
+            }
             {
                 dump_buf << "AIDL Power Hal exist: " << std::boolalpha
-                         << thermal_helper_.isAidlPowerHalExist() << std::endl;
+                         << thermal_helper_->isAidlPowerHalExist() << std::endl;
                 dump_buf << "AIDL Power Hal connected: " << std::boolalpha
-                         << thermal_helper_.isPowerHalConnected() << std::endl;
+                         << thermal_helper_->isPowerHalConnected() << std::endl;
                 dump_buf << "AIDL Power Hal Ext connected: " << std::boolalpha
-                         << thermal_helper_.isPowerHalExtConnected() << std::endl;
+                         << thermal_helper_->isPowerHalExtConnected() << std::endl;
             }
         }
         std::string buf = dump_buf.str();
@@ -48,7 +48,7 @@ struct CallbackSetting {
 
 class Thermal : public IThermal {
   public:
-    Thermal();
+    explicit Thermal(const std::shared_ptr<ThermalHelper> &thermal_helper);
     ~Thermal() = default;
 
     // Disallow copy and assign.
@@ -81,7 +81,7 @@ class Thermal : public IThermal {
     void sendThermalChangedCallback(const std::vector<Temperature_2_0> &temps);
 
   private:
-    ThermalHelper thermal_helper_;
+    std::shared_ptr<ThermalHelper> thermal_helper_;
     std::mutex thermal_callback_mutex_;
     std::vector<CallbackSetting> callbacks_;
 };
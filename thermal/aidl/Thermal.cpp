@@ -0,0 +1,291 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#include "Thermal.h"
+
+#include <algorithm>
+#include <sstream>
+
+#include <android-base/file.h>
+#include <android-base/logging.h>
+
+namespace aidl {
+namespace android {
+namespace hardware {
+namespace thermal {
+namespace impl {
+namespace pixel {
+
+namespace {
+
+namespace V2_0 = ::android::hardware::thermal::V2_0;
+using ::android::hardware::hidl_vec;
+
+ndk::ScopedAStatus initErrorStatus() {
+    return ndk::ScopedAStatus::fromExceptionCodeWithMessage(EX_ILLEGAL_STATE,
+                                                            "ThermalHAL not initialized properly.");
+}
+
+ndk::ScopedAStatus readErrorStatus() {
+    return ndk::ScopedAStatus::fromExceptionCodeWithMessage(EX_ILLEGAL_STATE,
+                                                            "Failed to read thermal sensors.");
+}
+
+Temperature toAidlTemperature(const Temperature_2_0 &t) {
+    Temperature temperature;
+    temperature.type = static_cast<TemperatureType>(t.type);
+    temperature.name = t.name;
+    temperature.value = t.value;
+    temperature.throttlingStatus = static_cast<ThrottlingSeverity>(t.throttlingStatus);
+    return temperature;
+}
+
+CoolingDevice toAidlCoolingDevice(const V2_0::CoolingDevice &c) {
+    CoolingDevice cooling_device;
+    cooling_device.type = static_cast<CoolingType>(c.type);
+    cooling_device.name = c.name;
+    cooling_device.value = c.value;
+    return cooling_device;
+}
+
+TemperatureThreshold toAidlTemperatureThreshold(const V2_0::TemperatureThreshold &t) {
+    TemperatureThreshold threshold;
+    threshold.type = static_cast<TemperatureType>(t.type);
+    threshold.name = t.name;
+    threshold.hotThrottlingThresholds.assign(t.hotThrottlingThresholds.data(),
+                                             t.hotThrottlingThresholds.data() +
+                                                     t.hotThrottlingThresholds.size());
+    threshold.coldThrottlingThresholds.assign(t.coldThrottlingThresholds.data(),
+                                              t.coldThrottlingThresholds.data() +
+                                                      t.coldThrottlingThresholds.size());
+    return threshold;
+}
+
+}  // namespace
+
+Thermal::Thermal(const std::shared_ptr<ThermalHelper> &thermal_helper)
+    : thermal_helper_(thermal_helper) {
+    thermal_helper_->addNotificationCallback(
+            std::bind(&Thermal::sendThermalChangedCallback, this, std::placeholders::_1));
+}
+
+ndk::ScopedAStatus Thermal::getCoolingDevices(std::vector<CoolingDevice> *_aidl_return) {
+    return getFilteredCoolingDevices(false, CoolingType::CPU, _aidl_return);
+}
+
+ndk::ScopedAStatus Thermal::getCoolingDevicesWithType(CoolingType type,
+                                                      std::vector<CoolingDevice> *_aidl_return) {
+    return getFilteredCoolingDevices(true, type, _aidl_return);
+}
+
+ndk::ScopedAStatus Thermal::getTemperatures(std::vector<Temperature> *_aidl_return) {
+    return getFilteredTemperatures(false, TemperatureType::UNKNOWN, _aidl_return);
+}
+
+ndk::ScopedAStatus Thermal::getTemperaturesWithType(TemperatureType type,
+                                                    std::vector<Temperature> *_aidl_return) {
+    return getFilteredTemperatures(true, type, _aidl_return);
+}
+
+ndk::ScopedAStatus Thermal::getTemperatureThresholds(
+        std::vector<TemperatureThreshold> *_aidl_return) {
+    return getFilteredTemperatureThresholds(false, TemperatureType::UNKNOWN, _aidl_return);
+}
+
+ndk::ScopedAStatus Thermal::getTemperatureThresholdsWithType(
+        TemperatureType type, std::vector<TemperatureThreshold> *_aidl_return) {
+    return getFilteredTemperatureThresholds(true, type, _aidl_return);
+}
+
+ndk::ScopedAStatus Thermal::registerThermalChangedCallback(
+        const std::shared_ptr<IThermalChangedCallback> &callback) {
+    return registerThermalChangedCallback(callback, false, TemperatureType::UNKNOWN);
+}
+
+ndk::ScopedAStatus Thermal::registerThermalChangedCallbackWithType(
+        const std::shared_ptr<IThermalChangedCallback> &callback, TemperatureType type) {
+    return registerThermalChangedCallback(callback, true, type);
+}
+
+ndk::ScopedAStatus Thermal::unregisterThermalChangedCallback(
+        const std::shared_ptr<IThermalChangedCallback> &callback) {
+    if (callback == nullptr) {
+        return ndk::ScopedAStatus::fromExceptionCodeWithMessage(EX_ILLEGAL_ARGUMENT,
+                                                                "Invalid nullptr callback");
+    }
+    bool removed = false;
+    std::lock_guard<std::mutex> _lock(thermal_callback_mutex_);
+    callbacks_.erase(
+            std::remove_if(callbacks_.begin(), callbacks_.end(),
+                           [&](const CallbackSetting &c) {
+                               if (c.callback->asBinder() == callback->asBinder()) {
+                                   LOG(INFO) << "a callback has been unregistered to ThermalHAL, "
+                                                "isFilter: "
+                                             << c.is_filter_type
+                                             << " Type: " << toString(c.type);
+                                   removed = true;
+                                   return true;
+                               }
+                               return false;
+                           }),
+            callbacks_.end());
+    if (!removed) {
+        return ndk::ScopedAStatus::fromExceptionCodeWithMessage(
+                EX_ILLEGAL_ARGUMENT, "The callback was not registered before");
+    }
+    return ndk::ScopedAStatus::ok();
+}
+
+ndk::ScopedAStatus Thermal::getFilteredCoolingDevices(bool filterType, CoolingType type,
+                                                      std::vector<CoolingDevice> *_aidl_return) {
+    if (!thermal_helper_->isInitializedOk()) {
+        LOG(ERROR) << "ThermalHAL not initialized properly.";
+        return initErrorStatus();
+    }
+    hidl_vec<V2_0::CoolingDevice> cooling_devices;
+    if (!thermal_helper_->fillCurrentCoolingDevices(
+                filterType, static_cast<V2_0::CoolingType>(type), &cooling_devices)) {
+        return readErrorStatus();
+    }
+    _aidl_return->clear();
+    for (const auto &c : cooling_devices) {
+        _aidl_return->push_back(toAidlCoolingDevice(c));
+    }
+    return ndk::ScopedAStatus::ok();
+}
+
+ndk::ScopedAStatus Thermal::getFilteredTemperatures(bool filterType, TemperatureType type,
+                                                    std::vector<Temperature> *_aidl_return) {
+    if (!thermal_helper_->isInitializedOk()) {
+        LOG(ERROR) << "ThermalHAL not initialized properly.";
+        return initErrorStatus();
+    }
+    hidl_vec<Temperature_2_0> temperatures;
+    if (!thermal_helper_->fillCurrentTemperatures(
+                filterType, static_cast<V2_0::TemperatureType>(type), &temperatures)) {
+        return readErrorStatus();
+    }
+    _aidl_return->clear();
+    for (const auto &t : temperatures) {
+        _aidl_return->push_back(toAidlTemperature(t));
+    }
+    return ndk::ScopedAStatus::ok();
+}
+
+ndk::ScopedAStatus Thermal::getFilteredTemperatureThresholds(
+        bool filterType, TemperatureType type, std::vector<TemperatureThreshold> *_aidl_return) {
+    if (!thermal_helper_->isInitializedOk()) {
+        LOG(ERROR) << "ThermalHAL not initialized properly.";
+        return initErrorStatus();
+    }
+    hidl_vec<V2_0::TemperatureThreshold> thresholds;
+    if (!thermal_helper_->fillTemperatureThresholds(
+                filterType, static_cast<V2_0::TemperatureType>(type), &thresholds)) {
+        return readErrorStatus();
+    }
+    _aidl_return->clear();
+    for (const auto &t : thresholds) {
+        _aidl_return->push_back(toAidlTemperatureThreshold(t));
+    }
+    return ndk::ScopedAStatus::ok();
+}
+
+ndk::ScopedAStatus Thermal::registerThermalChangedCallback(
+        const std::shared_ptr<IThermalChangedCallback> &callback, bool filterType,
+        TemperatureType type) {
+    if (callback == nullptr) {
+        return ndk::ScopedAStatus::fromExceptionCodeWithMessage(EX_ILLEGAL_ARGUMENT,
+                                                                "Invalid nullptr callback");
+    }
+    std::lock_guard<std::mutex> _lock(thermal_callback_mutex_);
+    if (std::any_of(callbacks_.begin(), callbacks_.end(), [&](const CallbackSetting &c) {
+            return c.callback->asBinder() == callback->asBinder();
+        })) {
+        return ndk::ScopedAStatus::fromExceptionCodeWithMessage(
+                EX_ILLEGAL_ARGUMENT, "Same callback registered already");
+    }
+    callbacks_.emplace_back(callback, filterType, type);
+    LOG(INFO) << "a callback has been registered to ThermalHAL, isFilter: " << filterType
+              << " Type: " << toString(type);
+    return ndk::ScopedAStatus::ok();
+}
+
+void Thermal::sendThermalChangedCallback(const std::vector<Temperature_2_0> &temps) {
+    std::lock_guard<std::mutex> _lock(thermal_callback_mutex_);
+    for (const auto &t : temps) {
+        const Temperature temperature = toAidlTemperature(t);
+        callbacks_.erase(
+                std::remove_if(callbacks_.begin(), callbacks_.end(),
+                               [&](const CallbackSetting &c) {
+                                   if (c.is_filter_type && temperature.type != c.type) {
+                                       return false;
+                                   }
+                                   if (!c.callback->notifyThrottling(temperature).isOk()) {
+                                       LOG(ERROR) << "a Thermal callback is dead, removed from "
+                                                     "callback list.";
+                                       return true;
+                                   }
+                                   return false;
+                               }),
+                callbacks_.end());
+    }
+}
+
+// The full dump is served by the HIDL front-end, lshal debug
+// android.hardware.thermal@2.0::IThermal/default
+binder_status_t Thermal::dump(int fd, const char **, uint32_t) {
+    std::ostringstream dump_buf;
+    if (!thermal_helper_->isInitializedOk()) {
+        dump_buf << "ThermalHAL not initialized properly." << std::endl;
+    } else {
+        std::vector<Temperature> temperatures;
+        dump_buf << "getTemperatures:" << std::endl;
+        if (!getTemperatures(&temperatures).isOk()) {
+            dump_buf << "Failed to read thermal sensors." << std::endl;
+        }
+        for (const auto &t : temperatures) {
+            dump_buf << " " << t.toString() << std::endl;
+        }
+
+        std::vector<CoolingDevice> cooling_devices;
+        dump_buf << "getCoolingDevices:" << std::endl;
+        if (!getCoolingDevices(&cooling_devices).isOk()) {
+            dump_buf << "Failed to read cooling devices." << std::endl;
+        }
+        for (const auto &c : cooling_devices) {
+            dump_buf << " " << c.toString() << std::endl;
+        }
+
+        std::lock_guard<std::mutex> _lock(thermal_callback_mutex_);
+        dump_buf << "Callbacks: Total " << callbacks_.size() << std::endl;
+        for (const auto &c : callbacks_) {
+            dump_buf << " IsFilter: " << c.is_filter_type << " Type: " << toString(c.type)
+                     << std::endl;
+        }
+    }
+    if (!::android::base::WriteStringToFd(dump_buf.str(), fd)) {
+        PLOG(ERROR) << "Failed to dump state to fd";
+    }
+    fsync(fd);
+    return STATUS_OK;
+}
+
+}  // namespace pixel
+}  // namespace impl
+}  // namespace thermal
+}  // namespace hardware
+}  // namespace android
+}  // namespace aidl
@@ -0,0 +1,63 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#include <android-base/logging.h>
+#include <android/binder_manager.h>
+#include <android/binder_process.h>
+#include <hidl/HidlTransportSupport.h>
+
+#include "../Thermal.h"
+#include "Thermal.h"
+
+using ::android::OK;
+using ::android::status_t;
+using ::android::hardware::configureRpcThreadpool;
+using ::android::hardware::thermal::V2_0::implementation::ThermalHelper;
+
+using ThermalAidl = ::aidl::android::hardware::thermal::impl::pixel::Thermal;
+using ThermalHidl = ::android::hardware::thermal::V2_0::implementation::Thermal;
+
+int main() {
+    LOG(INFO) << "Pixel Thermal HAL AIDL Service starting...";
+
+    // Both front-ends share the same backend so sensors are only monitored once
+    std::shared_ptr<ThermalHelper> thermal_helper = std::make_shared<ThermalHelper>();
+
+    // HIDL front-end stays registered while clients migrate to AIDL
+    configureRpcThreadpool(1, false /* callerWillJoin */);
+    ::android::sp<ThermalHidl> hidl_service = new ThermalHidl(thermal_helper);
+    status_t status = hidl_service->registerAsService();
+    if (status != OK) {
+        LOG(ERROR) << "Could not register HIDL service for ThermalHAL (" << status << ")";
+    }
+
+    // single thread
+    ABinderProcess_setThreadPoolMaxThreadCount(0);
+
+    std::shared_ptr<ThermalAidl> aidl_service =
+            ndk::SharedRefBase::make<ThermalAidl>(thermal_helper);
+    const std::string instance = std::string() + ThermalAidl::descriptor + "/default";
+    binder_status_t aidl_status =
+            AServiceManager_addService(aidl_service->asBinder().get(), instance.c_str());
+    CHECK(aidl_status == STATUS_OK);
+    LOG(INFO) << "Pixel Thermal HAL AIDL Service started successfully.";
+
+    ABinderProcess_joinThreadPool();
+
+    // should not reach
+    LOG(ERROR) << "Pixel Thermal HAL AIDL Service just died.";
+    return EXIT_FAILURE;
+}
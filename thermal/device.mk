@@ -1,6 +1,11 @@
 # Thermal HAL
+ifeq ($(TARGET_USES_AIDL_THERMAL_HAL),true)
 PRODUCT_PACKAGES += \
-    android.hardware.thermal@2.0-service.pixel \
+    android.hardware.thermal-service.pixel
+else
+PRODUCT_PACKAGES += \
+    android.hardware.thermal@2.0-service.pixel
+endif
 
 ifneq (,$(filter userdebug eng, $(TARGET_BUILD_VARIANT)))
 PRODUCT_PACKAGES += \
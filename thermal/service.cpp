@@ -27,6 +27,7 @@ using ::android::hardware::joinRpcThreadpool;
 // Generated HIDL files:
 using ::android::hardware::thermal::V2_0::IThermal;
 using ::android::hardware::thermal::V2_0::implementation::Thermal;
+using ::android::hardware::thermal::V2_0::implementation::ThermalHelper;
 
 static int shutdown() {
     LOG(ERROR) << "Pixel Thermal HAL Service is shutting down.";
@@ -39,7 +40,7 @@ int main(int /* argc */, char ** /* argv */) {
 
     LOG(INFO) << "Pixel Thermal HAL Service 2.0 starting...";
 
-    service = new Thermal();
+    service = new Thermal(std::make_shared<ThermalHelper>());
     if (service == nullptr) {
         LOG(ERROR) << "Error creating an instance of Thermal HAL. Exiting...";
         return shutdown();
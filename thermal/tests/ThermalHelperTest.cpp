@@ -0,0 +1,499 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#include <sys/stat.h>
+
+#include <chrono>
+#include <cmath>
+#include <cstdio>
+#include <condition_variable>
+#include <memory>
+#include <mutex>
+#include <thread>
+#include <vector>
+
+#include <android-base/file.h>
+#include <android-base/logging.h>
+#include <android-base/stringprintf.h>
+#include <android-base/strings.h>
+#include <gtest/gtest.h>
+
+#include "../thermal-helper.h"
+
+namespace android {
+namespace hardware {
+namespace thermal {
+namespace V2_0 {
+namespace implementation {
+
+using namespace std::chrono_literals;
+
+// Long enough for a few watcher callbacks at the 100ms polling delay of the config
+constexpr auto kCallbackTimeout = 1000ms;
+
+// JSON_CONFIG
+// {
+//     "Sensors": [
+//         {
+//             "Name": "skin",
+//             "Type": "SKIN",
+//             "HotThreshold": ["NAN", 39.0, 43.0, 45.0, 47.0, 50.0, 60.0],
+//             "HotHysteresis": [0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+//             "VrThreshold": "NAN",
+//             "Multiplier": 0.001,
+//             "Monitor": true,
+//             "PollingDelay": [100, 100, 100, 100, 100, 100, 100],
+//             "Profiles": [
+//                 {
+//                     "Name": "gaming",
+//                     "HotThreshold": ["NAN", 45.0, 47.0, 49.0, 51.0, 53.0, 60.0]
+//                 }
+//             ]
+//         },
+//         {
+//             "Name": "battery",
+//             "Type": "BATTERY",
+//             "HotThreshold": ["NAN", "NAN", "NAN", "NAN", "NAN", "NAN", 60.0],
+//             "VrThreshold": "NAN",
+//             "Multiplier": 1.0,
+//             "Transform": {
+//                 "Offset": 1.0,
+//                 "LookupTable": {
+//                     "Raw": [1000, 2000],
+//                     "Value": [60.0, 20.0]
+//                 }
+//             }
+//         }
+//     ],
+//     "CoolingDevices": [
+//         {
+//             "Name": "fan",
+//             "Type": "FAN"
+//         },
+//         {
+//             "Name": "cpu0",
+//             "Type": "CPU"
+//         }
+//     ]
+// }
+constexpr char kJSON_RAW[] =
+        "{\"Sensors\":[{\"Name\":\"skin\",\"Type\":\"SKIN\",\"HotThreshold\":[\"NAN\",39.0,43.0,45."
+        "0,47.0,50.0,60.0],\"HotHysteresis\":[0.0,1.0,1.0,1.0,1.0,1.0,1.0],\"VrThreshold\":\"NAN\","
+        "\"Multiplier\":0.001,\"Monitor\":true,\"PollingDelay\":[100,100,100,100,100,100,100],\"Pro"
+        "files\":[{\"Name\":\"gaming\",\"HotThreshold\":[\"NAN\",45.0,47.0,49.0,51.0,53.0,60.0]}]},"
+        "{\"Name\":\"battery\",\"Type\":\"BATTERY\",\"HotThreshold\":[\"NAN\",\"NAN\",\"NAN\",\"NAN"
+        "\",\"NAN\",\"NAN\",60.0],\"VrThreshold\":\"NAN\",\"Multiplier\":1.0,\"Transform\":{\"Offse"
+        "t\":1.0,\"LookupTable\":{\"Raw\":[1000,2000],\"Value\":[60.0,20.0]}}}],\"CoolingDevices\":"
+        "[{\"Name\":\"fan\",\"Type\":\"FAN\"},{\"Name\":\"cpu0\",\"Type\":\"CPU\"}]}";
+
+class ThermalHelperTest : public ::testing::Test {
+  protected:
+    virtual void SetUp() {
+        android::base::SetMinimumLogSeverity(android::base::VERBOSE);
+        // Fake /sys/devices/virtual/thermal
+        skin_temp_ = addThermalDir("thermal_zone0", "skin", "temp", "25000");
+        battery_temp_ = addThermalDir("thermal_zone1", "battery", "temp", "1775");
+        fan_state_ = addThermalDir("cooling_device0", "fan", "cur_state", "0");
+        cpu_state_ = addThermalDir("cooling_device1", "cpu0", "cur_state", "0");
+        createThermalHelper(kJSON_RAW);
+    }
+
+    virtual void TearDown() {
+        thermal_helper_.reset();
+        // TemporaryDir only removes an empty directory
+        for (auto it = created_paths_.rbegin(); it != created_paths_.rend(); ++it) {
+            remove(it->c_str());
+        }
+    }
+
+    // (Re)create the thermal helper from the config, notifying onThermalChanged.
+    void createThermalHelper(const std::string &json_doc) {
+        thermal_helper_.reset();
+        ASSERT_TRUE(android::base::WriteStringToFile(json_doc, config_.path));
+        thermal_helper_ = std::make_unique<ThermalHelper>(thermal_root_.path, config_.path);
+        ASSERT_TRUE(thermal_helper_->isInitializedOk());
+        thermal_helper_->addNotificationCallback(
+                std::bind(&ThermalHelperTest::onThermalChanged, this, std::placeholders::_1));
+    }
+
+    // Create a thermal zone or cooling device directory holding the name in its
+    // type file and the value in value_file, return the path of value_file.
+    std::string addThermalDir(const std::string &dir, const std::string &name,
+                              const std::string &value_file, const std::string &value) {
+        const std::string dir_path =
+                android::base::StringPrintf("%s/%s", thermal_root_.path, dir.c_str());
+        EXPECT_EQ(0, mkdir(dir_path.c_str(), 0755)) << strerror(errno);
+        created_paths_.emplace_back(dir_path);
+        const std::string type_path = dir_path + "/type";
+        EXPECT_TRUE(android::base::WriteStringToFile(name, type_path));
+        created_paths_.emplace_back(type_path);
+        const std::string value_path = dir_path + "/" + value_file;
+        EXPECT_TRUE(android::base::WriteStringToFile(value, value_path));
+        created_paths_.emplace_back(value_path);
+        return value_path;
+    }
+
+    void onThermalChanged(const std::vector<Temperature_2_0> &temps) {
+        std::lock_guard<std::mutex> _lock(notified_mutex_);
+        notified_temps_.insert(notified_temps_.end(), temps.begin(), temps.end());
+        notified_cv_.notify_all();
+    }
+
+    // Wait for the sensor to be notified at the severity, return false on timeout.
+    bool waitForNotification(const std::string &name, ThrottlingSeverity severity) {
+        std::unique_lock<std::mutex> _lock(notified_mutex_);
+        return notified_cv_.wait_for(_lock, kCallbackTimeout, [&] {
+            for (const auto &t : notified_temps_) {
+                if (t.name == name && t.throttlingStatus == severity) {
+                    return true;
+                }
+            }
+            return false;
+        });
+    }
+
+    // Forget the notifications so far, so a later wait only sees new ones.
+    void clearNotifications() {
+        std::lock_guard<std::mutex> _lock(notified_mutex_);
+        notified_temps_.clear();
+    }
+
+    // Wait for the file to hold the value, return false on timeout.
+    bool waitForFileContent(const std::string &path, const std::string &value) {
+        const auto end_time = std::chrono::steady_clock::now() + kCallbackTimeout;
+        std::string data;
+        while (std::chrono::steady_clock::now() < end_time) {
+            if (android::base::ReadFileToString(path, &data) &&
+                android::base::Trim(data) == value) {
+                return true;
+            }
+            std::this_thread::sleep_for(10ms);
+        }
+        return false;
+    }
+
+    size_t getNotificationCount() {
+        std::lock_guard<std::mutex> _lock(notified_mutex_);
+        return notified_temps_.size();
+    }
+
+    TemporaryDir thermal_root_;
+    TemporaryFile config_;
+    std::string skin_temp_;
+    std::string battery_temp_;
+    std::string fan_state_;
+    std::string cpu_state_;
+    std::vector<std::string> created_paths_;
+    std::unique_ptr<ThermalHelper> thermal_helper_;
+
+    std::mutex notified_mutex_;
+    std::condition_variable notified_cv_;
+    std::vector<Temperature_2_0> notified_temps_;
+};
+
+// Test fillCurrentTemperatures reads all sensors and filters by type
+TEST_F(ThermalHelperTest, FillCurrentTemperaturesTest) {
+    hidl_vec<Temperature_2_0> temps;
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(false, TemperatureType_2_0::UNKNOWN,
+                                                         &temps));
+    ASSERT_EQ(2u, temps.size());
+    for (const auto &t : temps) {
+        if (t.name == "skin") {
+            EXPECT_EQ(TemperatureType_2_0::SKIN, t.type);
+            EXPECT_FLOAT_EQ(25.0, t.value);
+        } else {
+            EXPECT_EQ("battery", t.name);
+            EXPECT_EQ(TemperatureType_2_0::BATTERY, t.type);
+            EXPECT_FLOAT_EQ(30.0, t.value);
+        }
+        EXPECT_EQ(ThrottlingSeverity::NONE, t.throttlingStatus);
+    }
+
+    EXPECT_TRUE(android::base::WriteStringToFile("41500", skin_temp_));
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_EQ("skin", temps[0].name);
+    EXPECT_FLOAT_EQ(41.5, temps[0].value);
+    EXPECT_EQ(ThrottlingSeverity::LIGHT, temps[0].throttlingStatus);
+
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::CPU, &temps));
+    EXPECT_EQ(0u, temps.size());
+}
+
+// Test fillTemperatureThresholds reports the configured thresholds
+TEST_F(ThermalHelperTest, FillTemperatureThresholdsTest) {
+    hidl_vec<TemperatureThreshold> thresholds;
+    EXPECT_TRUE(thermal_helper_->fillTemperatureThresholds(true, TemperatureType_2_0::SKIN,
+                                                           &thresholds));
+    ASSERT_EQ(1u, thresholds.size());
+    EXPECT_EQ("skin", thresholds[0].name);
+    EXPECT_TRUE(std::isnan(
+            thresholds[0].hotThrottlingThresholds[static_cast<size_t>(ThrottlingSeverity::NONE)]));
+    EXPECT_FLOAT_EQ(43.0, thresholds[0].hotThrottlingThresholds[static_cast<size_t>(
+                                  ThrottlingSeverity::MODERATE)]);
+    EXPECT_FLOAT_EQ(60.0, thresholds[0].hotThrottlingThresholds[static_cast<size_t>(
+                                  ThrottlingSeverity::SHUTDOWN)]);
+
+    EXPECT_TRUE(thermal_helper_->fillTemperatureThresholds(false, TemperatureType_2_0::UNKNOWN,
+                                                           &thresholds));
+    EXPECT_EQ(2u, thresholds.size());
+}
+
+// Test fillCurrentCoolingDevices reads cur_state and filters by type
+TEST_F(ThermalHelperTest, FillCurrentCoolingDevicesTest) {
+    EXPECT_TRUE(android::base::WriteStringToFile("2", fan_state_));
+    hidl_vec<CoolingDevice_2_0> cdevs;
+    EXPECT_TRUE(thermal_helper_->fillCurrentCoolingDevices(false, CoolingType::CPU, &cdevs));
+    EXPECT_EQ(2u, cdevs.size());
+
+    EXPECT_TRUE(thermal_helper_->fillCurrentCoolingDevices(true, CoolingType::FAN, &cdevs));
+    ASSERT_EQ(1u, cdevs.size());
+    EXPECT_EQ("fan", cdevs[0].name);
+    EXPECT_EQ(2u, cdevs[0].value);
+}
+
+// Test severity changes of a monitored sensor are notified
+TEST_F(ThermalHelperTest, SeverityCallbackTest) {
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+
+    EXPECT_TRUE(android::base::WriteStringToFile("61000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::SHUTDOWN));
+
+    EXPECT_TRUE(android::base::WriteStringToFile("25000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::NONE));
+
+    // battery is not monitored, it is never notified
+    EXPECT_TRUE(android::base::WriteStringToFile("61000", battery_temp_));
+    EXPECT_FALSE(waitForNotification("battery", ThrottlingSeverity::SHUTDOWN));
+}
+
+// Test a lower severity is only reached once below threshold - hysteresis
+TEST_F(ThermalHelperTest, HysteresisTest) {
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+    const size_t count = getNotificationCount();
+
+    // Below the MODERATE threshold but within its hysteresis
+    EXPECT_TRUE(android::base::WriteStringToFile("42500", skin_temp_));
+    EXPECT_FALSE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+    EXPECT_EQ(count, getNotificationCount());
+    hidl_vec<Temperature_2_0> temps;
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_EQ(ThrottlingSeverity::MODERATE, temps[0].throttlingStatus);
+
+    EXPECT_TRUE(android::base::WriteStringToFile("41500", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_EQ(ThrottlingSeverity::LIGHT, temps[0].throttlingStatus);
+}
+
+// Test the transform maps the raw reading through the lookup table and offset
+TEST_F(ThermalHelperTest, TransformTest) {
+    hidl_vec<Temperature_2_0> temps;
+    EXPECT_TRUE(android::base::WriteStringToFile("1500", battery_temp_));
+    EXPECT_TRUE(
+            thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::BATTERY, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_FLOAT_EQ(41.0, temps[0].value);
+    auto transform_map = thermal_helper_->GetSensorTransformMap();
+    ASSERT_EQ(1u, transform_map.count("battery"));
+    EXPECT_FLOAT_EQ(1500.0, transform_map.at("battery").raw_value);
+    EXPECT_FLOAT_EQ(41.0, transform_map.at("battery").processed_value);
+
+    // Out of the table, clamped to its end
+    EXPECT_TRUE(android::base::WriteStringToFile("500", battery_temp_));
+    EXPECT_TRUE(
+            thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::BATTERY, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_FLOAT_EQ(61.0, temps[0].value);
+}
+
+// Test switching the profile re-evaluates the sensors under its thresholds
+TEST_F(ThermalHelperTest, ProfileTest) {
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+
+    EXPECT_FALSE(thermal_helper_->setProfile("no_such_profile"));
+    EXPECT_EQ("", thermal_helper_->getActiveProfile());
+
+    EXPECT_TRUE(thermal_helper_->setProfile("gaming"));
+    EXPECT_EQ("gaming", thermal_helper_->getActiveProfile());
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::NONE));
+    hidl_vec<TemperatureThreshold> thresholds;
+    EXPECT_TRUE(thermal_helper_->fillTemperatureThresholds(true, TemperatureType_2_0::SKIN,
+                                                           &thresholds));
+    ASSERT_EQ(1u, thresholds.size());
+    EXPECT_FLOAT_EQ(47.0, thresholds[0].hotThrottlingThresholds[static_cast<size_t>(
+                                  ThrottlingSeverity::MODERATE)]);
+
+    clearNotifications();
+    EXPECT_TRUE(thermal_helper_->setProfile(""));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+}
+
+// Test a lower severity is held for the dwell time of the notified one, and a
+// change reverted before it goes out is counted as suppressed
+TEST_F(ThermalHelperTest, NotificationDwellTimeTest) {
+    std::string json_doc = kJSON_RAW;
+    const std::string from = "\"Monitor\":true,";
+    json_doc.replace(json_doc.find(from), from.length(),
+                     "\"Monitor\":true,\"MinDwellTime\":[0,0,1.5,0,0,0,0],");
+    createThermalHelper(json_doc);
+
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+    EXPECT_TRUE(android::base::WriteStringToFile("41500", skin_temp_));
+    EXPECT_FALSE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+    EXPECT_EQ(0u, thermal_helper_->GetSensorStatusMap().at("skin").suppressed_notifications);
+
+    // Back to MODERATE before LIGHT is notified
+    clearNotifications();
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+    EXPECT_TRUE(android::base::WriteStringToFile("41500", skin_temp_));
+    std::this_thread::sleep_for(300ms);
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_FALSE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+    EXPECT_EQ(1u, getNotificationCount());
+    EXPECT_EQ(1u, thermal_helper_->GetSensorStatusMap().at("skin").suppressed_notifications);
+}
+
+// Test notifications closer than MinInterval are held back, except an
+// escalation to SEVERE or above
+TEST_F(ThermalHelperTest, NotificationRateLimitTest) {
+    std::string json_doc = kJSON_RAW;
+    json_doc.insert(json_doc.rfind('}'), ",\"Notification\":{\"MinInterval\":1.5}");
+    createThermalHelper(json_doc);
+
+    EXPECT_TRUE(android::base::WriteStringToFile("44000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::MODERATE));
+    EXPECT_TRUE(android::base::WriteStringToFile("41500", skin_temp_));
+    EXPECT_FALSE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::LIGHT));
+
+    // Within MinInterval of the LIGHT notification, shorter than the wait timeout
+    clearNotifications();
+    EXPECT_TRUE(android::base::WriteStringToFile("46000", skin_temp_));
+    EXPECT_TRUE(waitForNotification("skin", ThrottlingSeverity::SEVERE));
+    EXPECT_EQ(1u, getNotificationCount());
+}
+
+// Test the PID controller throttles the binded cooling device and retries a
+// cur_state write that failed
+TEST_F(ThermalHelperTest, PidCoolingDeviceTest) {
+    std::string json_doc = kJSON_RAW;
+    std::string from = "\"Monitor\":true,";
+    json_doc.replace(json_doc.find(from), from.length(),
+                     "\"Monitor\":true,\"PIDInfo\":{\"TargetTemperature\":40.0,"
+                     "\"SwitchOnTemperature\":35.0,\"K_P\":100.0,\"PowerBudget\":1000.0,"
+                     "\"BindedCdev\":[\"fan\"]},");
+    from = "{\"Name\":\"fan\",\"Type\":\"FAN\"}";
+    json_doc.replace(json_doc.find(from), from.length(),
+                     "{\"Name\":\"fan\",\"Type\":\"FAN\",\"State2Power\":[1000.0,500.0,0.0]}");
+    createThermalHelper(json_doc);
+
+    // Budget 1000 - 100 * 2 fits into state 1
+    EXPECT_TRUE(android::base::WriteStringToFile("42000", skin_temp_));
+    EXPECT_TRUE(waitForFileContent(fan_state_, "1"));
+    EXPECT_TRUE(android::base::WriteStringToFile("35000", skin_temp_));
+    EXPECT_TRUE(waitForFileContent(fan_state_, "0"));
+
+    // cur_state cannot be written while it is a directory
+    ASSERT_EQ(0, remove(fan_state_.c_str()));
+    ASSERT_EQ(0, mkdir(fan_state_.c_str(), 0755));
+    EXPECT_TRUE(android::base::WriteStringToFile("50000", skin_temp_));
+    std::this_thread::sleep_for(300ms);
+    ASSERT_EQ(0, rmdir(fan_state_.c_str()));
+    EXPECT_TRUE(android::base::WriteStringToFile("0", fan_state_));
+    // Budget 1000 - 100 * 10 only fits into state 2
+    EXPECT_TRUE(waitForFileContent(fan_state_, "2"));
+}
+
+// Test the parser rejects sensors reading themselves through substitutes or
+// linked sensors
+TEST_F(ThermalHelperTest, SensorCycleTest) {
+    const std::string from = "\"Name\":\"battery\",";
+    std::string json_doc = kJSON_RAW;
+    json_doc.replace(json_doc.find(from), from.length(),
+                     "\"Name\":\"battery\",\"FaultDetection\":{\"Substitute\":\"skin\"},");
+    ASSERT_TRUE(android::base::WriteStringToFile(json_doc, config_.path));
+    EXPECT_EQ(2u, ParseSensorInfo(config_.path).size());
+
+    // skin and battery substitute each other
+    std::string cycle_doc = json_doc;
+    cycle_doc.insert(cycle_doc.find("\"Monitor\""),
+                     "\"FaultDetection\":{\"Substitute\":\"battery\"},");
+    ASSERT_TRUE(android::base::WriteStringToFile(cycle_doc, config_.path));
+    EXPECT_TRUE(ParseSensorInfo(config_.path).empty());
+
+    // skin substitutes a virtual sensor linking battery, which substitutes skin
+    cycle_doc = json_doc;
+    cycle_doc.insert(cycle_doc.find("\"Monitor\""),
+                     "\"FaultDetection\":{\"Substitute\":\"virtual\"},");
+    cycle_doc.insert(cycle_doc.find("],\"CoolingDevices\""),
+                     ",{\"Name\":\"virtual\",\"Type\":\"SKIN\",\"HotThreshold\":[\"NAN\","
+                     "\"NAN\",\"NAN\",\"NAN\",\"NAN\",\"NAN\",\"NAN\"],\"VirtualSensor\""
+                     ":true,\"Formula\":\"MAXIMUM\",\"Combination\":[\"battery\"]}");
+    ASSERT_TRUE(android::base::WriteStringToFile(cycle_doc, config_.path));
+    EXPECT_TRUE(ParseSensorInfo(config_.path).empty());
+}
+
+// Test an out of range reading falls back to the substitute sensor, then to
+// the fallback value once the substitute can not be read either
+TEST_F(ThermalHelperTest, SensorFaultFallbackTest) {
+    std::string json_doc = kJSON_RAW;
+    json_doc.insert(json_doc.find("\"Monitor\""),
+                    "\"FaultDetection\":{\"ValidRange\":[-20.0,100.0],\"Substitute\":"
+                    "\"battery\",\"FallbackValue\":20.0},");
+    createThermalHelper(json_doc);
+
+    hidl_vec<Temperature_2_0> temps;
+    EXPECT_TRUE(android::base::WriteStringToFile("-40000", skin_temp_));
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_FLOAT_EQ(30.0, temps[0].value);
+    auto fault_map = thermal_helper_->GetSensorFaultMap();
+    ASSERT_EQ(1u, fault_map.count("skin"));
+    EXPECT_EQ(SensorFault::OUT_OF_RANGE, fault_map.at("skin").fault);
+    EXPECT_FLOAT_EQ(30.0, fault_map.at("skin").reported_value);
+
+    EXPECT_TRUE(android::base::WriteStringToFile("", battery_temp_));
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_FLOAT_EQ(20.0, temps[0].value);
+
+    // Above the valid range the reading is kept
+    EXPECT_TRUE(android::base::WriteStringToFile("150000", skin_temp_));
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_FLOAT_EQ(150.0, temps[0].value);
+
+    EXPECT_TRUE(android::base::WriteStringToFile("25000", skin_temp_));
+    EXPECT_TRUE(thermal_helper_->fillCurrentTemperatures(true, TemperatureType_2_0::SKIN, &temps));
+    ASSERT_EQ(1u, temps.size());
+    EXPECT_FLOAT_EQ(25.0, temps[0].value);
+    EXPECT_EQ(SensorFault::NONE, thermal_helper_->GetSensorFaultMap().at("skin").fault);
+}
+
+}  // namespace implementation
+}  // namespace V2_0
+}  // namespace thermal
+}  // namespace hardware
+}  // namespace android
@@ -14,18 +14,27 @@
  * limitations under the License.
  */
 
+#include <algorithm>
+#include <cmath>
 #include <iterator>
+#include <limits>
+#include <numeric>
 #include <set>
 #include <sstream>
 #include <thread>
 #include <vector>
 
+#include <sys/system_properties.h>
+
 #include <android-base/file.h>
 #include <android-base/logging.h>
+#include <android-base/parseint.h>
 #include <android-base/properties.h>
 #include <android-base/stringprintf.h>
 #include <android-base/strings.h>
 #include <android/binder_manager.h>
+#include <android/frameworks/stats/1.0/IStats.h>
+#include <hardware/google/pixel/pixelstats/pixelatoms.pb.h>
 #include <hidl/HidlTransportSupport.h>
 
 #include "thermal-helper.h"
@@ -52,11 +61,23 @@ constexpr std::string_view kUserSpaceSuffix("user_space");
 constexpr std::string_view kCoolingDeviceCurStateSuffix("cur_state");
 constexpr std::string_view kConfigProperty("vendor.thermal.config");
 constexpr std::string_view kConfigDefaultFileName("thermal_info_config.json");
+// Set directly, or by the PowerHAL through a Property node for a PowerExt mode
+constexpr std::string_view kProfileProperty("vendor.thermal.profile");
+// How often the profile watcher thread checks for exit while the property is unchanged
+constexpr struct timespec kProfileWaitTimeout = {.tv_sec = 1, .tv_nsec = 0};
+constexpr size_t kSensorHistorySize = 512;
+constexpr std::chrono::hours kThermalStatsReportInterval(24);
 
 namespace {
 using android::base::StringPrintf;
+using android::frameworks::stats::V1_0::IStats;
+using android::hardware::google::pixel::PixelAtoms::ThermalSensorStats;
+namespace PixelAtoms = android::hardware::google::pixel::PixelAtoms;
 using android::hardware::thermal::V2_0::toString;
 
+// Proto messages are 1-indexed and VendorAtom field numbers start at 2
+constexpr int kVendorAtomOffset = 2;
+
 /*
  * Pixel don't offline CPU, so std::thread::hardware_concurrency(); should work.
  * However /sys/devices/system/cpu/present is preferred.
@@ -134,9 +155,10 @@ void parseCpuUsagesFileAndAssignUsages(hidl_vec<CpuUsage> *cpu_usages) {
     }
 }
 
-std::map<std::string, std::string> parseThermalPathMap(std::string_view prefix) {
+std::map<std::string, std::string> parseThermalPathMap(std::string_view root,
+                                                       std::string_view prefix) {
     std::map<std::string, std::string> path_map;
-    std::unique_ptr<DIR, int (*)(DIR *)> dir(opendir(kThermalSensorsRoot.data()), closedir);
+    std::unique_ptr<DIR, int (*)(DIR *)> dir(opendir(root.data()), closedir);
     if (!dir) {
         return path_map;
     }
@@ -152,23 +174,321 @@ std::map<std::string, std::string> parseThermalPathMap(std::string_view prefix)
             continue;
         }
 
-        std::string path = android::base::StringPrintf("%s/%s/%s", kThermalSensorsRoot.data(),
-                                                       dp->d_name, kThermalNameFile.data());
+        std::string path = android::base::StringPrintf("%s/%s/%s", root.data(), dp->d_name,
+                                                       kThermalNameFile.data());
         std::string name;
         if (!android::base::ReadFileToString(path, &name)) {
             PLOG(ERROR) << "Failed to read from " << path;
             continue;
         }
 
-        path_map.emplace(
-                android::base::Trim(name),
-                android::base::StringPrintf("%s/%s", kThermalSensorsRoot.data(), dp->d_name));
+        path_map.emplace(android::base::Trim(name),
+                         android::base::StringPrintf("%s/%s", root.data(), dp->d_name));
     }
 
     return path_map;
 }
 
+std::map<std::string, SensorStatus> initializeSensorStatusMap(
+        const std::map<std::string, SensorInfo> &sensor_info_map) {
+    std::map<std::string, SensorStatus> sensor_status_map;
+    for (auto const &name_status_pair : sensor_info_map) {
+        sensor_status_map[name_status_pair.first] = {
+            .severity = ThrottlingSeverity::NONE,
+            .prev_hot_severity = ThrottlingSeverity::NONE,
+            .prev_cold_severity = ThrottlingSeverity::NONE,
+            .prev_hint_severity = ThrottlingSeverity::NONE,
+            .pid_engaged = false,
+            .i_term = 0.0,
+            .prev_err = 0.0,
+            .power_budget = std::numeric_limits<float>::max(),
+            .last_pid_update_time = boot_clock::time_point::min(),
+            .cdev_requests = {},
+            .prediction_samples = {},
+            .slope = 0.0,
+            .predicted_severity = ThrottlingSeverity::NONE,
+            .notified_severity = ThrottlingSeverity::NONE,
+            .last_notified_time = boot_clock::time_point(),
+            .suppressed_notifications = 0,
+            .time_in_severity = {},
+            .peak_temp = NAN,
+            .last_stats_update_time = boot_clock::now(),
+        };
+    }
+    return sensor_status_map;
+}
+
+// Fit a line to the latest samples and project the temperature horizon ahead,
+// the predicted severity only counts if the sensor is heating up.
+void updatePrediction(const SensorInfo &sensor_info, std::string_view profile,
+                      const SensorSample &sample, SensorStatus *sensor_status) {
+    const PredictionInfo &prediction_info = *sensor_info.prediction_info;
+    auto &samples = sensor_status->prediction_samples;
+    samples.push_back(sample);
+    while (samples.size() > prediction_info.samples) {
+        samples.pop_front();
+    }
+    sensor_status->slope = 0.0;
+    sensor_status->predicted_severity = ThrottlingSeverity::NONE;
+    if (samples.size() < prediction_info.samples) {
+        return;
+    }
+
+    float sum_x = 0.0, sum_y = 0.0, sum_xy = 0.0, sum_xx = 0.0;
+    for (const auto &s : samples) {
+        const float x = std::chrono::duration_cast<std::chrono::milliseconds>(
+                                s.timestamp - samples.front().timestamp)
+                                .count() /
+                        1000.0;
+        sum_x += x;
+        sum_y += s.value;
+        sum_xy += x * s.value;
+        sum_xx += x * x;
+    }
+    const float n = samples.size();
+    const float denominator = n * sum_xx - sum_x * sum_x;
+    if (denominator <= 0) {
+        return;
+    }
+    sensor_status->slope = (n * sum_xy - sum_x * sum_y) / denominator;
+    if (sensor_status->slope <= 0) {
+        return;
+    }
+
+    const float horizon_s = prediction_info.horizon.count() / 1000.0;
+    const float predicted_temp = sample.value + sensor_status->slope * horizon_s;
+    const ThresholdProfile thresholds = getThresholdProfile(sensor_info, profile);
+    sensor_status->predicted_severity =
+            getSeverityFromThresholds(thresholds.hot_thresholds, sensor_info.cold_thresholds,
+                                      thresholds.hot_hysteresis, sensor_info.cold_hysteresis,
+                                      ThrottlingSeverity::NONE, ThrottlingSeverity::NONE,
+                                      predicted_temp)
+                    .first;
+}
+
+// Keep the history of sensors which are still monitored
+std::map<std::string, SensorHistory> initializeSensorHistoryMap(
+        const std::map<std::string, SensorInfo> &sensor_info_map,
+        std::map<std::string, SensorHistory> *prev_history_map) {
+    std::map<std::string, SensorHistory> sensor_history_map;
+    for (auto const &name_info_pair : sensor_info_map) {
+        if (!name_info_pair.second.is_monitor) {
+            continue;
+        }
+        auto it = prev_history_map->find(name_info_pair.first);
+        if (it != prev_history_map->end()) {
+            sensor_history_map.emplace(name_info_pair.first, std::move(it->second));
+        } else {
+            sensor_history_map.emplace(name_info_pair.first, SensorHistory(kSensorHistorySize));
+        }
+    }
+    return sensor_history_map;
+}
+
+std::set<std::string> getMonitoredSensors(
+        const std::map<std::string, SensorInfo> &sensor_info_map) {
+    std::set<std::string> monitored_sensors;
+    for (auto const &name_info_pair : sensor_info_map) {
+        if (name_info_pair.second.is_monitor) {
+            monitored_sensors.insert(name_info_pair.first);
+        }
+    }
+    return monitored_sensors;
+}
+
+// Keep the stats of cooling devices which are still in the config
+std::map<std::string, CdevStats> initializeCdevStatsMap(
+        const std::map<std::string, CdevInfo> &cooling_device_info_map,
+        std::map<std::string, CdevStats> *prev_stats_map) {
+    std::map<std::string, CdevStats> cdev_stats_map;
+    const auto now = boot_clock::now();
+    for (auto const &name_info_pair : cooling_device_info_map) {
+        auto it = prev_stats_map->find(name_info_pair.first);
+        if (it != prev_stats_map->end()) {
+            cdev_stats_map.emplace(name_info_pair.first, std::move(it->second));
+            continue;
+        }
+        cdev_stats_map[name_info_pair.first] = {
+                .state = -1,
+                .time_in_state = {},
+                .transitions = 0,
+                .last_change_time = now,
+                .last_update_time = now,
+                .reset_time = now,
+        };
+    }
+    return cdev_stats_map;
+}
+
+std::map<std::string, SensorFaultStatus> initializeSensorFaultMap(
+        const std::map<std::string, SensorInfo> &sensor_info_map) {
+    std::map<std::string, SensorFaultStatus> sensor_fault_map;
+    const auto now = boot_clock::now();
+    for (auto const &name_info_pair : sensor_info_map) {
+        if (name_info_pair.second.fault_info == nullptr) {
+            continue;
+        }
+        sensor_fault_map[name_info_pair.first] = {
+                .fault = SensorFault::NONE,
+                .fault_count = 0,
+                .last_value = NAN,
+                .reported_value = NAN,
+                .last_read_time = now,
+                .last_change_time = now,
+        };
+    }
+    return sensor_fault_map;
+}
+
+std::map<std::string, SensorTransformStatus> initializeSensorTransformMap(
+        const std::map<std::string, SensorInfo> &sensor_info_map) {
+    std::map<std::string, SensorTransformStatus> sensor_transform_map;
+    const auto now = boot_clock::now();
+    for (auto const &name_info_pair : sensor_info_map) {
+        if (name_info_pair.second.transform_info == nullptr) {
+            continue;
+        }
+        sensor_transform_map[name_info_pair.first] = {
+                .raw_value = NAN,
+                .processed_value = NAN,
+                .last_update_time = now,
+        };
+    }
+    return sensor_transform_map;
+}
+
+// Linear interpolation between the two nearest points of the lookup table,
+// readings out of the table are clamped to its ends.
+float lookupTable(const TransformInfo &transform_info, float raw_value) {
+    const auto &lut_raw = transform_info.lut_raw;
+    const auto &lut_values = transform_info.lut_values;
+    if (raw_value <= lut_raw.front()) {
+        return lut_values.front();
+    }
+    if (raw_value >= lut_raw.back()) {
+        return lut_values.back();
+    }
+    const size_t i = std::upper_bound(lut_raw.begin(), lut_raw.end(), raw_value) - lut_raw.begin();
+    return lut_values[i - 1] + (lut_values[i] - lut_values[i - 1]) * (raw_value - lut_raw[i - 1]) /
+                                       (lut_raw[i] - lut_raw[i - 1]);
+}
+
+SensorFault checkSensorFault(const FaultInfo &fault_info, float value,
+                             boot_clock::time_point now, SensorFaultStatus *status) {
+    if (std::isnan(value)) {
+        return SensorFault::READ_ERROR;
+    }
+
+    SensorFault fault = SensorFault::NONE;
+    if (value < fault_info.min_value || value > fault_info.max_value) {
+        fault = SensorFault::OUT_OF_RANGE;
+    } else if (!std::isnan(status->last_value)) {
+        // Allow at least one second worth of change between close readings
+        const float elapsed_sec = std::max(
+                std::chrono::duration<float>(now - status->last_read_time).count(), 1.0f);
+        if (std::fabs(value - status->last_value) > fault_info.max_delta_per_sec * elapsed_sec) {
+            fault = SensorFault::RATE_OF_CHANGE;
+        } else if (value == status->last_value &&
+                   fault_info.stuck_timeout > std::chrono::milliseconds::zero() &&
+                   now - status->last_change_time > fault_info.stuck_timeout) {
+            fault = SensorFault::STUCK;
+        }
+    }
+
+    if (value != status->last_value) {
+        status->last_change_time = now;
+    }
+    status->last_value = value;
+    status->last_read_time = now;
+    return fault;
+}
+
+// Account the time since the last update to the current severity
+void updateSensorStats(boot_clock::time_point now, SensorStatus *sensor_status) {
+    sensor_status->time_in_severity[static_cast<size_t>(sensor_status->severity)] +=
+            std::chrono::duration_cast<std::chrono::milliseconds>(
+                    now - sensor_status->last_stats_update_time);
+    sensor_status->last_stats_update_time = now;
+}
+
+// Polling delay the sensor requests at its current severity. Sensors computed
+// from periodic readings keep being polled even when not throttling.
+std::chrono::milliseconds getPollingDelay(const SensorInfo &sensor_info,
+                                          const SensorStatus &sensor_status) {
+    auto polling_delay = sensor_info.polling_delays[static_cast<size_t>(sensor_status.severity)];
+    if (polling_delay == kNoPollingDelay &&
+        (sensor_info.virtual_sensor_info != nullptr || sensor_info.pid_info != nullptr ||
+         sensor_info.prediction_info != nullptr || sensor_info.transform_info != nullptr)) {
+        polling_delay = kDefaultPollingDelay;
+    }
+    return polling_delay;
+}
+
+// Lowest limit requested for each power_supply node, the sensors missing in
+// sensor_status_map are taken as ThrottlingSeverity::NONE.
+std::map<std::string, ChargerLimit> getChargerLimits(
+        const std::map<std::string, SensorInfo> &sensor_info_map,
+        const std::map<std::string, SensorStatus> &sensor_status_map) {
+    std::map<std::string, ChargerLimit> limits;
+    for (const auto &name_info_pair : sensor_info_map) {
+        const auto &charger_throttling_info = name_info_pair.second.charger_throttling_info;
+        if (charger_throttling_info == nullptr) {
+            continue;
+        }
+        auto it = sensor_status_map.find(name_info_pair.first);
+        const ThrottlingSeverity severity =
+                it == sensor_status_map.end() ? ThrottlingSeverity::NONE : it->second.severity;
+        const float current_limit =
+                charger_throttling_info->current_limits[static_cast<size_t>(severity)];
+        for (const auto &node : charger_throttling_info->nodes) {
+            auto limit_it = limits.find(node);
+            if (limit_it != limits.end() && limit_it->second.current_limit <= current_limit) {
+                continue;
+            }
+            limits[node] = {
+                    .current_limit = current_limit,
+                    .sensor = name_info_pair.first,
+                    .severity = severity,
+            };
+        }
+    }
+    return limits;
+}
+
+// Send the ThermalSensorStats atoms, the statistics are dropped if the Stats
+// service is not up.
+void reportThermalStats(const std::vector<VendorAtom> &atoms) {
+    sp<IStats> stats_client = IStats::tryGetService();
+    if (!stats_client) {
+        LOG(ERROR) << "Unable to connect to Stats service";
+        return;
+    }
+    for (const auto &atom : atoms) {
+        Return<void> ret = stats_client->reportVendorAtom(atom);
+        if (!ret.isOk()) {
+            LOG(ERROR) << "Unable to report ThermalSensorStats to Stats service";
+        }
+    }
+}
+
 }  // namespace
+
+std::string_view toString(SensorFault fault) {
+    switch (fault) {
+        case SensorFault::NONE:
+            return "NONE";
+        case SensorFault::READ_ERROR:
+            return "READ_ERROR";
+        case SensorFault::OUT_OF_RANGE:
+            return "OUT_OF_RANGE";
+        case SensorFault::RATE_OF_CHANGE:
+            return "RATE_OF_CHANGE";
+        case SensorFault::STUCK:
+            return "STUCK";
+    }
+    return "UNKNOWN";
+}
+
 PowerHalService::PowerHalService()
     : power_hal_aidl_exist_(true), power_hal_aidl_(nullptr), power_hal_ext_aidl_(nullptr) {
     connect();
@@ -260,43 +580,41 @@ void PowerHalService::setMode(const std::string &type, const ThrottlingSeverity
  * reading the type file and assigning the temp file path to the map.  If we do
  * not succeed, abort.
  */
-ThermalHelper::ThermalHelper(const NotificationCallback &cb)
-    : thermal_watcher_(new ThermalWatcher(
+ThermalHelper::ThermalHelper()
+    : ThermalHelper(kThermalSensorsRoot,
+                    "/vendor/etc/" + android::base::GetProperty(kConfigProperty.data(),
+                                                                kConfigDefaultFileName.data())) {}
+
+ThermalHelper::ThermalHelper(std::string_view thermal_root, std::string_view config_path)
+    : thermal_root_(thermal_root),
+      config_path_(config_path),
+      thermal_watcher_(new ThermalWatcher(
               std::bind(&ThermalHelper::thermalWatcherCallbackFunc, this, std::placeholders::_1))),
-      cb_(cb),
-      cooling_device_info_map_(ParseCoolingDevice(
-              "/vendor/etc/" +
-              android::base::GetProperty(kConfigProperty.data(), kConfigDefaultFileName.data()))),
-      sensor_info_map_(ParseSensorInfo(
-              "/vendor/etc/" +
-              android::base::GetProperty(kConfigProperty.data(), kConfigDefaultFileName.data()))) {
-    for (auto const &name_status_pair : sensor_info_map_) {
-        sensor_status_map_[name_status_pair.first] = {
-            .severity = ThrottlingSeverity::NONE,
-            .prev_hot_severity = ThrottlingSeverity::NONE,
-            .prev_cold_severity = ThrottlingSeverity::NONE,
-            .prev_hint_severity = ThrottlingSeverity::NONE,
-        };
-    }
-
-    auto tz_map = parseThermalPathMap(kSensorPrefix.data());
-    auto cdev_map = parseThermalPathMap(kCoolingDevicePrefix.data());
+      last_stats_report_time_(boot_clock::now()) {
+    auto tz_map = parseThermalPathMap(thermal_root_, kSensorPrefix);
+    auto cdev_map = parseThermalPathMap(thermal_root_, kCoolingDevicePrefix);
 
-    is_initialized_ = initializeSensorMap(tz_map) && initializeCoolingDevices(cdev_map);
+    config_ = loadConfig(tz_map, cdev_map);
+    is_initialized_ = config_ != nullptr;
     if (!is_initialized_) {
         LOG(FATAL) << "ThermalHAL could not be initialized properly.";
     }
-    std::set<std::string> monitored_sensors;
-    std::transform(sensor_info_map_.cbegin(), sensor_info_map_.cend(),
-                   std::inserter(monitored_sensors, monitored_sensors.begin()),
-                   [](std::pair<std::string, SensorInfo> const &sensor) {
-                       if (sensor.second.is_monitor)
-                           return sensor.first;
-                       else
-                           return std::string();
-                   });
+    sensor_status_map_ = initializeSensorStatusMap(config_->sensor_info_map);
+    sensor_history_map_ =
+            initializeSensorHistoryMap(config_->sensor_info_map, &sensor_history_map_);
+    sensor_fault_map_ = initializeSensorFaultMap(config_->sensor_info_map);
+    sensor_transform_map_ = initializeSensorTransformMap(config_->sensor_info_map);
+    cdev_stats_map_ =
+            initializeCdevStatsMap(config_->cooling_device_info_map, &cdev_stats_map_);
+    const std::string profile = android::base::GetProperty(kProfileProperty.data(), "");
+    if (isProfileDefined(*config_, profile)) {
+        active_profile_ = profile;
+    } else {
+        LOG(ERROR) << "Unknown thermal profile " << profile << ", use the default thresholds";
+    }
 
-    thermal_watcher_->registerFilesToWatch(monitored_sensors, initializeTrip(tz_map));
+    thermal_watcher_->registerFilesToWatch(getMonitoredSensors(config_->sensor_info_map),
+                                           initializeTrip(tz_map, *config_, active_profile_));
 
     // Need start watching after status map initialized
     is_initialized_ = thermal_watcher_->startWatchingDeviceFiles();
@@ -307,21 +625,234 @@ ThermalHelper::ThermalHelper(const NotificationCallback &cb)
     if (!connectToPowerHal()) {
         LOG(ERROR) << "Fail to connect to Power Hal";
     } else {
-        updateSupportedPowerHints();
+        supported_powerhint_map_ = getSupportedPowerHints(*config_);
     }
+
+    profile_watcher_thread_ = std::thread(&ThermalHelper::profileWatcherLoop, this);
+}
+
+ThermalHelper::~ThermalHelper() {
+    // Both threads call back into this object
+    profile_watcher_exit_ = true;
+    profile_watcher_thread_.join();
+    thermal_watcher_->stopWatchingDeviceFiles();
+}
+
+void ThermalHelper::addNotificationCallback(const NotificationCallback &cb) {
+    std::lock_guard<std::mutex> _lock(notification_callback_mutex_);
+    notification_callbacks_.push_back(cb);
+}
+
+std::unique_ptr<ThermalConfig> ThermalHelper::loadConfig(
+        const std::map<std::string, std::string> &tz_map,
+        const std::map<std::string, std::string> &cdev_map) const {
+    std::unique_ptr<ThermalConfig> config(new ThermalConfig{
+            .cooling_device_info_map = {},
+            .sensor_info_map = {},
+            .notification_info = {},
+            .thermal_sensors = {},
+            .cooling_devices = {},
+    });
+
+    if (!ParseThermalConfig(config_path_, &config->sensor_info_map,
+                            &config->cooling_device_info_map, &config->notification_info)) {
+        LOG(ERROR) << "Failed to parse thermal config " << config_path_;
+        return nullptr;
+    }
+    if (!initializeSensorMap(tz_map, config.get()) ||
+        !initializeCoolingDevices(cdev_map, config.get())) {
+        LOG(ERROR) << "Failed to resolve thermal config " << config_path_;
+        return nullptr;
+    }
+    return config;
+}
+
+bool ThermalHelper::reloadConfig() {
+    auto tz_map = parseThermalPathMap(thermal_root_, kSensorPrefix);
+    auto cdev_map = parseThermalPathMap(thermal_root_, kCoolingDevicePrefix);
+
+    std::shared_ptr<const ThermalConfig> new_config = loadConfig(tz_map, cdev_map);
+    if (new_config == nullptr || new_config->sensor_info_map.empty()) {
+        LOG(ERROR) << "Invalid thermal config, keep the current one";
+        return false;
+    }
+
+    // Hold off the watcher thread until the new config is in place
+    std::lock_guard<std::mutex> _callback_lock(thermal_watcher_callback_mutex_);
+    const auto old_config = GetConfig();
+    auto new_status_map = initializeSensorStatusMap(new_config->sensor_info_map);
+    auto new_powerhint_map = getSupportedPowerHints(*new_config);
+    const bool uevent_monitor = initializeTrip(tz_map, *new_config, getActiveProfile());
+
+    // Release the mitigations requested under the old config
+    for (const auto &cdev_state : cdev_applied_state_map_) {
+        if (cdev_state.second != 0 &&
+            !old_config->cooling_devices.writeThermalFile(cdev_state.first, "0")) {
+            LOG(ERROR) << "Failed to release cooling device " << cdev_state.first;
+        }
+    }
+    cdev_applied_state_map_.clear();
+    // Lift the charge current limits to their NONE severity under the old config
+    writeChargerLimits(getChargerLimits(old_config->sensor_info_map, {}));
+    {
+        std::lock_guard<std::mutex> _lock(charger_limit_mutex_);
+        charger_limit_map_.clear();
+    }
+    pending_notifications_.clear();
+    {
+        std::unique_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+        for (const auto &name_status_pair : sensor_status_map_) {
+            if (name_status_pair.second.prev_hint_severity != ThrottlingSeverity::NONE) {
+                power_hal_service_.setMode(name_status_pair.first,
+                                           name_status_pair.second.prev_hint_severity, false);
+            }
+        }
+        config_ = new_config;
+        sensor_status_map_ = std::move(new_status_map);
+        supported_powerhint_map_ = std::move(new_powerhint_map);
+        // Replaced along with the config for GetThermalSnapshot
+        std::lock_guard<std::mutex> _fault_lock(sensor_fault_mutex_);
+        sensor_fault_map_ = initializeSensorFaultMap(new_config->sensor_info_map);
+    }
+    {
+        std::lock_guard<std::mutex> _lock(sensor_history_mutex_);
+        sensor_history_map_ =
+                initializeSensorHistoryMap(new_config->sensor_info_map, &sensor_history_map_);
+    }
+    {
+        std::lock_guard<std::mutex> _lock(sensor_transform_mutex_);
+        sensor_transform_map_ = initializeSensorTransformMap(new_config->sensor_info_map);
+    }
+    {
+        std::lock_guard<std::mutex> _lock(cdev_stats_mutex_);
+        cdev_stats_map_ =
+                initializeCdevStatsMap(new_config->cooling_device_info_map, &cdev_stats_map_);
+    }
+
+    thermal_watcher_->registerFilesToWatch(getMonitoredSensors(new_config->sensor_info_map),
+                                           uevent_monitor);
+    thermal_watcher_->wake();
+    LOG(INFO) << "Thermal config reloaded, " << new_config->sensor_info_map.size()
+              << " sensors and " << new_config->cooling_device_info_map.size()
+              << " cooling devices";
+    return true;
+}
+
+bool ThermalHelper::setProfile(std::string_view profile) {
+    // Hold off the watcher thread until the trip points are updated
+    std::lock_guard<std::mutex> _callback_lock(thermal_watcher_callback_mutex_);
+    const auto config = GetConfig();
+    if (!isProfileDefined(*config, profile)) {
+        LOG(ERROR) << "Unknown thermal profile " << profile << ", keep " << getActiveProfile();
+        return false;
+    }
+    {
+        std::unique_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+        if (active_profile_ == profile) {
+            return true;
+        }
+        active_profile_ = profile;
+    }
+
+    const bool uevent_monitor = initializeTrip(parseThermalPathMap(thermal_root_, kSensorPrefix),
+                                               *config, profile);
+    thermal_watcher_->registerFilesToWatch(getMonitoredSensors(config->sensor_info_map),
+                                           uevent_monitor);
+    // Re-evaluate all sensors under the new thresholds
+    thermal_watcher_->wake();
+    LOG(INFO) << "Thermal profile switched to "
+              << (profile.empty() ? std::string_view("default") : profile);
+    return true;
+}
+
+std::string ThermalHelper::getActiveProfile() const {
+    std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+    return active_profile_;
+}
+
+bool ThermalHelper::isProfileDefined(const ThermalConfig &config, std::string_view profile) const {
+    if (profile.empty()) {
+        return true;
+    }
+    for (const auto &name_info_pair : config.sensor_info_map) {
+        if (name_info_pair.second.profiles.count(profile.data())) {
+            return true;
+        }
+    }
+    return false;
+}
+
+void ThermalHelper::profileWatcherLoop() {
+    uint32_t serial = 0;
+    std::string profile = getActiveProfile();
+    while (!profile_watcher_exit_) {
+        // Wait on the global serial until the property is created
+        const prop_info *pi = __system_property_find(kProfileProperty.data());
+        if (!__system_property_wait(pi, serial, &serial, &kProfileWaitTimeout)) {
+            continue;
+        }
+        const std::string new_profile = android::base::GetProperty(kProfileProperty.data(), "");
+        if (new_profile != profile) {
+            setProfile(new_profile);
+            profile = new_profile;
+        }
+    }
+}
+
+std::map<std::string, SensorHistory> ThermalHelper::GetSensorHistoryMap() const {
+    std::lock_guard<std::mutex> _lock(sensor_history_mutex_);
+    return sensor_history_map_;
+}
+
+std::map<std::string, CdevStats> ThermalHelper::GetCdevStatsMap() const {
+    std::lock_guard<std::mutex> _lock(cdev_stats_mutex_);
+    return cdev_stats_map_;
+}
+
+void ThermalHelper::resetCdevStats() {
+    std::lock_guard<std::mutex> _lock(cdev_stats_mutex_);
+    const auto now = boot_clock::now();
+    for (auto &name_stats_pair : cdev_stats_map_) {
+        CdevStats &cdev_stats = name_stats_pair.second;
+        cdev_stats.time_in_state.clear();
+        cdev_stats.transitions = 0;
+        cdev_stats.last_update_time = now;
+        cdev_stats.reset_time = now;
+    }
+}
+
+std::map<std::string, SensorFaultStatus> ThermalHelper::GetSensorFaultMap() const {
+    std::lock_guard<std::mutex> _lock(sensor_fault_mutex_);
+    return sensor_fault_map_;
+}
+
+std::map<std::string, SensorTransformStatus> ThermalHelper::GetSensorTransformMap() const {
+    std::lock_guard<std::mutex> _lock(sensor_transform_mutex_);
+    return sensor_transform_map_;
+}
+
+std::map<std::string, ChargerLimit> ThermalHelper::GetChargerLimitMap() const {
+    std::lock_guard<std::mutex> _lock(charger_limit_mutex_);
+    return charger_limit_map_;
+}
+
+std::shared_ptr<const ThermalConfig> ThermalHelper::GetConfig() const {
+    std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+    return config_;
 }
 
 bool ThermalHelper::readCoolingDevice(std::string_view cooling_device,
                                       CoolingDevice_2_0 *out) const {
+    const auto config = GetConfig();
     // Read the file.  If the file can't be read temp will be empty string.
     std::string data;
 
-    if (!cooling_devices_.readThermalFile(cooling_device, &data)) {
+    if (!config->cooling_devices.readThermalFile(cooling_device, &data)) {
         LOG(ERROR) << "readCoolingDevice: failed to read cooling_device: " << cooling_device;
         return false;
     }
 
-    const CoolingType &type = cooling_device_info_map_.at(cooling_device.data());
+    const CoolingType &type = config->cooling_device_info_map.at(cooling_device.data()).type;
 
     out->type = type;
     out->name = cooling_device.data();
@@ -330,32 +861,180 @@ bool ThermalHelper::readCoolingDevice(std::string_view cooling_device,
     return true;
 }
 
-bool ThermalHelper::readTemperature(std::string_view sensor_name, Temperature_1_0 *out) const {
-    // Read the file.  If the file can't be read temp will be empty string.
-    std::string temp;
-
-    if (!thermal_sensors_.readThermalFile(sensor_name, &temp)) {
+bool ThermalHelper::readThermalSensor(const ThermalConfig &config, std::string_view sensor_name,
+                                      float *temp) const {
+    auto it = config.sensor_info_map.find(sensor_name.data());
+    if (it == config.sensor_info_map.end()) {
         LOG(ERROR) << "readTemperature: sensor not found: " << sensor_name;
         return false;
     }
+    const auto &sensor_info = it->second;
 
-    if (temp.empty()) {
-        LOG(ERROR) << "readTemperature: failed to read sensor: " << sensor_name;
+    // Sensors being read by this thread, the config parser rejects cycles through
+    // substitutes and linked sensors, this only guards the stack against a miss.
+    thread_local std::set<std::string> reading_sensors;
+    if (!reading_sensors.insert(it->first).second) {
+        LOG(ERROR) << "readTemperature: sensor reads itself: " << sensor_name;
         return false;
     }
+    struct ReadingGuard {
+        const std::string &name;
+        ~ReadingGuard() { reading_sensors.erase(name); }
+    } reading_guard{it->first};
+
+    if (sensor_info.fault_info == nullptr) {
+        return readSensorValue(config, sensor_name, sensor_info, temp);
+    }
 
-    const SensorInfo &sensor_info = sensor_info_map_.at(sensor_name.data());
+    const auto &fault_info = *sensor_info.fault_info;
+    float value = NAN;
+    if (!readSensorValue(config, sensor_name, sensor_info, &value)) {
+        value = NAN;
+    }
+    const SensorFault fault = updateSensorFault(sensor_name, fault_info, value);
+    if (fault == SensorFault::NONE) {
+        *temp = value;
+        return true;
+    }
+
+    // A suspicious but plausible reading, or one above the valid range, is only
+    // ever raised, so a faulty sensor can not mask a real overheat.
+    const bool keep_value = fault == SensorFault::RATE_OF_CHANGE || fault == SensorFault::STUCK ||
+                            (fault == SensorFault::OUT_OF_RANGE && value > fault_info.max_value);
+    float result = keep_value ? value : NAN;
+    float substitute_temp;
+    if (!fault_info.substitute.empty() &&
+        readThermalSensor(config, fault_info.substitute, &substitute_temp)) {
+        result = std::fmax(result, substitute_temp);
+    }
+    result = std::fmax(result, fault_info.fallback_value);
+    if (std::isnan(result)) {
+        LOG(ERROR) << "readTemperature: no fallback for faulty sensor: " << sensor_name;
+        return false;
+    }
+
+    std::lock_guard<std::mutex> _lock(sensor_fault_mutex_);
+    auto fault_it = sensor_fault_map_.find(sensor_name.data());
+    if (fault_it != sensor_fault_map_.end()) {
+        fault_it->second.reported_value = result;
+    }
+    *temp = result;
+    return true;
+}
+
+SensorFault ThermalHelper::updateSensorFault(std::string_view sensor_name,
+                                             const FaultInfo &fault_info, float value) const {
+    std::lock_guard<std::mutex> _lock(sensor_fault_mutex_);
+    auto it = sensor_fault_map_.find(sensor_name.data());
+    // The sensor is gone with a config reload in between
+    if (it == sensor_fault_map_.end()) {
+        return SensorFault::NONE;
+    }
+    SensorFaultStatus &status = it->second;
+    const SensorFault fault = checkSensorFault(fault_info, value, boot_clock::now(), &status);
+    if (fault != status.fault) {
+        if (fault != SensorFault::NONE) {
+            LOG(ERROR) << "Sensor " << sensor_name << " turns faulty: " << toString(fault)
+                       << ", value: " << value;
+            status.fault_count++;
+        } else {
+            LOG(INFO) << "Sensor " << sensor_name << " recovers from " << toString(status.fault)
+                      << ", value: " << value;
+        }
+        status.fault = fault;
+    }
+    status.reported_value = value;
+    return fault;
+}
+
+float ThermalHelper::applyTransform(std::string_view sensor_name, const SensorInfo &sensor_info,
+                                    float raw_value) const {
+    const auto &transform_info = *sensor_info.transform_info;
+    float value = transform_info.lut_raw.empty() ? raw_value
+                                                 : lookupTable(transform_info, raw_value);
+    value = value * sensor_info.multiplier + transform_info.offset;
+
+    std::lock_guard<std::mutex> _lock(sensor_transform_mutex_);
+    auto it = sensor_transform_map_.find(sensor_name.data());
+    // The sensor is gone with a config reload in between
+    if (it == sensor_transform_map_.end()) {
+        return value;
+    }
+    SensorTransformStatus &status = it->second;
+    const auto now = boot_clock::now();
+    // The weight of the new reading grows with the time since the last one, so
+    // the smoothing does not depend on how often the sensor is read.
+    if (transform_info.ema_time_constant.count() > 0 && !std::isnan(status.processed_value)) {
+        const float dt = std::chrono::duration_cast<std::chrono::milliseconds>(
+                                 now - status.last_update_time)
+                                 .count();
+        const float alpha = 1.0 - std::exp(-dt / transform_info.ema_time_constant.count());
+        value = status.processed_value + alpha * (value - status.processed_value);
+    }
+    status.raw_value = raw_value;
+    status.processed_value = value;
+    status.last_update_time = now;
+    return value;
+}
+
+bool ThermalHelper::readSensorValue(const ThermalConfig &config, std::string_view sensor_name,
+                                    const SensorInfo &sensor_info, float *temp) const {
+    if (sensor_info.virtual_sensor_info == nullptr) {
+        // Read the file.  If the file can't be read temp will be empty string.
+        std::string data;
+
+        if (!config.thermal_sensors.readThermalFile(sensor_name, &data)) {
+            LOG(ERROR) << "readTemperature: sensor not found: " << sensor_name;
+            return false;
+        }
+
+        if (data.empty()) {
+            LOG(ERROR) << "readTemperature: failed to read sensor: " << sensor_name;
+            return false;
+        }
+
+        const float raw_value = std::stof(data);
+        *temp = sensor_info.transform_info == nullptr
+                        ? raw_value * sensor_info.multiplier
+                        : applyTransform(sensor_name, sensor_info, raw_value);
+        return true;
+    }
+
+    const auto &virtual_sensor_info = *sensor_info.virtual_sensor_info;
+    std::vector<float> linked_temps(virtual_sensor_info.linked_sensors.size());
+    for (size_t i = 0; i < virtual_sensor_info.linked_sensors.size(); i++) {
+        if (!readThermalSensor(config, virtual_sensor_info.linked_sensors[i], &linked_temps[i])) {
+            LOG(ERROR) << "readTemperature: failed to read linked sensor "
+                       << virtual_sensor_info.linked_sensors[i] << " of " << sensor_name;
+            return false;
+        }
+    }
+
+    *temp = getVirtualSensorValue(virtual_sensor_info, linked_temps);
+    return true;
+}
+
+bool ThermalHelper::readTemperature(std::string_view sensor_name, Temperature_1_0 *out) const {
+    const auto config = GetConfig();
+    float temp;
+
+    if (!readThermalSensor(*config, sensor_name, &temp)) {
+        return false;
+    }
+
+    const SensorInfo &sensor_info = config->sensor_info_map.at(sensor_name.data());
     TemperatureType_1_0 type =
         (static_cast<int>(sensor_info.type) > static_cast<int>(TemperatureType_1_0::SKIN))
             ? TemperatureType_1_0::UNKNOWN
             : static_cast<TemperatureType_1_0>(sensor_info.type);
     out->type = type;
     out->name = sensor_name.data();
-    out->currentValue = std::stof(temp) * sensor_info.multiplier;
+    out->currentValue = temp;
+    const ThresholdProfile thresholds = getThresholdProfile(sensor_info, getActiveProfile());
     out->throttlingThreshold =
-        sensor_info.hot_thresholds[static_cast<size_t>(ThrottlingSeverity::SEVERE)];
+        thresholds.hot_thresholds[static_cast<size_t>(ThrottlingSeverity::SEVERE)];
     out->shutdownThreshold =
-        sensor_info.hot_thresholds[static_cast<size_t>(ThrottlingSeverity::SHUTDOWN)];
+        thresholds.hot_thresholds[static_cast<size_t>(ThrottlingSeverity::SHUTDOWN)];
     out->vrThrottlingThreshold = sensor_info.vr_threshold;
 
     return true;
@@ -364,133 +1043,101 @@ bool ThermalHelper::readTemperature(std::string_view sensor_name, Temperature_1_
 bool ThermalHelper::readTemperature(
         std::string_view sensor_name, Temperature_2_0 *out,
         std::pair<ThrottlingSeverity, ThrottlingSeverity> *throtting_status) const {
-    // Read the file.  If the file can't be read temp will be empty string.
-    std::string temp;
-
-    if (!thermal_sensors_.readThermalFile(sensor_name, &temp)) {
-        LOG(ERROR) << "readTemperature: sensor not found: " << sensor_name;
-        return false;
-    }
+    const auto config = GetConfig();
+    float temp;
 
-    if (temp.empty()) {
-        LOG(ERROR) << "readTemperature: failed to read sensor: " << sensor_name;
+    if (!readThermalSensor(*config, sensor_name, &temp)) {
         return false;
     }
 
-    const auto &sensor_info = sensor_info_map_.at(sensor_name.data());
+    const auto &sensor_info = config->sensor_info_map.at(sensor_name.data());
     out->type = sensor_info.type;
     out->name = sensor_name.data();
-    out->value = std::stof(temp) * sensor_info.multiplier;
+    out->value = temp;
 
     std::pair<ThrottlingSeverity, ThrottlingSeverity> status =
         std::make_pair(ThrottlingSeverity::NONE, ThrottlingSeverity::NONE);
+    ThrottlingSeverity predicted_severity = ThrottlingSeverity::NONE;
     // Only update status if the thermal sensor is being monitored
     if (sensor_info.is_monitor) {
         ThrottlingSeverity prev_hot_severity, prev_cold_severity;
+        std::string profile;
         {
             // reader lock, readTemperature will be called in Binder call and the watcher thread.
             std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
-            prev_hot_severity = sensor_status_map_.at(sensor_name.data()).prev_hot_severity;
-            prev_cold_severity = sensor_status_map_.at(sensor_name.data()).prev_cold_severity;
+            auto it = sensor_status_map_.find(sensor_name.data());
+            if (it == sensor_status_map_.end()) {
+                LOG(ERROR) << "readTemperature: sensor status not found: " << sensor_name;
+                return false;
+            }
+            prev_hot_severity = it->second.prev_hot_severity;
+            prev_cold_severity = it->second.prev_cold_severity;
+            predicted_severity = it->second.predicted_severity;
+            profile = active_profile_;
         }
-        status = getSeverityFromThresholds(sensor_info.hot_thresholds, sensor_info.cold_thresholds,
-                                           sensor_info.hot_hysteresis, sensor_info.cold_hysteresis,
+        const ThresholdProfile thresholds = getThresholdProfile(sensor_info, profile);
+        status = getSeverityFromThresholds(thresholds.hot_thresholds, sensor_info.cold_thresholds,
+                                           thresholds.hot_hysteresis, sensor_info.cold_hysteresis,
                                            prev_hot_severity, prev_cold_severity, out->value);
     }
     if (throtting_status) {
         *throtting_status = status;
     }
 
-    out->throttlingStatus = static_cast<size_t>(status.first) > static_cast<size_t>(status.second)
-                                ? status.first
-                                : status.second;
+    out->throttlingStatus = std::max({status.first, status.second, predicted_severity});
 
     return true;
 }
 
 bool ThermalHelper::readTemperatureThreshold(std::string_view sensor_name,
                                              TemperatureThreshold *out) const {
-    // Read the file.  If the file can't be read temp will be empty string.
-    std::string temp;
-    std::string path;
+    const auto config = GetConfig();
 
-    if (!sensor_info_map_.count(sensor_name.data())) {
+    if (!config->sensor_info_map.count(sensor_name.data())) {
         LOG(ERROR) << __func__ << ": sensor not found: " << sensor_name;
         return false;
     }
 
-    const auto &sensor_info = sensor_info_map_.at(sensor_name.data());
+    const auto &sensor_info = config->sensor_info_map.at(sensor_name.data());
 
     out->type = sensor_info.type;
     out->name = sensor_name.data();
-    out->hotThrottlingThresholds = sensor_info.hot_thresholds;
+    out->hotThrottlingThresholds =
+            getThresholdProfile(sensor_info, getActiveProfile()).hot_thresholds;
     out->coldThrottlingThresholds = sensor_info.cold_thresholds;
     out->vrThrottlingThreshold = sensor_info.vr_threshold;
     return true;
 }
 
-std::pair<ThrottlingSeverity, ThrottlingSeverity> ThermalHelper::getSeverityFromThresholds(
-    const ThrottlingArray &hot_thresholds, const ThrottlingArray &cold_thresholds,
-    const ThrottlingArray &hot_hysteresis, const ThrottlingArray &cold_hysteresis,
-    ThrottlingSeverity prev_hot_severity, ThrottlingSeverity prev_cold_severity,
-    float value) const {
-    ThrottlingSeverity ret_hot = ThrottlingSeverity::NONE;
-    ThrottlingSeverity ret_hot_hysteresis = ThrottlingSeverity::NONE;
-    ThrottlingSeverity ret_cold = ThrottlingSeverity::NONE;
-    ThrottlingSeverity ret_cold_hysteresis = ThrottlingSeverity::NONE;
-
-    // Here we want to control the iteration from high to low, and hidl_enum_range doesn't support
-    // a reverse iterator yet.
-    for (size_t i = static_cast<size_t>(ThrottlingSeverity::SHUTDOWN);
-         i > static_cast<size_t>(ThrottlingSeverity::NONE); --i) {
-        if (!std::isnan(hot_thresholds[i]) && hot_thresholds[i] <= value &&
-            ret_hot == ThrottlingSeverity::NONE) {
-            ret_hot = static_cast<ThrottlingSeverity>(i);
-        }
-        if (!std::isnan(hot_thresholds[i]) && (hot_thresholds[i] - hot_hysteresis[i]) < value &&
-            ret_hot_hysteresis == ThrottlingSeverity::NONE) {
-            ret_hot_hysteresis = static_cast<ThrottlingSeverity>(i);
-        }
-        if (!std::isnan(cold_thresholds[i]) && cold_thresholds[i] >= value &&
-            ret_cold == ThrottlingSeverity::NONE) {
-            ret_cold = static_cast<ThrottlingSeverity>(i);
-        }
-        if (!std::isnan(cold_thresholds[i]) && (cold_thresholds[i] + cold_hysteresis[i]) > value &&
-            ret_cold_hysteresis == ThrottlingSeverity::NONE) {
-            ret_cold_hysteresis = static_cast<ThrottlingSeverity>(i);
-        }
-    }
-    if (static_cast<size_t>(ret_hot) < static_cast<size_t>(prev_hot_severity)) {
-        ret_hot = ret_hot_hysteresis;
-    }
-    if (static_cast<size_t>(ret_cold) < static_cast<size_t>(prev_cold_severity)) {
-        ret_cold = ret_cold_hysteresis;
-    }
-
-    return std::make_pair(ret_hot, ret_cold);
-}
-
-bool ThermalHelper::initializeSensorMap(const std::map<std::string, std::string> &path_map) {
-    for (const auto &sensor_info_pair : sensor_info_map_) {
+bool ThermalHelper::initializeSensorMap(const std::map<std::string, std::string> &path_map,
+                                        ThermalConfig *config) const {
+    size_t num_physical_sensors = 0;
+    for (const auto &sensor_info_pair : config->sensor_info_map) {
         std::string_view sensor_name = sensor_info_pair.first;
+        // Virtual sensors are computed from their linked sensors
+        if (sensor_info_pair.second.virtual_sensor_info != nullptr) {
+            continue;
+        }
+        ++num_physical_sensors;
         if (!path_map.count(sensor_name.data())) {
             LOG(ERROR) << "Could not find " << sensor_name << " in sysfs";
             continue;
         }
         std::string path = android::base::StringPrintf(
                 "%s/%s", path_map.at(sensor_name.data()).c_str(), kSensorTempSuffix.data());
-        if (!thermal_sensors_.addThermalFile(sensor_name, path)) {
+        if (!config->thermal_sensors.addThermalFile(sensor_name, path)) {
             LOG(ERROR) << "Could not add " << sensor_name << "to sensors map";
         }
     }
-    if (sensor_info_map_.size() == thermal_sensors_.getNumThermalFiles()) {
+    if (num_physical_sensors == config->thermal_sensors.getNumThermalFiles()) {
         return true;
     }
     return false;
 }
 
-bool ThermalHelper::initializeCoolingDevices(const std::map<std::string, std::string> &path_map) {
-    for (const auto &cooling_device_info_pair : cooling_device_info_map_) {
+bool ThermalHelper::initializeCoolingDevices(const std::map<std::string, std::string> &path_map,
+                                             ThermalConfig *config) const {
+    for (const auto &cooling_device_info_pair : config->cooling_device_info_map) {
         std::string_view cooling_device_name = cooling_device_info_pair.first;
         if (!path_map.count(cooling_device_name.data())) {
             LOG(ERROR) << "Could not find " << cooling_device_name << " in sysfs";
@@ -499,22 +1146,40 @@ bool ThermalHelper::initializeCoolingDevices(const std::map<std::string, std::st
         std::string path = android::base::StringPrintf(
                 "%s/%s", path_map.at(cooling_device_name.data()).c_str(),
                 kCoolingDeviceCurStateSuffix.data());
-        if (!cooling_devices_.addThermalFile(cooling_device_name, path)) {
+        if (!config->cooling_devices.addThermalFile(cooling_device_name, path)) {
             LOG(ERROR) << "Could not add " << cooling_device_name << "to cooling device map";
             continue;
         }
     }
 
-    if (cooling_device_info_map_.size() == cooling_devices_.getNumThermalFiles()) {
-        return true;
+    if (config->cooling_device_info_map.size() != config->cooling_devices.getNumThermalFiles()) {
+        return false;
     }
-    return false;
+    return true;
 }
 
-bool ThermalHelper::initializeTrip(const std::map<std::string, std::string> &path_map) {
-    for (const auto &sensor_info : sensor_info_map_) {
+bool ThermalHelper::initializeTrip(const std::map<std::string, std::string> &path_map,
+                                   const ThermalConfig &config, std::string_view profile) const {
+    for (const auto &sensor_info : config.sensor_info_map) {
         if (sensor_info.second.is_monitor) {
             std::string_view sensor_name = sensor_info.first;
+            const ThresholdProfile thresholds = getThresholdProfile(sensor_info.second, profile);
+            // Virtual sensors never trigger uevent, fall back to polling
+            if (sensor_info.second.virtual_sensor_info != nullptr) {
+                LOG(INFO) << sensor_name << " is a monitored virtual sensor, uevent is disabled";
+                return false;
+            }
+            // PID controller and prediction need periodic samples below the first trip point
+            if (sensor_info.second.pid_info != nullptr ||
+                sensor_info.second.prediction_info != nullptr) {
+                LOG(INFO) << sensor_name << " needs periodic samples, uevent is disabled";
+                return false;
+            }
+            // The kernel compares trip points against the raw reading, not the transformed one
+            if (sensor_info.second.transform_info != nullptr) {
+                LOG(INFO) << sensor_name << " has a Transform, uevent is disabled";
+                return false;
+            }
             std::string_view tz_path = path_map.at(sensor_name.data());
             std::string tz_policy;
             std::string path = android::base::StringPrintf("%s/%s", (tz_path.data()),
@@ -532,11 +1197,11 @@ bool ThermalHelper::initializeTrip(const std::map<std::string, std::string> &pat
 
             // Update thermal zone trip point
             for (size_t i = 0; i < kThrottlingSeverityCount; ++i) {
-                if (!std::isnan(sensor_info.second.hot_thresholds[i]) &&
-                    !std::isnan(sensor_info.second.hot_hysteresis[i])) {
+                if (!std::isnan(thresholds.hot_thresholds[i]) &&
+                    !std::isnan(thresholds.hot_hysteresis[i])) {
                     // Update trip_point_0_temp threshold
                     std::string threshold = std::to_string(static_cast<int>(
-                            sensor_info.second.hot_thresholds[i] / sensor_info.second.multiplier));
+                            thresholds.hot_thresholds[i] / sensor_info.second.multiplier));
                     path = android::base::StringPrintf("%s/%s", (tz_path.data()),
                                                        kSensorTripPointTempZeroFile.data());
                     if (!android::base::WriteStringToFile(threshold, path)) {
@@ -546,7 +1211,7 @@ bool ThermalHelper::initializeTrip(const std::map<std::string, std::string> &pat
                     }
                     // Update trip_point_0_hyst threshold
                     threshold = std::to_string(static_cast<int>(
-                            sensor_info.second.hot_hysteresis[i] / sensor_info.second.multiplier));
+                            thresholds.hot_hysteresis[i] / sensor_info.second.multiplier));
                     path = android::base::StringPrintf("%s/%s", (tz_path.data()),
                                                        kSensorTripPointHystZeroFile.data());
                     if (!android::base::WriteStringToFile(threshold, path)) {
@@ -565,9 +1230,10 @@ bool ThermalHelper::initializeTrip(const std::map<std::string, std::string> &pat
     return true;
 }
 bool ThermalHelper::fillTemperatures(hidl_vec<Temperature_1_0> *temperatures) const {
-    temperatures->resize(sensor_info_map_.size());
+    const auto config = GetConfig();
+    temperatures->resize(config->sensor_info_map.size());
     int current_index = 0;
-    for (const auto &name_info_pair : sensor_info_map_) {
+    for (const auto &name_info_pair : config->sensor_info_map) {
         Temperature_1_0 temp;
 
         if (readTemperature(name_info_pair.first, &temp)) {
@@ -584,8 +1250,9 @@ bool ThermalHelper::fillTemperatures(hidl_vec<Temperature_1_0> *temperatures) co
 
 bool ThermalHelper::fillCurrentTemperatures(bool filterType, TemperatureType_2_0 type,
                                             hidl_vec<Temperature_2_0> *temperatures) const {
+    const auto config = GetConfig();
     std::vector<Temperature_2_0> ret;
-    for (const auto &name_info_pair : sensor_info_map_) {
+    for (const auto &name_info_pair : config->sensor_info_map) {
         Temperature_2_0 temp;
         if (filterType && name_info_pair.second.type != type) {
             continue;
@@ -604,8 +1271,9 @@ bool ThermalHelper::fillCurrentTemperatures(bool filterType, TemperatureType_2_0
 
 bool ThermalHelper::fillTemperatureThresholds(bool filterType, TemperatureType_2_0 type,
                                               hidl_vec<TemperatureThreshold> *thresholds) const {
+    const auto config = GetConfig();
     std::vector<TemperatureThreshold> ret;
-    for (const auto &name_info_pair : sensor_info_map_) {
+    for (const auto &name_info_pair : config->sensor_info_map) {
         TemperatureThreshold temp;
         if (filterType && name_info_pair.second.type != type) {
             continue;
@@ -624,10 +1292,11 @@ bool ThermalHelper::fillTemperatureThresholds(bool filterType, TemperatureType_2
 
 bool ThermalHelper::fillCurrentCoolingDevices(bool filterType, CoolingType type,
                                               hidl_vec<CoolingDevice_2_0> *cooling_devices) const {
+    const auto config = GetConfig();
     std::vector<CoolingDevice_2_0> ret;
-    for (const auto &name_info_pair : cooling_device_info_map_) {
+    for (const auto &name_info_pair : config->cooling_device_info_map) {
         CoolingDevice_2_0 value;
-        if (filterType && name_info_pair.second != type) {
+        if (filterType && name_info_pair.second.type != type) {
             continue;
         }
         if (readCoolingDevice(name_info_pair.first, &value)) {
@@ -649,14 +1318,16 @@ bool ThermalHelper::fillCpuUsages(hidl_vec<CpuUsage> *cpu_usages) const {
 
 // This is called in the different thread context and will update sensor_status
 // uevent_sensors is the set of sensors which trigger uevent from thermal core driver.
-bool ThermalHelper::thermalWatcherCallbackFunc(const std::set<std::string> &uevent_sensors) {
-    std::vector<Temperature_2_0> temps;
-    bool thermal_triggered = false;
+std::chrono::milliseconds ThermalHelper::thermalWatcherCallbackFunc(
+        const std::set<std::string> &uevent_sensors) {
+    std::chrono::milliseconds polling_delay = kNoPollingDelay;
+    std::unique_lock<std::mutex> _callback_lock(thermal_watcher_callback_mutex_);
+    const auto config = GetConfig();
     for (auto &name_status_pair : sensor_status_map_) {
         Temperature_2_0 temp;
         TemperatureThreshold threshold;
         SensorStatus &sensor_status = name_status_pair.second;
-        const SensorInfo &sensor_info = sensor_info_map_.at(name_status_pair.first);
+        const SensorInfo &sensor_info = config->sensor_info_map.at(name_status_pair.first);
         // Only send notification on whitelisted sensors
         if (!sensor_info.is_monitor) {
             continue;
@@ -664,9 +1335,7 @@ bool ThermalHelper::thermalWatcherCallbackFunc(const std::set<std::string> &ueve
         // If callback is triggered by uevent, only check the sensors within uevent_sensors
         if (uevent_sensors.size() != 0 &&
             uevent_sensors.find(name_status_pair.first) == uevent_sensors.end()) {
-            if (sensor_status.severity != ThrottlingSeverity::NONE) {
-                thermal_triggered = true;
-            }
+            polling_delay = std::min(polling_delay, getPollingDelay(sensor_info, sensor_status));
             continue;
         }
 
@@ -691,29 +1360,346 @@ bool ThermalHelper::thermalWatcherCallbackFunc(const std::set<std::string> &ueve
             if (throtting_status.second != sensor_status.prev_cold_severity) {
                 sensor_status.prev_cold_severity = throtting_status.second;
             }
-            if (temp.throttlingStatus != sensor_status.severity) {
-                temps.push_back(temp);
-                sensor_status.severity = temp.throttlingStatus;
+            if (sensor_info.prediction_info != nullptr) {
+                updatePrediction(sensor_info, active_profile_,
+                                 {
+                                         .timestamp = boot_clock::now(),
+                                         .value = temp.value,
+                                         .severity = temp.throttlingStatus,
+                                 },
+                                 &sensor_status);
+                temp.throttlingStatus = std::max({throtting_status.first, throtting_status.second,
+                                                  sensor_status.predicted_severity});
+            }
+            updateSensorStats(boot_clock::now(), &sensor_status);
+            sensor_status.peak_temp = std::fmax(sensor_status.peak_temp, temp.value);
+            sensor_status.severity = temp.throttlingStatus;
+            queueNotification(temp, &sensor_status);
+            if (sensor_info.pid_info != nullptr) {
+                updatePidBudget(*config, *sensor_info.pid_info, temp.value, &sensor_status);
             }
         }
-        if (sensor_status.severity != ThrottlingSeverity::NONE) {
-            thermal_triggered = true;
+        {
+            std::lock_guard<std::mutex> _lock(sensor_history_mutex_);
+            sensor_history_map_.at(name_status_pair.first)
+                    .addSample({
+                            .timestamp = boot_clock::now(),
+                            .value = temp.value,
+                            .severity = temp.throttlingStatus,
+                    });
+        }
+        polling_delay = std::min(polling_delay, getPollingDelay(sensor_info, sensor_status));
+        if (sensor_status.severity != ThrottlingSeverity::NONE || sensor_status.pid_engaged) {
             LOG(INFO) << temp.name << ": " << temp.value;
         }
     }
-    if (!temps.empty() && cb_) {
-        cb_(temps);
+    // Every pass, so a failed write is retried
+    applyCoolingDeviceRequests(*config);
+    applyChargerThrottling(*config);
+    updateCdevStats(*config);
+    const auto temps = flushNotifications(*config, &polling_delay);
+    const auto atoms = takeThermalStats(*config);
+    _callback_lock.unlock();
+    if (!atoms.empty()) {
+        reportThermalStats(atoms);
     }
+    if (!temps.empty()) {
+        // Power hints are sent once no matter how many front-ends are registered
+        for (const auto &t : temps) {
+            sendPowerExtHint(t);
+        }
+        std::lock_guard<std::mutex> _lock(notification_callback_mutex_);
+        for (const auto &cb : notification_callbacks_) {
+            cb(temps);
+        }
+    }
+
+    return polling_delay;
+}
+
+void ThermalHelper::queueNotification(const Temperature_2_0 &temp, SensorStatus *sensor_status) {
+    auto it = pending_notifications_.find(temp.name);
+    if (temp.throttlingStatus == sensor_status->notified_severity) {
+        // Back to the notified severity before the pending change went out
+        if (it != pending_notifications_.end()) {
+            pending_notifications_.erase(it);
+            sensor_status->suppressed_notifications++;
+        }
+        return;
+    }
+    if (it == pending_notifications_.end()) {
+        pending_notifications_.emplace(temp.name, temp);
+        return;
+    }
+    if (it->second.throttlingStatus != temp.throttlingStatus) {
+        sensor_status->suppressed_notifications++;
+    }
+    it->second = temp;
+}
+
+std::vector<Temperature_2_0> ThermalHelper::flushNotifications(
+        const ThermalConfig &config, std::chrono::milliseconds *polling_delay) {
+    std::vector<Temperature_2_0> temps;
+    if (pending_notifications_.empty()) {
+        return temps;
+    }
+
+    const auto now = boot_clock::now();
+    const auto next_notification_time =
+            last_notification_time_ + config.notification_info.min_interval;
+    const bool rate_limited = now < next_notification_time;
+
+    std::unique_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+    for (auto it = pending_notifications_.begin(); it != pending_notifications_.end();) {
+        SensorStatus &sensor_status = sensor_status_map_.at(it->first);
+        const SensorInfo &sensor_info = config.sensor_info_map.at(it->first);
+        // Escalation to SEVERE and above is not held back by the rate limit
+        if (rate_limited && (it->second.throttlingStatus <= sensor_status.notified_severity ||
+                             it->second.throttlingStatus < ThrottlingSeverity::SEVERE)) {
+            *polling_delay = std::min(
+                    *polling_delay,
+                    std::chrono::ceil<std::chrono::milliseconds>(next_notification_time - now));
+            ++it;
+            continue;
+        }
+        // Escalation goes out right away, a lower severity waits for the dwell time
+        if (it->second.throttlingStatus < sensor_status.notified_severity) {
+            const auto dwell_end_time =
+                    sensor_status.last_notified_time +
+                    sensor_info.min_dwell_times[static_cast<size_t>(
+                            sensor_status.notified_severity)];
+            if (now < dwell_end_time) {
+                *polling_delay = std::min(
+                        *polling_delay,
+                        std::chrono::ceil<std::chrono::milliseconds>(dwell_end_time - now));
+                ++it;
+                continue;
+            }
+        }
+        temps.push_back(it->second);
+        sensor_status.notified_severity = it->second.throttlingStatus;
+        sensor_status.last_notified_time = now;
+        it = pending_notifications_.erase(it);
+    }
+    if (!temps.empty()) {
+        last_notification_time_ = now;
+    }
+    return temps;
+}
+
+void ThermalHelper::updateCdevStats(const ThermalConfig &config) {
+    const auto now = boot_clock::now();
+    std::lock_guard<std::mutex> _lock(cdev_stats_mutex_);
+    for (auto &name_stats_pair : cdev_stats_map_) {
+        std::string data;
+        int state;
+        if (!config.cooling_devices.readThermalFile(name_stats_pair.first, &data) ||
+            !android::base::ParseInt(data, &state, 0)) {
+            continue;
+        }
+        CdevStats &cdev_stats = name_stats_pair.second;
+        // Credit the time since the last sample to the state seen back then
+        if (cdev_stats.state >= 0) {
+            cdev_stats.time_in_state[cdev_stats.state] +=
+                    std::chrono::duration_cast<std::chrono::milliseconds>(
+                            now - cdev_stats.last_update_time);
+            if (state != cdev_stats.state) {
+                cdev_stats.transitions++;
+                cdev_stats.last_change_time = now;
+            }
+        }
+        cdev_stats.state = state;
+        cdev_stats.last_update_time = now;
+    }
+}
+
+std::vector<VendorAtom> ThermalHelper::takeThermalStats(const ThermalConfig &config) {
+    std::vector<VendorAtom> atoms;
+    const auto now = boot_clock::now();
+    if (now - last_stats_report_time_ < kThermalStatsReportInterval) {
+        return atoms;
+    }
+    last_stats_report_time_ = now;
+
+    std::unique_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+    for (auto &name_status_pair : sensor_status_map_) {
+        SensorStatus &sensor_status = name_status_pair.second;
+        auto it = config.sensor_info_map.find(name_status_pair.first);
+        if (it == config.sensor_info_map.end() || !it->second.is_monitor) {
+            continue;
+        }
+        updateSensorStats(now, &sensor_status);
+        // Not read since the last report
+        if (std::isnan(sensor_status.peak_temp)) {
+            sensor_status.time_in_severity = {};
+            continue;
+        }
+
+        std::vector<VendorAtom::Value> values(ThermalSensorStats::kPeakTemperatureFieldNumber -
+                                              kVendorAtomOffset + 1);
+        VendorAtom::Value tmp;
+        tmp.stringValue(name_status_pair.first);
+        values[ThermalSensorStats::kSensorNameFieldNumber - kVendorAtomOffset] = tmp;
+        for (size_t i = 0; i < kThrottlingSeverityCount; ++i) {
+            tmp.longValue(sensor_status.time_in_severity[i].count());
+            values[ThermalSensorStats::kTimeInNoneMsFieldNumber - kVendorAtomOffset + i] = tmp;
+        }
+        tmp.floatValue(sensor_status.peak_temp);
+        values[ThermalSensorStats::kPeakTemperatureFieldNumber - kVendorAtomOffset] = tmp;
+        atoms.push_back({.reverseDomainName = PixelAtoms::ReverseDomainNames().pixel(),
+                         .atomId = PixelAtoms::Ids::THERMAL_SENSOR_STATS,
+                         .values = values});
+
+        sensor_status.time_in_severity = {};
+        sensor_status.peak_temp = NAN;
+    }
+    return atoms;
+}
+
+void ThermalHelper::updatePidBudget(const ThermalConfig &config, const PIDInfo &pid_info,
+                                    float temp, SensorStatus *sensor_status) {
+    std::map<std::string, int> cdev_requests;
+    const auto now = boot_clock::now();
+
+    if (temp < pid_info.switch_on_temp) {
+        // Release all binded cooling devices and reset the controller
+        sensor_status->pid_engaged = false;
+        sensor_status->i_term = 0.0;
+        sensor_status->prev_err = 0.0;
+        sensor_status->power_budget = std::numeric_limits<float>::max();
+        for (const auto &cdev : pid_info.binded_cdevs) {
+            cdev_requests[cdev] = 0;
+        }
+    } else {
+        const float err = pid_info.target_temp - temp;
+        float d_term = 0.0;
+        if (sensor_status->pid_engaged) {
+            const float dt = std::chrono::duration_cast<std::chrono::milliseconds>(
+                                     now - sensor_status->last_pid_update_time)
+                                     .count() /
+                             1000.0;
+            if (dt > 0) {
+                sensor_status->i_term += pid_info.k_i * err * dt;
+                sensor_status->i_term =
+                        std::clamp(sensor_status->i_term, -pid_info.i_max, pid_info.i_max);
+                d_term = pid_info.k_d * (err - sensor_status->prev_err) / dt;
+            }
+        }
+        const float budget = pid_info.s_power + pid_info.k_p * err + sensor_status->i_term + d_term;
+        sensor_status->power_budget =
+                std::clamp(budget, pid_info.min_budget, pid_info.max_budget);
+        sensor_status->prev_err = err;
+        sensor_status->pid_engaged = true;
+        LOG(VERBOSE) << "PID err: " << err << " i_term: " << sensor_status->i_term
+                     << " d_term: " << d_term << " budget: " << sensor_status->power_budget;
+
+        // Spread the budget by weight, each cooling device picks the least
+        // throttled state that fits into its share
+        const float total_weight = std::accumulate(pid_info.cdev_weights.begin(),
+                                                   pid_info.cdev_weights.end(), 0.0f);
+        for (size_t i = 0; i < pid_info.binded_cdevs.size(); ++i) {
+            const auto &state2power =
+                    config.cooling_device_info_map.at(pid_info.binded_cdevs[i]).state2power;
+            const float cdev_budget =
+                    sensor_status->power_budget * pid_info.cdev_weights[i] / total_weight;
+            int state = 0;
+            while (static_cast<size_t>(state) < state2power.size() - 1 &&
+                   state2power[state] > cdev_budget) {
+                ++state;
+            }
+            cdev_requests[pid_info.binded_cdevs[i]] = state;
+        }
+    }
+    sensor_status->last_pid_update_time = now;
+    sensor_status->cdev_requests = std::move(cdev_requests);
+}
+
+void ThermalHelper::applyCoolingDeviceRequests(const ThermalConfig &config) {
+    std::map<std::string, int> cdev_states;
+    {
+        std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+        for (const auto &name_status_pair : sensor_status_map_) {
+            for (const auto &cdev_request : name_status_pair.second.cdev_requests) {
+                cdev_states[cdev_request.first] =
+                        std::max(cdev_states[cdev_request.first], cdev_request.second);
+            }
+        }
+    }
+
+    for (const auto &cdev_state : cdev_states) {
+        auto it = cdev_applied_state_map_.find(cdev_state.first);
+        if (it != cdev_applied_state_map_.end() && it->second == cdev_state.second) {
+            continue;
+        }
+        if (!config.cooling_devices.writeThermalFile(cdev_state.first,
+                                                     std::to_string(cdev_state.second))) {
+            LOG(ERROR) << "Failed to set cooling device " << cdev_state.first << " to state "
+                       << cdev_state.second;
+            continue;
+        }
+        LOG(INFO) << "Set cooling device " << cdev_state.first << " to state "
+                  << cdev_state.second;
+        cdev_applied_state_map_[cdev_state.first] = cdev_state.second;
+    }
+}
+
+void ThermalHelper::applyChargerThrottling(const ThermalConfig &config) {
+    std::map<std::string, ChargerLimit> limits;
+    {
+        std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+        limits = getChargerLimits(config.sensor_info_map, sensor_status_map_);
+    }
+    writeChargerLimits(limits);
+}
+
+void ThermalHelper::writeChargerLimits(const std::map<std::string, ChargerLimit> &limits) {
+    std::lock_guard<std::mutex> _lock(charger_limit_mutex_);
+    for (const auto &node_limit_pair : limits) {
+        auto it = charger_limit_map_.find(node_limit_pair.first);
+        if (it != charger_limit_map_.end() &&
+            it->second.current_limit == node_limit_pair.second.current_limit) {
+            it->second = node_limit_pair.second;
+            continue;
+        }
+        // power_supply nodes take the current in uA
+        const std::string value =
+                std::to_string(static_cast<int64_t>(node_limit_pair.second.current_limit * 1000));
+        if (!android::base::WriteStringToFile(value, node_limit_pair.first)) {
+            PLOG(ERROR) << "Failed to write charge current limit " << value << " to "
+                        << node_limit_pair.first;
+            continue;
+        }
+        LOG(INFO) << "Set charge current limit of " << node_limit_pair.first << " to "
+                  << node_limit_pair.second.current_limit << "mA by "
+                  << node_limit_pair.second.sensor << " at "
+                  << toString(node_limit_pair.second.severity);
+        charger_limit_map_[node_limit_pair.first] = node_limit_pair.second;
+    }
+}
+
+ThermalSnapshot ThermalHelper::GetThermalSnapshot() const {
+    std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+    std::lock_guard<std::mutex> _fault_lock(sensor_fault_mutex_);
+    return {
+            .config = config_,
+            .sensor_status_map = sensor_status_map_,
+            .sensor_fault_map = sensor_fault_map_,
+    };
+}
 
-    return thermal_triggered;
+std::map<std::string, SensorStatus> ThermalHelper::GetSensorStatusMap() const {
+    std::shared_lock<std::shared_mutex> _lock(sensor_status_map_mutex_);
+    return sensor_status_map_;
 }
 
 bool ThermalHelper::connectToPowerHal() {
     return power_hal_service_.connect();
 }
 
-void ThermalHelper::updateSupportedPowerHints() {
-    for (auto const &name_status_pair : sensor_info_map_) {
+std::map<std::string, std::map<ThrottlingSeverity, ThrottlingSeverity>>
+ThermalHelper::getSupportedPowerHints(const ThermalConfig &config) {
+    std::map<std::string, std::map<ThrottlingSeverity, ThrottlingSeverity>> supported_powerhint_map;
+    for (auto const &name_status_pair : config.sensor_info_map) {
         if (!name_status_pair.second.send_powerhint) {
             continue;
         }
@@ -723,7 +1709,7 @@ void ThermalHelper::updateSupportedPowerHints() {
                        << " current_severity :" << toString(current_severity) << " severity "
                        << toString(severity);
             if (severity == ThrottlingSeverity::NONE) {
-                supported_powerhint_map_[name_status_pair.first][ThrottlingSeverity::NONE] =
+                supported_powerhint_map[name_status_pair.first][ThrottlingSeverity::NONE] =
                         ThrottlingSeverity::NONE;
                 continue;
             }
@@ -736,9 +1722,10 @@ void ThermalHelper::updateSupportedPowerHints() {
             }
             if (isSupported)
                 current_severity = severity;
-            supported_powerhint_map_[name_status_pair.first][severity] = current_severity;
+            supported_powerhint_map[name_status_pair.first][severity] = current_severity;
         }
     }
+    return supported_powerhint_map;
 }
 
 void ThermalHelper::sendPowerExtHint(const Temperature_2_0 &t) {
@@ -746,7 +1733,9 @@ void ThermalHelper::sendPowerExtHint(const Temperature_2_0 &t) {
     if (!isAidlPowerHalExist())
         return;
 
-    if (!sensor_info_map_.at(t.name).send_powerhint)
+    // The sensor might be gone if the config was reloaded since the notification
+    auto it = config_->sensor_info_map.find(t.name);
+    if (it == config_->sensor_info_map.end() || !it->second.send_powerhint)
         return;
 
     ThrottlingSeverity prev_hint_severity;
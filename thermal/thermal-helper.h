@@ -31,7 +31,10 @@
 #define THERMAL_THERMAL_HELPER_H__
 
 #include <array>
+#include <atomic>
 #include <chrono>
+#include <deque>
+#include <memory>
 #include <mutex>
 #include <shared_mutex>
 #include <string>
@@ -42,10 +45,13 @@
 
 #include <aidl/android/hardware/power/IPower.h>
 #include <aidl/google/hardware/power/extension/pixel/IPowerExt.h>
+#include <android/frameworks/stats/1.0/IStats.h>
 #include <android/hardware/thermal/2.0/IThermal.h>
 
 #include "utils/config_parser.h"
+#include "utils/sensor_history.h"
 #include "utils/thermal_files.h"
+#include "utils/thermal_severity.h"
 #include "utils/thermal_watcher.h"
 
 namespace android {
@@ -56,6 +62,7 @@ namespace implementation {
 
 using ::aidl::android::hardware::power::IPower;
 using ::aidl::google::hardware::power::extension::pixel::IPowerExt;
+using ::android::frameworks::stats::V1_0::VendorAtom;
 using ::android::hardware::hidl_vec;
 using ::android::hardware::thermal::V1_0::CpuUsage;
 using ::android::hardware::thermal::V2_0::CoolingType;
@@ -77,6 +84,95 @@ struct SensorStatus {
     ThrottlingSeverity prev_hot_severity;
     ThrottlingSeverity prev_cold_severity;
     ThrottlingSeverity prev_hint_severity;
+    // PID controller state, only used when SensorInfo has pid_info
+    bool pid_engaged;
+    float i_term;
+    float prev_err;
+    float power_budget;
+    boot_clock::time_point last_pid_update_time;
+    // Cooling state requested by this sensor for each binded cooling device
+    std::map<std::string, int> cdev_requests;
+    // Prediction state, only used when SensorInfo has prediction_info
+    std::deque<SensorSample> prediction_samples;
+    float slope;
+    ThrottlingSeverity predicted_severity;
+    // The severity listeners were last notified of
+    ThrottlingSeverity notified_severity;
+    boot_clock::time_point last_notified_time;
+    // Severity changes dropped before being notified
+    size_t suppressed_notifications;
+    // Statistics since the last report, only used for monitored sensors
+    std::array<std::chrono::milliseconds, kThrottlingSeverityCount> time_in_severity;
+    float peak_temp;
+    boot_clock::time_point last_stats_update_time;
+};
+
+enum class SensorFault : uint32_t {
+    NONE = 0,
+    READ_ERROR,
+    OUT_OF_RANGE,
+    RATE_OF_CHANGE,
+    STUCK,
+};
+
+std::string_view toString(SensorFault fault);
+
+// Plausibility state of a sensor with fault_info
+struct SensorFaultStatus {
+    SensorFault fault;
+    // Number of times the sensor turned faulty
+    size_t fault_count;
+    // Last raw reading and the value reported in its place while faulty
+    float last_value;
+    float reported_value;
+    boot_clock::time_point last_read_time;
+    boot_clock::time_point last_change_time;
+};
+
+// Latest reading of a sensor with transform_info
+struct SensorTransformStatus {
+    float raw_value;
+    // Output of the transform, it also holds the EMA state
+    float processed_value;
+    boot_clock::time_point last_update_time;
+};
+
+// State residency of a cooling device, sampled on every watcher callback
+struct CdevStats {
+    // Last sampled state, -1 before the first sample
+    int state;
+    std::map<int, std::chrono::milliseconds> time_in_state;
+    size_t transitions;
+    boot_clock::time_point last_change_time;
+    boot_clock::time_point last_update_time;
+    boot_clock::time_point reset_time;
+};
+
+// Charge current limit applied to a power_supply node
+struct ChargerLimit {
+    // In mA
+    float current_limit;
+    // The sensor and its severity which set the limit
+    std::string sensor;
+    ThrottlingSeverity severity;
+};
+
+// Parsed thermal config and the sysfs files it resolves to. A loaded config is
+// never modified, reloading replaces it as a whole.
+struct ThermalConfig {
+    std::map<std::string, CdevInfo> cooling_device_info_map;
+    std::map<std::string, SensorInfo> sensor_info_map;
+    NotificationInfo notification_info;
+    ThermalFiles thermal_sensors;
+    ThermalFiles cooling_devices;
+};
+
+// The config with the sensor states taken along with it, so the maps do not
+// straddle a config reload.
+struct ThermalSnapshot {
+    std::shared_ptr<const ThermalConfig> config;
+    std::map<std::string, SensorStatus> sensor_status_map;
+    std::map<std::string, SensorFaultStatus> sensor_fault_map;
 };
 
 class PowerHalService {
@@ -99,8 +195,15 @@ class PowerHalService {
 
 class ThermalHelper {
   public:
-    ThermalHelper(const NotificationCallback &cb);
-    ~ThermalHelper() = default;
+    ThermalHelper();
+    // Use the thermal zones and cooling devices under thermal_root and the
+    // config at config_path instead of the device ones, e.g. a fake sysfs tree.
+    ThermalHelper(std::string_view thermal_root, std::string_view config_path);
+    ~ThermalHelper();
+
+    // Register a front-end to be notified of severity changes, this can be
+    // called in any thread.
+    void addNotificationCallback(const NotificationCallback &cb);
 
     bool fillTemperatures(hidl_vec<Temperature_1_0> *temperatures) const;
     bool fillCurrentTemperatures(bool filterType, TemperatureType_2_0 type,
@@ -125,8 +228,32 @@ class ThermalHelper {
     bool readTemperatureThreshold(std::string_view sensor_name, TemperatureThreshold *out) const;
     // Read the value of a single cooling device.
     bool readCoolingDevice(std::string_view cooling_device, CoolingDevice_2_0 *out) const;
-    // Get SensorInfo Map
-    const std::map<std::string, SensorInfo> &GetSensorInfoMap() const { return sensor_info_map_; }
+    // Get the current config, it stays valid even if the config is reloaded.
+    std::shared_ptr<const ThermalConfig> GetConfig() const;
+    // Get the current config along with the sensor status and fault maps
+    ThermalSnapshot GetThermalSnapshot() const;
+    // Get a snapshot of SensorStatus Map
+    std::map<std::string, SensorStatus> GetSensorStatusMap() const;
+    // Get a snapshot of the recent readings of monitored sensors
+    std::map<std::string, SensorHistory> GetSensorHistoryMap() const;
+    // Get a snapshot of the state residency of cooling devices
+    std::map<std::string, CdevStats> GetCdevStatsMap() const;
+    // Clear the residency and transitions, the current state is kept
+    void resetCdevStats();
+    // Get a snapshot of the fault state of sensors with fault detection
+    std::map<std::string, SensorFaultStatus> GetSensorFaultMap() const;
+    // Get a snapshot of the raw and processed readings of sensors with transforms
+    std::map<std::string, SensorTransformStatus> GetSensorTransformMap() const;
+    // Get a snapshot of the charge current limit applied to each power_supply node
+    std::map<std::string, ChargerLimit> GetChargerLimitMap() const;
+    // Switch the thresholds of all sensors to the named profile, an empty name
+    // is the default thresholds. Return false if no sensor defines the profile.
+    bool setProfile(std::string_view profile);
+    // Get the active profile, empty for the default thresholds
+    std::string getActiveProfile() const;
+    // Re-parse the config file and switch to it. The current config is kept
+    // if the new one fails validation.
+    bool reloadConfig();
 
     void sendPowerExtHint(const Temperature_2_0 &t);
 
@@ -135,35 +262,110 @@ class ThermalHelper {
     bool isPowerHalExtConnected() { return power_hal_service_.isPowerHalExtConnected(); }
 
   private:
-    bool initializeSensorMap(const std::map<std::string, std::string> &path_map);
-    bool initializeCoolingDevices(const std::map<std::string, std::string> &path_map);
-    bool initializeTrip(const std::map<std::string, std::string> &path_map);
-
-    // For thermal_watcher_'s polling thread
-    bool thermalWatcherCallbackFunc(const std::set<std::string> &uevent_sensors);
-    // Return hot and cold severity status as std::pair
-    std::pair<ThrottlingSeverity, ThrottlingSeverity> getSeverityFromThresholds(
-        const ThrottlingArray &hot_thresholds, const ThrottlingArray &cold_thresholds,
-        const ThrottlingArray &hot_hysteresis, const ThrottlingArray &cold_hysteresis,
-        ThrottlingSeverity prev_hot_severity, ThrottlingSeverity prev_cold_severity,
-        float value) const;
+    // Parse the config file and resolve its sysfs files, return nullptr if invalid.
+    std::unique_ptr<ThermalConfig> loadConfig(
+            const std::map<std::string, std::string> &tz_map,
+            const std::map<std::string, std::string> &cdev_map) const;
+    bool initializeSensorMap(const std::map<std::string, std::string> &path_map,
+                             ThermalConfig *config) const;
+    bool initializeCoolingDevices(const std::map<std::string, std::string> &path_map,
+                                  ThermalConfig *config) const;
+    bool initializeTrip(const std::map<std::string, std::string> &path_map,
+                        const ThermalConfig &config, std::string_view profile) const;
+    // Return true if the profile is empty, i.e. the default thresholds, or any
+    // sensor of the config defines it.
+    bool isProfileDefined(const ThermalConfig &config, std::string_view profile) const;
+    // Switch the profile whenever kProfileProperty changes, until destruction.
+    void profileWatcherLoop();
+    // Read the processed value of a physical or virtual sensor.
+    bool readThermalSensor(const ThermalConfig &config, std::string_view sensor_name,
+                           float *temp) const;
+    // Read the value of a physical or virtual sensor without fault detection.
+    bool readSensorValue(const ThermalConfig &config, std::string_view sensor_name,
+                         const SensorInfo &sensor_info, float *temp) const;
+    // Apply the sensor's transform to a raw reading and update its EMA state.
+    float applyTransform(std::string_view sensor_name, const SensorInfo &sensor_info,
+                         float raw_value) const;
+    // Check a raw reading, NAN if the read failed, against the sensor's rules
+    // and update its fault state.
+    SensorFault updateSensorFault(std::string_view sensor_name, const FaultInfo &fault_info,
+                                  float value) const;
+
+    // For thermal_watcher_'s polling thread, return the delay until the next polling
+    std::chrono::milliseconds thermalWatcherCallbackFunc(
+            const std::set<std::string> &uevent_sensors);
+
+    // Run one PID step for the sensor and update its cooling device requests.
+    // Caller should hold sensor_status_map_mutex_.
+    void updatePidBudget(const ThermalConfig &config, const PIDInfo &pid_info, float temp,
+                         SensorStatus *sensor_status);
+    // Queue the sensor's severity change for notification, caller should hold
+    // thermal_watcher_callback_mutex_ and sensor_status_map_mutex_.
+    void queueNotification(const Temperature_2_0 &temp, SensorStatus *sensor_status);
+    // Take the queued changes which are past their dwell time, if the rate limit
+    // allows. polling_delay is lowered to when the held changes are due.
+    std::vector<Temperature_2_0> flushNotifications(const ThermalConfig &config,
+                                                    std::chrono::milliseconds *polling_delay);
+    // Take the statistics of monitored sensors once per kThermalStatsReportInterval
+    // and reset them, the atoms are reported to IStats by the caller.
+    std::vector<VendorAtom> takeThermalStats(const ThermalConfig &config);
+    // Sample the cur_state of each cooling device into cdev_stats_map_.
+    void updateCdevStats(const ThermalConfig &config);
+    // Write the highest state requested across sensors to each cooling device,
+    // skipping the ones already written at that state.
+    void applyCoolingDeviceRequests(const ThermalConfig &config);
+    // Write the lowest charge current limit across sensors to each power_supply node.
+    void applyChargerThrottling(const ThermalConfig &config);
+    // Write the given limits, skipping the nodes already at their limit.
+    void writeChargerLimits(const std::map<std::string, ChargerLimit> &limits);
 
     bool connectToPowerHal();
-    void updateSupportedPowerHints();
+    std::map<std::string, std::map<ThrottlingSeverity, ThrottlingSeverity>>
+    getSupportedPowerHints(const ThermalConfig &config);
 
+    const std::string thermal_root_;
+    const std::string config_path_;
     sp<ThermalWatcher> thermal_watcher_;
-    ThermalFiles thermal_sensors_;
-    ThermalFiles cooling_devices_;
     bool is_initialized_;
-    const NotificationCallback cb_;
-    const std::map<std::string, CoolingType> cooling_device_info_map_;
-    const std::map<std::string, SensorInfo> sensor_info_map_;
+    std::mutex notification_callback_mutex_;
+    std::vector<NotificationCallback> notification_callbacks_;
     std::map<std::string, std::map<ThrottlingSeverity, ThrottlingSeverity>>
             supported_powerhint_map_;
     PowerHalService power_hal_service_;
 
+    // Serialize the watcher callback with config reload
+    std::mutex thermal_watcher_callback_mutex_;
     mutable std::shared_mutex sensor_status_map_mutex_;
+    // Guarded by sensor_status_map_mutex_
+    std::shared_ptr<const ThermalConfig> config_;
     std::map<std::string, SensorStatus> sensor_status_map_;
+    std::string active_profile_;
+    // Last state written to each PID binded cooling device
+    std::map<std::string, int> cdev_applied_state_map_;
+    // Guarded by thermal_watcher_callback_mutex_, latest change of each sensor
+    // not notified yet
+    std::map<std::string, Temperature_2_0> pending_notifications_;
+    boot_clock::time_point last_notification_time_;
+    boot_clock::time_point last_stats_report_time_;
+
+    mutable std::mutex sensor_history_mutex_;
+    std::map<std::string, SensorHistory> sensor_history_map_;
+
+    mutable std::mutex cdev_stats_mutex_;
+    std::map<std::string, CdevStats> cdev_stats_map_;
+
+    mutable std::mutex sensor_transform_mutex_;
+    mutable std::map<std::string, SensorTransformStatus> sensor_transform_map_;
+
+    std::thread profile_watcher_thread_;
+    std::atomic<bool> profile_watcher_exit_ = false;
+
+    mutable std::mutex charger_limit_mutex_;
+    std::map<std::string, ChargerLimit> charger_limit_map_;
+
+    // Updated by every reading, including the const ones from binder threads
+    mutable std::mutex sensor_fault_mutex_;
+    mutable std::map<std::string, SensorFaultStatus> sensor_fault_map_;
 };
 
 }  // namespace implementation
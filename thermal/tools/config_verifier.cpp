@@ -0,0 +1,426 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#include <android-base/file.h>
+#include <android-base/logging.h>
+#include <android-base/parsedouble.h>
+#include <android-base/strings.h>
+#include <getopt.h>
+
+#include <algorithm>
+#include <iostream>
+#include <map>
+#include <regex>
+#include <sstream>
+#include <string>
+#include <vector>
+
+#include <json/reader.h>
+#include <json/value.h>
+
+#include "../utils/config_parser.h"
+#include "../utils/thermal_severity.h"
+
+namespace android {
+namespace hardware {
+namespace thermal {
+namespace V2_0 {
+namespace implementation {
+
+namespace {
+
+using ::android::hardware::thermal::V2_0::toString;
+
+bool matchSchemaType(const Json::Value &value, const std::string &type) {
+    if (type == "object") return value.isObject();
+    if (type == "array") return value.isArray();
+    if (type == "string") return value.isString();
+    if (type == "number") return value.isNumeric();
+    if (type == "integer") return value.isIntegral();
+    if (type == "boolean") return value.isBool();
+    if (type == "null") return value.isNull();
+    return false;
+}
+
+// Validate value against the subset of JSON schema draft-07 used by
+// config_schema.json: type, required, properties, items, minItems, maxItems,
+// pattern, enum, minimum and exclusiveMinimum.
+bool validateSchema(const Json::Value &value, const Json::Value &schema, const std::string &path) {
+    bool ret = true;
+
+    const Json::Value &type = schema["type"];
+    if (!type.isNull()) {
+        bool type_matched = false;
+        if (type.isArray()) {
+            for (Json::Value::ArrayIndex i = 0; i < type.size(); ++i) {
+                type_matched |= matchSchemaType(value, type[i].asString());
+            }
+        } else {
+            type_matched = matchSchemaType(value, type.asString());
+        }
+        if (!type_matched) {
+            LOG(ERROR) << path << ": expected type " << type.toStyledString();
+            return false;
+        }
+    }
+
+    if (!schema["enum"].isNull()) {
+        bool enum_matched = false;
+        for (Json::Value::ArrayIndex i = 0; i < schema["enum"].size(); ++i) {
+            enum_matched |= (schema["enum"][i] == value);
+        }
+        if (!enum_matched) {
+            LOG(ERROR) << path << ": " << ::android::base::Trim(value.toStyledString())
+                       << " is not one of " << schema["enum"].toStyledString();
+            ret = false;
+        }
+    }
+
+    if (value.isString() && !schema["pattern"].isNull()) {
+        // Compile each pattern once, not for every value checked against it
+        static std::map<std::string, std::regex> patterns;
+        const std::string pattern = schema["pattern"].asString();
+        auto it = patterns.find(pattern);
+        if (it == patterns.end()) {
+            it = patterns.emplace(pattern, std::regex(pattern)).first;
+        }
+        if (!std::regex_search(value.asString(), it->second)) {
+            LOG(ERROR) << path << ": " << value.asString() << " does not match " << pattern;
+            ret = false;
+        }
+    }
+
+    if (value.isNumeric()) {
+        if (!schema["minimum"].isNull() && value.asDouble() < schema["minimum"].asDouble()) {
+            LOG(ERROR) << path << ": " << value.asDouble() << " < "
+                       << schema["minimum"].asDouble();
+            ret = false;
+        }
+        if (!schema["exclusiveMinimum"].isNull() &&
+            value.asDouble() <= schema["exclusiveMinimum"].asDouble()) {
+            LOG(ERROR) << path << ": " << value.asDouble()
+                       << " <= " << schema["exclusiveMinimum"].asDouble();
+            ret = false;
+        }
+    }
+
+    if (value.isObject()) {
+        const Json::Value &required = schema["required"];
+        for (Json::Value::ArrayIndex i = 0; i < required.size(); ++i) {
+            if (!value.isMember(required[i].asString())) {
+                LOG(ERROR) << path << ": missing required " << required[i].asString();
+                ret = false;
+            }
+        }
+        const Json::Value &properties = schema["properties"];
+        for (const auto &name : value.getMemberNames()) {
+            if (properties.isMember(name)) {
+                ret &= validateSchema(value[name], properties[name], path + "/" + name);
+            }
+        }
+    }
+
+    if (value.isArray()) {
+        if (!schema["minItems"].isNull() && value.size() < schema["minItems"].asUInt()) {
+            LOG(ERROR) << path << ": " << value.size() << " items < "
+                       << schema["minItems"].asUInt();
+            ret = false;
+        }
+        if (!schema["maxItems"].isNull() && value.size() > schema["maxItems"].asUInt()) {
+            LOG(ERROR) << path << ": " << value.size() << " items > "
+                       << schema["maxItems"].asUInt();
+            ret = false;
+        }
+        if (schema["items"].isObject()) {
+            for (Json::Value::ArrayIndex i = 0; i < value.size(); ++i) {
+                ret &= validateSchema(value[i], schema["items"],
+                                      path + "/" + std::to_string(i));
+            }
+        }
+    }
+
+    return ret;
+}
+
+bool readJson(const std::string &path, Json::Value *root) {
+    std::string json_doc;
+    if (!android::base::ReadFileToString(path, &json_doc)) {
+        LOG(ERROR) << "Failed to read JSON from " << path;
+        return false;
+    }
+    Json::Reader reader;
+    if (!reader.parse(json_doc, *root)) {
+        LOG(ERROR) << "Failed to parse JSON from " << path << ": "
+                   << reader.getFormattedErrorMessages();
+        return false;
+    }
+    return true;
+}
+
+}  // namespace
+
+bool VerifyConfig(const std::string &config_path, const std::string &schema_path) {
+    Json::Value config;
+    if (!readJson(config_path, &config)) {
+        return false;
+    }
+
+    if (!schema_path.empty()) {
+        Json::Value schema;
+        if (!readJson(schema_path, &schema)) {
+            return false;
+        }
+        if (!validateSchema(config, schema, "")) {
+            LOG(ERROR) << "Config does not match schema " << schema_path;
+            return false;
+        }
+    }
+
+    // The same checks the HAL runs at boot, short of resolving the sysfs nodes
+    std::map<std::string, SensorInfo> sensor_info_map;
+    std::map<std::string, CdevInfo> cooling_device_info_map;
+    NotificationInfo notification_info;
+    return ParseThermalConfig(config_path, &sensor_info_map, &cooling_device_info_map,
+                              &notification_info);
+}
+
+// Replay a CSV of "timestamp,<sensor>,<sensor>..." rows, the header row names
+// the sensors. Monitored virtual sensors are computed from their linked sensors
+// when not recorded. Severity transitions of monitored sensors under the
+// thresholds of profile, empty for the default ones, are written to out.
+bool ReplayTemperatures(const std::string &config_path, const std::string &csv_path,
+                        const std::string &profile, std::ostream *out) {
+    struct ReplayStatus {
+        std::string name;
+        ThrottlingSeverity severity;
+        ThrottlingSeverity prev_hot_severity;
+        ThrottlingSeverity prev_cold_severity;
+    };
+
+    const auto sensor_info_map = ParseSensorInfo(config_path);
+    if (sensor_info_map.empty()) {
+        LOG(ERROR) << "Failed to parse Sensors section from " << config_path;
+        return false;
+    }
+    if (!profile.empty() &&
+        std::none_of(sensor_info_map.begin(), sensor_info_map.end(),
+                     [&](const auto &name_info_pair) {
+                         return name_info_pair.second.profiles.count(profile) > 0;
+                     })) {
+        LOG(ERROR) << "No sensor defines profile " << profile;
+        return false;
+    }
+
+    std::string csv;
+    if (!android::base::ReadFileToString(csv_path, &csv)) {
+        LOG(ERROR) << "Failed to read CSV from " << csv_path;
+        return false;
+    }
+    std::vector<std::string> lines = android::base::Split(android::base::Trim(csv), "\n");
+    std::vector<std::string> columns = android::base::Split(lines[0], ",");
+    // Column of each recorded sensor
+    std::map<std::string, size_t> recorded;
+    for (size_t i = 1; i < columns.size(); ++i) {
+        columns[i] = android::base::Trim(columns[i]);
+        if (!sensor_info_map.count(columns[i])) {
+            LOG(WARNING) << "Sensor " << columns[i] << " not found in config, skipped";
+            continue;
+        }
+        recorded[columns[i]] = i;
+    }
+
+    std::vector<ReplayStatus> status_list;
+    for (const auto &name_info_pair : sensor_info_map) {
+        const SensorInfo &sensor_info = name_info_pair.second;
+        if (!sensor_info.is_monitor) {
+            continue;
+        }
+        if (!recorded.count(name_info_pair.first) &&
+            (sensor_info.virtual_sensor_info == nullptr ||
+             std::any_of(sensor_info.virtual_sensor_info->linked_sensors.begin(),
+                         sensor_info.virtual_sensor_info->linked_sensors.end(),
+                         [&](const std::string &linked) { return !recorded.count(linked); }))) {
+            LOG(WARNING) << "Monitored sensor " << name_info_pair.first
+                         << " can not be computed from the CSV, skipped";
+            continue;
+        }
+        status_list.push_back({name_info_pair.first, ThrottlingSeverity::NONE,
+                               ThrottlingSeverity::NONE, ThrottlingSeverity::NONE});
+    }
+
+    *out << "timestamp,sensor,value,from,to" << std::endl;
+    for (size_t line = 1; line < lines.size(); ++line) {
+        std::vector<std::string> fields = android::base::Split(lines[line], ",");
+        if (fields.size() != columns.size()) {
+            LOG(ERROR) << csv_path << ":" << line + 1 << ": expected " << columns.size()
+                       << " fields but got " << fields.size();
+            return false;
+        }
+        const std::string timestamp = android::base::Trim(fields[0]);
+        std::map<std::string, float> values;
+        for (const auto &name_column_pair : recorded) {
+            float value;
+            if (!android::base::ParseFloat(android::base::Trim(fields[name_column_pair.second]),
+                                           &value)) {
+                LOG(ERROR) << csv_path << ":" << line + 1 << ": invalid value for "
+                           << name_column_pair.first;
+                return false;
+            }
+            values[name_column_pair.first] = value;
+        }
+
+        for (auto &status : status_list) {
+            const SensorInfo &sensor_info = sensor_info_map.at(status.name);
+            float value;
+            if (values.count(status.name)) {
+                value = values.at(status.name);
+            } else {
+                const auto &virtual_sensor_info = *sensor_info.virtual_sensor_info;
+                std::vector<float> linked_temps;
+                for (const auto &linked_sensor : virtual_sensor_info.linked_sensors) {
+                    linked_temps.emplace_back(values.at(linked_sensor));
+                }
+                value = getVirtualSensorValue(virtual_sensor_info, linked_temps);
+            }
+
+            const ThresholdProfile thresholds = getThresholdProfile(sensor_info, profile);
+            auto severity = getSeverityFromThresholds(
+                    thresholds.hot_thresholds, sensor_info.cold_thresholds,
+                    thresholds.hot_hysteresis, sensor_info.cold_hysteresis,
+                    status.prev_hot_severity, status.prev_cold_severity, value);
+            status.prev_hot_severity = severity.first;
+            status.prev_cold_severity = severity.second;
+            ThrottlingSeverity throttling_status =
+                    static_cast<size_t>(severity.first) > static_cast<size_t>(severity.second)
+                            ? severity.first
+                            : severity.second;
+            if (throttling_status != status.severity) {
+                *out << timestamp << "," << status.name << "," << value << ","
+                     << toString(status.severity) << "," << toString(throttling_status)
+                     << std::endl;
+                status.severity = throttling_status;
+            }
+        }
+    }
+    return true;
+}
+
+}  // namespace implementation
+}  // namespace V2_0
+}  // namespace thermal
+}  // namespace hardware
+}  // namespace android
+
+static void printUsage(const char *exec_name) {
+    std::string usage = exec_name;
+    usage = usage +
+            " is a command-line tool to verify a thermal HAL Json config and replay recorded "
+            "temperatures through its thresholds.\n"
+            "Usages:\n"
+            "    " +
+            exec_name +
+            " [options]\n"
+            "\n"
+            "Options:\n"
+            "   --config, -c  [PATH]\n"
+            "       path to Json config file\n\n"
+            "   --schema, -s  [PATH]\n"
+            "       path to config_schema.json to validate the config against\n\n"
+            "   --replay, -r  [PATH]\n"
+            "       path to CSV of temperatures, the header row is timestamp followed by sensor "
+            "names\n\n"
+            "   --profile, -p  [NAME]\n"
+            "       threshold profile to replay under, the default thresholds if not set\n\n"
+            "   --help, -h\n"
+            "       print this message\n\n"
+            "   --verbose, -v\n"
+            "       print verbose log during execution\n\n";
+
+    std::cout << usage;
+}
+
+int main(int argc, char *argv[]) {
+    android::base::InitLogging(argv, android::base::StderrLogger);
+    android::base::SetMinimumLogSeverity(android::base::WARNING);
+
+    std::string config_path;
+    std::string schema_path;
+    std::string replay_path;
+    std::string profile;
+
+    while (true) {
+        static struct option opts[] = {
+                {"config", required_argument, nullptr, 'c'},
+                {"schema", required_argument, nullptr, 's'},
+                {"replay", required_argument, nullptr, 'r'},
+                {"profile", required_argument, nullptr, 'p'},
+                {"help", no_argument, nullptr, 'h'},
+                {"verbose", no_argument, nullptr, 'v'},
+                {0, 0, 0, 0}  // termination of the option list
+        };
+
+        int option_index = 0;
+        int c = getopt_long(argc, argv, "c:s:r:p:hv", opts, &option_index);
+        if (c == -1) {
+            break;
+        }
+
+        switch (c) {
+            case 'c':
+                config_path = optarg;
+                break;
+            case 's':
+                schema_path = optarg;
+                break;
+            case 'r':
+                replay_path = optarg;
+                break;
+            case 'p':
+                profile = optarg;
+                break;
+            case 'v':
+                android::base::SetMinimumLogSeverity(android::base::VERBOSE);
+                break;
+            case 'h':
+                printUsage(argv[0]);
+                return 0;
+            default:
+                // getopt already prints "invalid option -- %c" for us.
+                return 1;
+        }
+    }
+
+    if (config_path.empty()) {
+        LOG(ERROR) << "Need specify JSON config";
+        printUsage(argv[0]);
+        return 1;
+    }
+
+    if (!android::hardware::thermal::V2_0::implementation::VerifyConfig(config_path,
+                                                                        schema_path)) {
+        LOG(ERROR) << "Failed to verify thermal config " << config_path;
+        return 1;
+    }
+
+    if (!replay_path.empty() &&
+        !android::hardware::thermal::V2_0::implementation::ReplayTemperatures(
+                config_path, replay_path, profile, &std::cout)) {
+        LOG(ERROR) << "Failed to replay " << replay_path;
+        return 1;
+    }
+
+    return 0;
+}
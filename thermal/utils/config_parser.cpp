@@ -57,6 +57,478 @@ float getFloatFromValue(const Json::Value &value) {
     }
 }
 
+// Return false when failed parsing
+bool getFormulaFromString(std::string_view str, FormulaOption *out) {
+    static const std::map<std::string_view, FormulaOption> kFormulaMap = {
+            {"WEIGHTED_SUM", FormulaOption::WEIGHTED_SUM},
+            {"MAXIMUM", FormulaOption::MAXIMUM},
+            {"MINIMUM", FormulaOption::MINIMUM},
+            {"AVERAGE", FormulaOption::AVERAGE},
+    };
+    auto it = kFormulaMap.find(str);
+    if (it == kFormulaMap.end()) {
+        return false;
+    }
+    *out = it->second;
+    return true;
+}
+
+bool parseVirtualSensorInfo(const std::string &name, const Json::Value &sensor,
+                            std::unique_ptr<VirtualSensorInfo> *out) {
+    std::vector<std::string> linked_sensors;
+    std::vector<float> coefficients;
+    FormulaOption formula;
+
+    std::string formula_str = sensor["Formula"].asString();
+    LOG(INFO) << "VirtualSensor[" << name << "]'s Formula: " << formula_str;
+    if (!getFormulaFromString(formula_str, &formula)) {
+        LOG(ERROR) << "Invalid VirtualSensor[" << name << "]'s Formula: " << formula_str;
+        return false;
+    }
+
+    Json::Value values = sensor["Combination"];
+    if (values.size() == 0) {
+        LOG(ERROR) << "Invalid VirtualSensor[" << name << "]'s Combination, empty list";
+        return false;
+    }
+    for (Json::Value::ArrayIndex j = 0; j < values.size(); ++j) {
+        linked_sensors.emplace_back(values[j].asString());
+        LOG(INFO) << "VirtualSensor[" << name << "]'s Combination[" << j
+                  << "]: " << linked_sensors[j];
+    }
+
+    values = sensor["Coefficient"];
+    if (values.size() == 0 && formula != FormulaOption::WEIGHTED_SUM) {
+        LOG(INFO) << "Cannot find VirtualSensor[" << name
+                  << "]'s Coefficient, default all to 1.0";
+        coefficients.assign(linked_sensors.size(), 1.0);
+    } else if (values.size() != linked_sensors.size()) {
+        LOG(ERROR) << "Invalid VirtualSensor[" << name << "]'s Coefficient count "
+                   << values.size() << ", expected " << linked_sensors.size();
+        return false;
+    } else {
+        for (Json::Value::ArrayIndex j = 0; j < values.size(); ++j) {
+            coefficients.emplace_back(getFloatFromValue(values[j]));
+            if (std::isnan(coefficients[j])) {
+                LOG(ERROR) << "Invalid VirtualSensor[" << name << "]'s Coefficient[" << j
+                           << "]: " << coefficients[j];
+                return false;
+            }
+            LOG(INFO) << "VirtualSensor[" << name << "]'s Coefficient[" << j
+                      << "]: " << coefficients[j];
+        }
+    }
+
+    float offset = 0.0;
+    if (!sensor["Offset"].empty()) {
+        offset = getFloatFromValue(sensor["Offset"]);
+        if (std::isnan(offset)) {
+            LOG(ERROR) << "Invalid VirtualSensor[" << name << "]'s Offset: " << offset;
+            return false;
+        }
+    }
+    LOG(INFO) << "VirtualSensor[" << name << "]'s Offset: " << offset;
+
+    out->reset(new VirtualSensorInfo{
+            .linked_sensors = linked_sensors,
+            .coefficients = coefficients,
+            .offset = offset,
+            .formula = formula,
+    });
+    return true;
+}
+
+bool parsePIDInfo(const std::string &name, const Json::Value &pid,
+                  std::unique_ptr<PIDInfo> *out) {
+    float target_temp = getFloatFromValue(pid["TargetTemperature"]);
+    if (pid["TargetTemperature"].empty() || std::isnan(target_temp)) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s TargetTemperature";
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s TargetTemperature: " << target_temp;
+
+    float switch_on_temp = target_temp;
+    if (pid["SwitchOnTemperature"].empty()) {
+        LOG(INFO) << "Cannot find Sensor[" << name
+                  << "]'s SwitchOnTemperature, default to TargetTemperature";
+    } else {
+        switch_on_temp = getFloatFromValue(pid["SwitchOnTemperature"]);
+        if (std::isnan(switch_on_temp) || switch_on_temp > target_temp) {
+            LOG(ERROR) << "Invalid Sensor[" << name
+                       << "]'s SwitchOnTemperature: " << switch_on_temp;
+            return false;
+        }
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s SwitchOnTemperature: " << switch_on_temp;
+
+    std::map<std::string_view, float> gains = {
+            {"K_P", 0.0}, {"K_I", 0.0}, {"K_D", 0.0}, {"I_Max", 0.0}, {"PowerBudget", NAN},
+    };
+    for (auto &gain : gains) {
+        const Json::Value &value = pid[gain.first.data()];
+        if (value.empty()) {
+            if (std::isnan(gain.second)) {
+                LOG(ERROR) << "Failed to read Sensor[" << name << "]'s " << gain.first;
+                return false;
+            }
+            LOG(INFO) << "Cannot find Sensor[" << name << "]'s " << gain.first
+                      << ", default to " << gain.second;
+            continue;
+        }
+        gain.second = getFloatFromValue(value);
+        if (std::isnan(gain.second) || gain.second < 0) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s " << gain.first << ": "
+                       << gain.second;
+            return false;
+        }
+        LOG(INFO) << "Sensor[" << name << "]'s " << gain.first << ": " << gain.second;
+    }
+
+    float min_budget = 0.0;
+    if (!pid["MinBudget"].empty()) {
+        min_budget = getFloatFromValue(pid["MinBudget"]);
+    }
+    float max_budget = std::numeric_limits<float>::max();
+    if (!pid["MaxBudget"].empty()) {
+        max_budget = getFloatFromValue(pid["MaxBudget"]);
+    }
+    if (std::isnan(min_budget) || std::isnan(max_budget) || min_budget > max_budget) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s budget range: [" << min_budget << ", "
+                   << max_budget << "]";
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s budget range: [" << min_budget << ", " << max_budget
+              << "]";
+
+    std::vector<std::string> binded_cdevs;
+    Json::Value values = pid["BindedCdev"];
+    if (values.size() == 0) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s BindedCdev, empty list";
+        return false;
+    }
+    for (Json::Value::ArrayIndex j = 0; j < values.size(); ++j) {
+        binded_cdevs.emplace_back(values[j].asString());
+        LOG(INFO) << "Sensor[" << name << "]'s BindedCdev[" << j << "]: " << binded_cdevs[j];
+    }
+
+    std::vector<float> cdev_weights;
+    values = pid["CdevWeight"];
+    if (values.size() == 0) {
+        LOG(INFO) << "Cannot find Sensor[" << name << "]'s CdevWeight, default all to 1.0";
+        cdev_weights.assign(binded_cdevs.size(), 1.0);
+    } else if (values.size() != binded_cdevs.size()) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s CdevWeight count " << values.size()
+                   << ", expected " << binded_cdevs.size();
+        return false;
+    } else {
+        for (Json::Value::ArrayIndex j = 0; j < values.size(); ++j) {
+            cdev_weights.emplace_back(getFloatFromValue(values[j]));
+            if (std::isnan(cdev_weights[j]) || cdev_weights[j] <= 0) {
+                LOG(ERROR) << "Invalid Sensor[" << name << "]'s CdevWeight[" << j
+                           << "]: " << cdev_weights[j];
+                return false;
+            }
+            LOG(INFO) << "Sensor[" << name << "]'s CdevWeight[" << j << "]: " << cdev_weights[j];
+        }
+    }
+
+    out->reset(new PIDInfo{
+            .target_temp = target_temp,
+            .switch_on_temp = switch_on_temp,
+            .k_p = gains["K_P"],
+            .k_i = gains["K_I"],
+            .k_d = gains["K_D"],
+            .i_max = gains["I_Max"],
+            .s_power = gains["PowerBudget"],
+            .min_budget = min_budget,
+            .max_budget = max_budget,
+            .binded_cdevs = binded_cdevs,
+            .cdev_weights = cdev_weights,
+    });
+    return true;
+}
+
+bool parsePredictionInfo(const std::string &name, const Json::Value &prediction,
+                         std::unique_ptr<PredictionInfo> *out) {
+    if (!prediction["Samples"].isUInt() || prediction["Samples"].asUInt() < 2) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s Prediction Samples, need at least 2";
+        return false;
+    }
+    size_t samples = prediction["Samples"].asUInt();
+    LOG(INFO) << "Sensor[" << name << "]'s Prediction Samples: " << samples;
+
+    float horizon = getFloatFromValue(prediction["Horizon"]);
+    if (prediction["Horizon"].empty() || std::isnan(horizon) || horizon <= 0) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s Prediction Horizon";
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s Prediction Horizon: " << horizon << "s";
+
+    out->reset(new PredictionInfo{
+            .samples = samples,
+            .horizon = std::chrono::milliseconds(static_cast<int64_t>(horizon * 1000)),
+    });
+    return true;
+}
+
+bool parseTransformInfo(const std::string &name, const Json::Value &transform,
+                        std::unique_ptr<TransformInfo> *out) {
+    float offset = transform["Offset"].empty() ? 0.0 : getFloatFromValue(transform["Offset"]);
+    if (std::isnan(offset)) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s Transform Offset";
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s Transform Offset: " << offset;
+
+    std::vector<float> lut_raw;
+    std::vector<float> lut_values;
+    const Json::Value &lut = transform["LookupTable"];
+    if (!lut.empty()) {
+        const Json::Value &raw = lut["Raw"];
+        const Json::Value &values = lut["Value"];
+        if (raw.size() < 2 || raw.size() != values.size()) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s Transform LookupTable, need at least "
+                       << "2 Raw and as many Value";
+            return false;
+        }
+        for (Json::Value::ArrayIndex j = 0; j < raw.size(); ++j) {
+            lut_raw.emplace_back(getFloatFromValue(raw[j]));
+            lut_values.emplace_back(getFloatFromValue(values[j]));
+            if (std::isnan(lut_raw[j]) || std::isnan(lut_values[j]) ||
+                (j > 0 && lut_raw[j] <= lut_raw[j - 1])) {
+                LOG(ERROR) << "Invalid Sensor[" << name << "]'s Transform LookupTable[" << j
+                           << "]: " << lut_raw[j] << " -> " << lut_values[j];
+                return false;
+            }
+            LOG(INFO) << "Sensor[" << name << "]'s Transform LookupTable[" << j
+                      << "]: " << lut_raw[j] << " -> " << lut_values[j];
+        }
+    }
+
+    float ema_time_constant = transform["EMATimeConstant"].empty()
+                                      ? 0.0
+                                      : getFloatFromValue(transform["EMATimeConstant"]);
+    if (std::isnan(ema_time_constant) || ema_time_constant < 0) {
+        LOG(ERROR) << "Invalid Sensor[" << name
+                   << "]'s Transform EMATimeConstant: " << ema_time_constant;
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s Transform EMATimeConstant: " << ema_time_constant
+              << "s";
+
+    out->reset(new TransformInfo{
+            .offset = offset,
+            .lut_raw = lut_raw,
+            .lut_values = lut_values,
+            .ema_time_constant =
+                    std::chrono::milliseconds(static_cast<int64_t>(ema_time_constant * 1000)),
+    });
+    return true;
+}
+
+bool parseThresholdProfiles(const std::string &name, const Json::Value &profiles,
+                            const ThrottlingArray &default_hot_hysteresis,
+                            std::map<std::string, ThresholdProfile> *out) {
+    for (Json::Value::ArrayIndex i = 0; i < profiles.size(); ++i) {
+        const std::string &profile_name = profiles[i]["Name"].asString();
+        if (profile_name.empty() || out->count(profile_name)) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s Profile[" << i
+                       << "]'s Name: " << profile_name;
+            return false;
+        }
+
+        ThrottlingArray hot_thresholds;
+        Json::Value values = profiles[i]["HotThreshold"];
+        if (values.size() != kThrottlingSeverityCount) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s Profile[" << profile_name
+                       << "]'s HotThreshold count" << values.size();
+            return false;
+        }
+        float min = std::numeric_limits<float>::min();
+        for (Json::Value::ArrayIndex j = 0; j < kThrottlingSeverityCount; ++j) {
+            hot_thresholds[j] = getFloatFromValue(values[j]);
+            if (!std::isnan(hot_thresholds[j])) {
+                if (hot_thresholds[j] < min) {
+                    LOG(ERROR) << "Invalid Sensor[" << name << "]'s Profile[" << profile_name
+                               << "]'s HotThreshold[" << j << "]: " << hot_thresholds[j] << " < "
+                               << min;
+                    return false;
+                }
+                min = hot_thresholds[j];
+            }
+            LOG(INFO) << "Sensor[" << name << "]'s Profile[" << profile_name
+                      << "]'s HotThreshold[" << j << "]: " << hot_thresholds[j];
+        }
+
+        ThrottlingArray hot_hysteresis = default_hot_hysteresis;
+        values = profiles[i]["HotHysteresis"];
+        if (values.size() != kThrottlingSeverityCount) {
+            LOG(INFO) << "Cannot find valid Sensor[" << name << "]'s Profile[" << profile_name
+                      << "]'s HotHysteresis, default to the sensor's";
+        } else {
+            for (Json::Value::ArrayIndex j = 0; j < kThrottlingSeverityCount; ++j) {
+                hot_hysteresis[j] = getFloatFromValue(values[j]);
+                if (std::isnan(hot_hysteresis[j])) {
+                    LOG(ERROR) << "Invalid Sensor[" << name << "]'s Profile[" << profile_name
+                               << "]'s HotHysteresis: " << hot_hysteresis[j];
+                    return false;
+                }
+                LOG(INFO) << "Sensor[" << name << "]'s Profile[" << profile_name
+                          << "]'s HotHysteresis[" << j << "]: " << hot_hysteresis[j];
+            }
+        }
+
+        (*out)[profile_name] = {
+                .hot_thresholds = hot_thresholds,
+                .hot_hysteresis = hot_hysteresis,
+        };
+    }
+    return true;
+}
+
+bool parseChargerThrottlingInfo(const std::string &name, const Json::Value &charger,
+                                std::unique_ptr<ChargerThrottlingInfo> *out) {
+    std::vector<std::string> nodes;
+    const Json::Value &values = charger["Nodes"];
+    for (Json::Value::ArrayIndex j = 0; j < values.size(); ++j) {
+        nodes.emplace_back(values[j].asString());
+        LOG(INFO) << "Sensor[" << name << "]'s ChargerThrottling Node[" << j
+                  << "]: " << nodes[j];
+    }
+    if (nodes.empty()) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s ChargerThrottling, no Nodes";
+        return false;
+    }
+
+    ThrottlingArray current_limits;
+    const Json::Value &limits = charger["CurrentLimit"];
+    if (limits.size() != kThrottlingSeverityCount) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s ChargerThrottling CurrentLimit, need "
+                   << kThrottlingSeverityCount << " values";
+        return false;
+    }
+    for (Json::Value::ArrayIndex j = 0; j < kThrottlingSeverityCount; ++j) {
+        current_limits[j] = getFloatFromValue(limits[j]);
+        if (std::isnan(current_limits[j]) || current_limits[j] < 0) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s ChargerThrottling CurrentLimit[" << j
+                       << "]: " << current_limits[j];
+            return false;
+        }
+        // A higher severity should never allow more current
+        if (j && current_limits[j] > current_limits[j - 1]) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s ChargerThrottling CurrentLimit[" << j
+                       << "]: " << current_limits[j] << " > " << current_limits[j - 1];
+            return false;
+        }
+        LOG(INFO) << "Sensor[" << name << "]'s ChargerThrottling CurrentLimit[" << j
+                  << "]: " << current_limits[j] << "mA";
+    }
+
+    out->reset(new ChargerThrottlingInfo{
+            .nodes = nodes,
+            .current_limits = current_limits,
+    });
+    return true;
+}
+
+bool parseFaultInfo(const std::string &name, const Json::Value &fault,
+                    std::unique_ptr<FaultInfo> *out) {
+    float min_value = NAN;
+    float max_value = NAN;
+    const Json::Value &valid_range = fault["ValidRange"];
+    if (!valid_range.empty()) {
+        if (valid_range.size() != 2) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s ValidRange, need [min, max]";
+            return false;
+        }
+        min_value = getFloatFromValue(valid_range[0]);
+        max_value = getFloatFromValue(valid_range[1]);
+        if (min_value > max_value) {
+            LOG(ERROR) << "Invalid Sensor[" << name << "]'s ValidRange: " << min_value << " > "
+                       << max_value;
+            return false;
+        }
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s ValidRange: [" << min_value << ", " << max_value
+              << "]";
+
+    float max_delta_per_sec =
+            fault["MaxDeltaPerSec"].empty() ? NAN : getFloatFromValue(fault["MaxDeltaPerSec"]);
+    if (max_delta_per_sec <= 0) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s MaxDeltaPerSec: " << max_delta_per_sec;
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s MaxDeltaPerSec: " << max_delta_per_sec;
+
+    float stuck_timeout =
+            fault["StuckTimeout"].empty() ? NAN : getFloatFromValue(fault["StuckTimeout"]);
+    if (stuck_timeout <= 0) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s StuckTimeout: " << stuck_timeout;
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s StuckTimeout: " << stuck_timeout << "s";
+
+    std::string substitute = fault["Substitute"].asString();
+    if (substitute == name) {
+        LOG(ERROR) << "Invalid Sensor[" << name << "]'s Substitute: itself";
+        return false;
+    }
+    LOG(INFO) << "Sensor[" << name << "]'s Substitute: " << substitute;
+
+    float fallback_value =
+            fault["FallbackValue"].empty() ? NAN : getFloatFromValue(fault["FallbackValue"]);
+    LOG(INFO) << "Sensor[" << name << "]'s FallbackValue: " << fallback_value;
+
+    out->reset(new FaultInfo{
+            .min_value = min_value,
+            .max_value = max_value,
+            .max_delta_per_sec = max_delta_per_sec,
+            .stuck_timeout = std::isnan(stuck_timeout)
+                                     ? std::chrono::milliseconds::zero()
+                                     : std::chrono::milliseconds(
+                                               static_cast<int64_t>(stuck_timeout * 1000)),
+            .substitute = substitute,
+            .fallback_value = fallback_value,
+    });
+    return true;
+}
+
+// Sensors a sensor reads from: the linked sensors of a virtual sensor and the
+// substitute of a faulty sensor.
+std::vector<std::string> getSensorDependencies(const SensorInfo &sensor_info) {
+    std::vector<std::string> dependencies;
+    if (sensor_info.virtual_sensor_info != nullptr) {
+        dependencies = sensor_info.virtual_sensor_info->linked_sensors;
+    }
+    if (sensor_info.fault_info != nullptr && !sensor_info.fault_info->substitute.empty()) {
+        dependencies.emplace_back(sensor_info.fault_info->substitute);
+    }
+    return dependencies;
+}
+
+// Return true when reading name ends up reading a sensor still on the path
+bool hasSensorCycle(const std::map<std::string, SensorInfo> &sensors, const std::string &name,
+                    std::set<std::string> *path, std::set<std::string> *visited) {
+    if (path->count(name)) {
+        return true;
+    }
+    if (!visited->insert(name).second) {
+        return false;
+    }
+    auto it = sensors.find(name);
+    if (it == sensors.end()) {
+        return false;
+    }
+    path->insert(name);
+    for (const auto &dependency : getSensorDependencies(it->second)) {
+        if (hasSensorCycle(sensors, dependency, path, visited)) {
+            return true;
+        }
+    }
+    path->erase(name);
+    return false;
+}
+
 }  // namespace
 
 std::map<std::string, SensorInfo> ParseSensorInfo(std::string_view config_path) {
@@ -159,6 +631,12 @@ std::map<std::string, SensorInfo> ParseSensorInfo(std::string_view config_path)
             }
         }
 
+        std::map<std::string, ThresholdProfile> profiles;
+        if (!parseThresholdProfiles(name, sensors[i]["Profiles"], hot_hysteresis, &profiles)) {
+            sensors_parsed.clear();
+            return sensors_parsed;
+        }
+
         values = sensors[i]["ColdThreshold"];
         if (values.size() != kThrottlingSeverityCount) {
             LOG(INFO) << "Cannot find valid "
@@ -226,27 +704,213 @@ std::map<std::string, SensorInfo> ParseSensorInfo(std::string_view config_path)
         LOG(INFO) << "Sensor[" << name << "]'s SendPowerHint: " << std::boolalpha << send_powerhint
                   << std::noboolalpha;
 
+        PollingDelayArray polling_delays;
+        polling_delays.fill(kDefaultPollingDelay);
+        polling_delays[static_cast<size_t>(ThrottlingSeverity::NONE)] = kNoPollingDelay;
+        values = sensors[i]["PollingDelay"];
+        if (values.size() != kThrottlingSeverityCount) {
+            LOG(INFO) << "Cannot find valid "
+                      << "Sensor[" << name << "]'s PollingDelay, use default";
+        } else {
+            for (Json::Value::ArrayIndex j = 0; j < kThrottlingSeverityCount; ++j) {
+                float delay = getFloatFromValue(values[j]);
+                if (std::isnan(delay)) {
+                    continue;
+                }
+                if (delay <= 0) {
+                    LOG(ERROR) << "Invalid "
+                               << "Sensor[" << name << "]'s PollingDelay[" << j << "]: " << delay;
+                    sensors_parsed.clear();
+                    return sensors_parsed;
+                }
+                polling_delays[j] = std::chrono::milliseconds(static_cast<int64_t>(delay));
+                LOG(INFO) << "Sensor[" << name << "]'s PollingDelay[" << j << "]: " << delay
+                          << "ms";
+            }
+        }
+
+        PollingDelayArray min_dwell_times;
+        min_dwell_times.fill(std::chrono::milliseconds::zero());
+        values = sensors[i]["MinDwellTime"];
+        if (values.size() != kThrottlingSeverityCount) {
+            LOG(INFO) << "Cannot find valid "
+                      << "Sensor[" << name << "]'s MinDwellTime, default all to 0";
+        } else {
+            for (Json::Value::ArrayIndex j = 0; j < kThrottlingSeverityCount; ++j) {
+                float dwell_time = getFloatFromValue(values[j]);
+                if (std::isnan(dwell_time) || dwell_time < 0) {
+                    LOG(ERROR) << "Invalid "
+                               << "Sensor[" << name << "]'s MinDwellTime[" << j
+                               << "]: " << dwell_time;
+                    sensors_parsed.clear();
+                    return sensors_parsed;
+                }
+                min_dwell_times[j] =
+                        std::chrono::milliseconds(static_cast<int64_t>(dwell_time * 1000));
+                LOG(INFO) << "Sensor[" << name << "]'s MinDwellTime[" << j << "]: " << dwell_time
+                          << "s";
+            }
+        }
+
+        std::unique_ptr<VirtualSensorInfo> virtual_sensor_info;
+        if (!sensors[i]["VirtualSensor"].empty() && sensors[i]["VirtualSensor"].isBool() &&
+            sensors[i]["VirtualSensor"].asBool()) {
+            if (!parseVirtualSensorInfo(name, sensors[i], &virtual_sensor_info)) {
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+        LOG(INFO) << "Sensor[" << name << "]'s VirtualSensor: " << std::boolalpha
+                  << (virtual_sensor_info != nullptr) << std::noboolalpha;
+
+        std::unique_ptr<PIDInfo> pid_info;
+        if (!sensors[i]["PIDInfo"].empty()) {
+            if (!is_monitor) {
+                LOG(ERROR) << "Sensor[" << name << "]'s PIDInfo requires Monitor";
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+            if (!parsePIDInfo(name, sensors[i]["PIDInfo"], &pid_info)) {
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+
+        std::unique_ptr<PredictionInfo> prediction_info;
+        if (!sensors[i]["Prediction"].empty()) {
+            if (!is_monitor) {
+                LOG(ERROR) << "Sensor[" << name << "]'s Prediction requires Monitor";
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+            if (!parsePredictionInfo(name, sensors[i]["Prediction"], &prediction_info)) {
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+
+        std::unique_ptr<FaultInfo> fault_info;
+        if (!sensors[i]["FaultDetection"].empty()) {
+            if (!parseFaultInfo(name, sensors[i]["FaultDetection"], &fault_info)) {
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+
+        std::unique_ptr<TransformInfo> transform_info;
+        if (!sensors[i]["Transform"].empty()) {
+            if (virtual_sensor_info != nullptr) {
+                LOG(ERROR) << "Sensor[" << name << "]'s Transform requires a physical sensor";
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+            if (!parseTransformInfo(name, sensors[i]["Transform"], &transform_info)) {
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+
+        std::unique_ptr<ChargerThrottlingInfo> charger_throttling_info;
+        if (!sensors[i]["ChargerThrottling"].empty()) {
+            if (!is_monitor) {
+                LOG(ERROR) << "Sensor[" << name << "]'s ChargerThrottling requires Monitor";
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+            if (!parseChargerThrottlingInfo(name, sensors[i]["ChargerThrottling"],
+                                            &charger_throttling_info)) {
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+
         sensors_parsed[name] = {
                 .type = sensor_type,
                 .hot_thresholds = hot_thresholds,
                 .cold_thresholds = cold_thresholds,
                 .hot_hysteresis = hot_hysteresis,
                 .cold_hysteresis = cold_hysteresis,
+                .profiles = profiles,
                 .vr_threshold = vr_threshold,
                 .multiplier = multiplier,
                 .is_monitor = is_monitor,
                 .send_powerhint = send_powerhint,
+                .polling_delays = polling_delays,
+                .min_dwell_times = min_dwell_times,
+                .virtual_sensor_info = std::move(virtual_sensor_info),
+                .pid_info = std::move(pid_info),
+                .prediction_info = std::move(prediction_info),
+                .fault_info = std::move(fault_info),
+                .transform_info = std::move(transform_info),
+                .charger_throttling_info = std::move(charger_throttling_info),
         };
         ++total_parsed;
     }
 
+    // Linked sensors of a virtual sensor must be physical sensors declared in the config
+    for (const auto &name_info_pair : sensors_parsed) {
+        if (name_info_pair.second.virtual_sensor_info == nullptr) {
+            continue;
+        }
+        for (const auto &linked_sensor :
+             name_info_pair.second.virtual_sensor_info->linked_sensors) {
+            auto it = sensors_parsed.find(linked_sensor);
+            if (it == sensors_parsed.end()) {
+                LOG(ERROR) << "VirtualSensor[" << name_info_pair.first
+                           << "]'s linked sensor not found: " << linked_sensor;
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+            if (it->second.virtual_sensor_info != nullptr) {
+                LOG(ERROR) << "VirtualSensor[" << name_info_pair.first
+                           << "]'s linked sensor is virtual: " << linked_sensor;
+                sensors_parsed.clear();
+                return sensors_parsed;
+            }
+        }
+    }
+
+    // A substitute must be declared and must not fall back to another sensor itself
+    for (const auto &name_info_pair : sensors_parsed) {
+        if (name_info_pair.second.fault_info == nullptr ||
+            name_info_pair.second.fault_info->substitute.empty()) {
+            continue;
+        }
+        const std::string &substitute = name_info_pair.second.fault_info->substitute;
+        auto it = sensors_parsed.find(substitute);
+        if (it == sensors_parsed.end()) {
+            LOG(ERROR) << "Sensor[" << name_info_pair.first
+                       << "]'s substitute not found: " << substitute;
+            sensors_parsed.clear();
+            return sensors_parsed;
+        }
+        if (it->second.fault_info != nullptr && !it->second.fault_info->substitute.empty()) {
+            LOG(ERROR) << "Sensor[" << name_info_pair.first
+                       << "]'s substitute has a substitute: " << substitute;
+            sensors_parsed.clear();
+            return sensors_parsed;
+        }
+    }
+
+    // Substitutes and linked sensors must not lead back to the sensor being read
+    std::set<std::string> visited;
+    for (const auto &name_info_pair : sensors_parsed) {
+        std::set<std::string> path;
+        if (hasSensorCycle(sensors_parsed, name_info_pair.first, &path, &visited)) {
+            LOG(ERROR) << "Sensor[" << name_info_pair.first
+                       << "] reads itself through its substitute or linked sensors";
+            sensors_parsed.clear();
+            return sensors_parsed;
+        }
+    }
+
     LOG(INFO) << total_parsed << " Sensors parsed successfully";
     return sensors_parsed;
 }
 
-std::map<std::string, CoolingType> ParseCoolingDevice(std::string_view config_path) {
+std::map<std::string, CdevInfo> ParseCoolingDevice(std::string_view config_path) {
     std::string json_doc;
-    std::map<std::string, CoolingType> cooling_devices_parsed;
+    std::map<std::string, CdevInfo> cooling_devices_parsed;
     if (!android::base::ReadFileToString(config_path.data(), &json_doc)) {
         LOG(ERROR) << "Failed to read JSON config from " << config_path;
         return cooling_devices_parsed;
@@ -292,7 +956,25 @@ std::map<std::string, CoolingType> ParseCoolingDevice(std::string_view config_pa
             return cooling_devices_parsed;
         }
 
-        cooling_devices_parsed[name] = cooling_device_type;
+        std::vector<float> state2power;
+        Json::Value values = cooling_devices[i]["State2Power"];
+        for (Json::Value::ArrayIndex j = 0; j < values.size(); ++j) {
+            state2power.emplace_back(getFloatFromValue(values[j]));
+            if (std::isnan(state2power[j]) || (j > 0 && state2power[j] > state2power[j - 1])) {
+                LOG(ERROR) << "Invalid "
+                           << "CoolingDevice[" << name << "]'s State2Power[" << j
+                           << "]: " << state2power[j];
+                cooling_devices_parsed.clear();
+                return cooling_devices_parsed;
+            }
+            LOG(INFO) << "CoolingDevice[" << name << "]'s State2Power[" << j
+                      << "]: " << state2power[j];
+        }
+
+        cooling_devices_parsed[name] = {
+                .type = cooling_device_type,
+                .state2power = state2power,
+        };
 
         ++total_parsed;
     }
@@ -301,6 +983,95 @@ std::map<std::string, CoolingType> ParseCoolingDevice(std::string_view config_pa
     return cooling_devices_parsed;
 }
 
+bool ParseNotificationInfo(std::string_view config_path, NotificationInfo *notification_info) {
+    std::string json_doc;
+    if (!android::base::ReadFileToString(config_path.data(), &json_doc)) {
+        LOG(ERROR) << "Failed to read JSON config from " << config_path;
+        return false;
+    }
+
+    Json::Value root;
+    Json::Reader reader;
+
+    if (!reader.parse(json_doc, root)) {
+        LOG(ERROR) << "Failed to parse JSON config";
+        return false;
+    }
+
+    const Json::Value &notification = root["Notification"];
+    float min_interval = 0.0;
+    if (notification["MinInterval"].empty()) {
+        LOG(INFO) << "Cannot find Notification's MinInterval, default to 0";
+    } else {
+        min_interval = getFloatFromValue(notification["MinInterval"]);
+        if (std::isnan(min_interval) || min_interval < 0) {
+            LOG(ERROR) << "Invalid Notification's MinInterval: " << min_interval;
+            return false;
+        }
+    }
+    LOG(INFO) << "Notification's MinInterval: " << min_interval << "s";
+
+    notification_info->min_interval =
+            std::chrono::milliseconds(static_cast<int64_t>(min_interval * 1000));
+    return true;
+}
+
+bool ParseThermalConfig(std::string_view config_path,
+                        std::map<std::string, SensorInfo> *sensor_info_map,
+                        std::map<std::string, CdevInfo> *cooling_device_info_map,
+                        NotificationInfo *notification_info) {
+    std::string json_doc;
+    if (!android::base::ReadFileToString(config_path.data(), &json_doc)) {
+        LOG(ERROR) << "Failed to read JSON config from " << config_path;
+        return false;
+    }
+
+    Json::Value root;
+    Json::Reader reader;
+
+    if (!reader.parse(json_doc, root)) {
+        LOG(ERROR) << "Failed to parse JSON config";
+        return false;
+    }
+
+    // The parsers return an empty map on any malformed entry
+    *sensor_info_map = ParseSensorInfo(config_path);
+    if (sensor_info_map->size() != root["Sensors"].size()) {
+        LOG(ERROR) << "Failed to parse Sensors section from " << config_path;
+        return false;
+    }
+    *cooling_device_info_map = ParseCoolingDevice(config_path);
+    if (cooling_device_info_map->size() != root["CoolingDevices"].size()) {
+        LOG(ERROR) << "Failed to parse CoolingDevices section from " << config_path;
+        return false;
+    }
+    if (!ParseNotificationInfo(config_path, notification_info)) {
+        LOG(ERROR) << "Failed to parse Notification section from " << config_path;
+        return false;
+    }
+
+    // PID controlled sensors can only bind cooling devices with power tables
+    for (const auto &name_info_pair : *sensor_info_map) {
+        if (name_info_pair.second.pid_info == nullptr) {
+            continue;
+        }
+        for (const auto &cdev : name_info_pair.second.pid_info->binded_cdevs) {
+            auto it = cooling_device_info_map->find(cdev);
+            if (it == cooling_device_info_map->end()) {
+                LOG(ERROR) << "Sensor[" << name_info_pair.first
+                           << "] binds unknown cooling device " << cdev;
+                return false;
+            }
+            if (it->second.state2power.empty()) {
+                LOG(ERROR) << "Sensor[" << name_info_pair.first << "] binds cooling device "
+                           << cdev << " without State2Power";
+                return false;
+            }
+        }
+    }
+    return true;
+}
+
 }  // namespace implementation
 }  // namespace V2_0
 }  // namespace thermal
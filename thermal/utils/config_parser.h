@@ -17,8 +17,11 @@
 #ifndef THERMAL_UTILS_CONFIG_PARSER_H__
 #define THERMAL_UTILS_CONFIG_PARSER_H__
 
+#include <chrono>
 #include <map>
+#include <memory>
 #include <string>
+#include <vector>
 
 #include <android/hardware/thermal/2.0/IThermal.h>
 
@@ -35,6 +38,91 @@ using ::android::hardware::thermal::V2_0::ThrottlingSeverity;
 constexpr size_t kThrottlingSeverityCount = std::distance(
     hidl_enum_range<ThrottlingSeverity>().begin(), hidl_enum_range<ThrottlingSeverity>().end());
 using ThrottlingArray = std::array<float, static_cast<size_t>(kThrottlingSeverityCount)>;
+using PollingDelayArray = std::array<std::chrono::milliseconds, kThrottlingSeverityCount>;
+
+// Default polling delay of a monitored sensor above ThrottlingSeverity::NONE.
+constexpr std::chrono::milliseconds kDefaultPollingDelay(2000);
+// Polling delay of a sensor which needs no periodic reading, the watcher waits
+// for uevent or uses its own idle polling interval.
+constexpr std::chrono::milliseconds kNoPollingDelay = std::chrono::milliseconds::max();
+
+enum FormulaOption : uint32_t {
+    WEIGHTED_SUM = 0,
+    MAXIMUM,
+    MINIMUM,
+    AVERAGE,
+};
+
+// A virtual sensor has no thermal zone of its own, its temperature is computed
+// from the linked sensors with the given formula, then shifted by offset.
+struct VirtualSensorInfo {
+    std::vector<std::string> linked_sensors;
+    std::vector<float> coefficients;
+    float offset;
+    FormulaOption formula;
+};
+
+// Closed-loop controller which converts the distance to target_temp into a power
+// budget, then spreads the budget across the binded cooling devices by weight.
+struct PIDInfo {
+    float target_temp;
+    float switch_on_temp;
+    float k_p;
+    float k_i;
+    float k_d;
+    float i_max;
+    float s_power;
+    float min_budget;
+    float max_budget;
+    std::vector<std::string> binded_cdevs;
+    std::vector<float> cdev_weights;
+};
+
+// Linear regression over the latest samples, the severity is escalated when the
+// temperature projected horizon ahead crosses a higher hot threshold.
+struct PredictionInfo {
+    size_t samples;
+    std::chrono::milliseconds horizon;
+};
+
+// Plausibility rules of a sensor reading. A reading which fails to read, falls
+// out of [min_value, max_value], changes faster than max_delta_per_sec or stays
+// the same for stuck_timeout marks the sensor faulty, its value is then raised
+// to the substitute sensor or fallback_value.
+struct FaultInfo {
+    float min_value;
+    float max_value;
+    float max_delta_per_sec;
+    std::chrono::milliseconds stuck_timeout;
+    std::string substitute;
+    float fallback_value;
+};
+
+// Processing of a physical sensor's raw reading. The reading is mapped through
+// the piecewise-linear lookup table if any, scaled by the sensor's multiplier and
+// shifted by offset, then smoothed by an EMA with ema_time_constant if non-zero.
+struct TransformInfo {
+    float offset;
+    // Ascending raw readings and the values they map to, clamped at both ends
+    std::vector<float> lut_raw;
+    std::vector<float> lut_values;
+    std::chrono::milliseconds ema_time_constant;
+};
+
+// Charge current limit, in mA, at each severity of a sensor. The limit is written
+// in uA to the power_supply nodes, the lowest limit wins when several sensors
+// bind the same node.
+struct ChargerThrottlingInfo {
+    std::vector<std::string> nodes;
+    ThrottlingArray current_limits;
+};
+
+// Named thresholds of a sensor, they replace the sensor's HotThreshold and
+// HotHysteresis while the profile is active.
+struct ThresholdProfile {
+    ThrottlingArray hot_thresholds;
+    ThrottlingArray hot_hysteresis;
+};
 
 struct SensorInfo {
     TemperatureType_2_0 type;
@@ -42,14 +130,44 @@ struct SensorInfo {
     ThrottlingArray cold_thresholds;
     ThrottlingArray hot_hysteresis;
     ThrottlingArray cold_hysteresis;
+    std::map<std::string, ThresholdProfile> profiles;
     float vr_threshold;
     float multiplier;
     bool is_monitor;
     bool send_powerhint;
+    // How often the sensor should be read at each severity
+    PollingDelayArray polling_delays;
+    // How long a notified severity is held before a lower one is notified
+    PollingDelayArray min_dwell_times;
+    std::unique_ptr<VirtualSensorInfo> virtual_sensor_info;
+    std::unique_ptr<PIDInfo> pid_info;
+    std::unique_ptr<PredictionInfo> prediction_info;
+    std::unique_ptr<FaultInfo> fault_info;
+    std::unique_ptr<TransformInfo> transform_info;
+    std::unique_ptr<ChargerThrottlingInfo> charger_throttling_info;
+};
+
+struct CdevInfo {
+    CoolingType type;
+    // Power consumption of each cooling state, state 0 is unthrottled.
+    std::vector<float> state2power;
+};
+
+// Severity change notifications closer than min_interval are coalesced into
+// one callback, except an escalation to SEVERE or above which goes out right away.
+struct NotificationInfo {
+    std::chrono::milliseconds min_interval;
 };
 
 std::map<std::string, SensorInfo> ParseSensorInfo(std::string_view config_path);
-std::map<std::string, CoolingType> ParseCoolingDevice(std::string_view config_path);
+std::map<std::string, CdevInfo> ParseCoolingDevice(std::string_view config_path);
+bool ParseNotificationInfo(std::string_view config_path, NotificationInfo *notification_info);
+// Parse every section of the config and check the references across sections,
+// return false when any entry is malformed instead of dropping the whole section.
+bool ParseThermalConfig(std::string_view config_path,
+                        std::map<std::string, SensorInfo> *sensor_info_map,
+                        std::map<std::string, CdevInfo> *cooling_device_info_map,
+                        NotificationInfo *notification_info);
 
 }  // namespace implementation
 }  // namespace V2_0
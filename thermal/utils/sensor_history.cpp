@@ -0,0 +1,70 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#include <algorithm>
+
+#include "sensor_history.h"
+
+namespace android {
+namespace hardware {
+namespace thermal {
+namespace V2_0 {
+namespace implementation {
+
+void SensorHistory::addSample(const SensorSample &sample) {
+    if (capacity_ == 0) {
+        return;
+    }
+    if (samples_.size() < capacity_) {
+        samples_.push_back(sample);
+        return;
+    }
+    samples_[next_] = sample;
+    next_ = (next_ + 1) % capacity_;
+}
+
+std::vector<SensorSample> SensorHistory::getSamples() const {
+    std::vector<SensorSample> samples;
+    samples.reserve(samples_.size());
+    samples.insert(samples.end(), samples_.begin() + next_, samples_.end());
+    samples.insert(samples.end(), samples_.begin(), samples_.begin() + next_);
+    return samples;
+}
+
+SensorStats SensorHistory::getStats(std::chrono::milliseconds window,
+                                    boot_clock::time_point now) const {
+    SensorStats stats = {.count = 0, .min = 0.0, .max = 0.0, .avg = 0.0};
+    float sum = 0.0;
+    for (const auto &sample : samples_) {
+        if (now - sample.timestamp > window) {
+            continue;
+        }
+        stats.min = stats.count ? std::min(stats.min, sample.value) : sample.value;
+        stats.max = stats.count ? std::max(stats.max, sample.value) : sample.value;
+        sum += sample.value;
+        ++stats.count;
+    }
+    if (stats.count) {
+        stats.avg = sum / stats.count;
+    }
+    return stats;
+}
+
+}  // namespace implementation
+}  // namespace V2_0
+}  // namespace thermal
+}  // namespace hardware
+}  // namespace android
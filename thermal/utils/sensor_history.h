@@ -0,0 +1,73 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#ifndef THERMAL_UTILS_SENSOR_HISTORY_H__
+#define THERMAL_UTILS_SENSOR_HISTORY_H__
+
+#include <chrono>
+#include <vector>
+
+#include <android-base/chrono_utils.h>
+#include <android/hardware/thermal/2.0/IThermal.h>
+
+namespace android {
+namespace hardware {
+namespace thermal {
+namespace V2_0 {
+namespace implementation {
+
+using ::android::base::boot_clock;
+using ::android::hardware::thermal::V2_0::ThrottlingSeverity;
+
+struct SensorSample {
+    boot_clock::time_point timestamp;
+    float value;
+    ThrottlingSeverity severity;
+};
+
+struct SensorStats {
+    size_t count;
+    float min;
+    float max;
+    float avg;
+};
+
+// Fixed-size ring buffer of the latest readings of a sensor.
+class SensorHistory {
+  public:
+    explicit SensorHistory(size_t capacity) : capacity_(capacity), next_(0) {}
+
+    void addSample(const SensorSample &sample);
+    // Return the samples from the oldest to the latest.
+    std::vector<SensorSample> getSamples() const;
+    // Return the stats of the samples taken within window before now, count is
+    // 0 if there is no such sample.
+    SensorStats getStats(std::chrono::milliseconds window, boot_clock::time_point now) const;
+
+  private:
+    size_t capacity_;
+    // Index where the next sample goes once the buffer is full
+    size_t next_;
+    std::vector<SensorSample> samples_;
+};
+
+}  // namespace implementation
+}  // namespace V2_0
+}  // namespace thermal
+}  // namespace hardware
+}  // namespace android
+
+#endif  // THERMAL_UTILS_SENSOR_HISTORY_H__
@@ -58,6 +58,19 @@ bool ThermalFiles::readThermalFile(std::string_view thermal_name, std::string *d
     return true;
 }
 
+bool ThermalFiles::writeThermalFile(std::string_view thermal_name, std::string_view data) const {
+    std::string file_path = getThermalFilePath(thermal_name);
+    if (file_path.empty()) {
+        return false;
+    }
+
+    if (!::android::base::WriteStringToFile(std::string(data), file_path)) {
+        PLOG(WARNING) << "Failed to write " << data << " to " << thermal_name;
+        return false;
+    }
+    return true;
+}
+
 }  // namespace implementation
 }  // namespace V2_0
 }  // namespace thermal
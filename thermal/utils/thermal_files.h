@@ -40,6 +40,8 @@ class ThermalFiles {
     // data to empty and return false. If the thermal_name is found and its content
     // is read, this function will fill in data accordingly then return true.
     bool readThermalFile(std::string_view thermal_name, std::string *data) const;
+    // Returns true if data was written to the file registered for thermal_name.
+    bool writeThermalFile(std::string_view thermal_name, std::string_view data) const;
     size_t getNumThermalFiles() const { return thermal_name_to_path_map_.size(); }
 
   private:
@@ -0,0 +1,54 @@
+/*
+ * Copyright (C) 2020 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#ifndef THERMAL_UTILS_THERMAL_SEVERITY_H__
+#define THERMAL_UTILS_THERMAL_SEVERITY_H__
+
+#include <string_view>
+#include <utility>
+#include <vector>
+
+#include "config_parser.h"
+
+namespace android {
+namespace hardware {
+namespace thermal {
+namespace V2_0 {
+namespace implementation {
+
+// Return hot and cold severity status as std::pair
+std::pair<ThrottlingSeverity, ThrottlingSeverity> getSeverityFromThresholds(
+        const ThrottlingArray &hot_thresholds, const ThrottlingArray &cold_thresholds,
+        const ThrottlingArray &hot_hysteresis, const ThrottlingArray &cold_hysteresis,
+        ThrottlingSeverity prev_hot_severity, ThrottlingSeverity prev_cold_severity,
+        float value);
+
+// Hot thresholds and hysteresis of the sensor under the profile, the sensor's
+// own ones if the profile does not override them.
+ThresholdProfile getThresholdProfile(const SensorInfo &sensor_info, std::string_view profile);
+
+// Combine the temperatures of the linked sensors, in linked_sensors order, into
+// the virtual sensor's temperature.
+float getVirtualSensorValue(const VirtualSensorInfo &virtual_sensor_info,
+                            const std::vector<float> &linked_temps);
+
+}  // namespace implementation
+}  // namespace V2_0
+}  // namespace thermal
+}  // namespace hardware
+}  // namespace android
+
+#endif  // THERMAL_UTILS_THERMAL_SEVERITY_H__
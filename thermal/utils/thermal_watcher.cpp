@@ -37,24 +37,28 @@ using std::chrono_literals::operator""ms;
 
 void ThermalWatcher::registerFilesToWatch(const std::set<std::string> &sensors_to_watch,
                                           bool uevent_monitor) {
-    monitored_sensors_.insert(sensors_to_watch.begin(), sensors_to_watch.end());
+    std::lock_guard<std::mutex> _lock(monitored_sensors_mutex_);
+    monitored_sensors_ = sensors_to_watch;
     if (!uevent_monitor) {
         is_polling_ = true;
         return;
     }
-    uevent_fd_.reset((TEMP_FAILURE_RETRY(uevent_open_socket(64 * 1024, true))));
+    // The uevent socket is kept across re-registration
     if (uevent_fd_.get() < 0) {
-        LOG(ERROR) << "failed to open uevent socket";
-        is_polling_ = true;
-        return;
-    }
+        uevent_fd_.reset((TEMP_FAILURE_RETRY(uevent_open_socket(64 * 1024, true))));
+        if (uevent_fd_.get() < 0) {
+            LOG(ERROR) << "failed to open uevent socket";
+            is_polling_ = true;
+            return;
+        }
 
-    fcntl(uevent_fd_, F_SETFL, O_NONBLOCK);
+        fcntl(uevent_fd_, F_SETFL, O_NONBLOCK);
 
-    looper_->addFd(uevent_fd_.get(), 0, Looper::EVENT_INPUT, nullptr, nullptr);
+        looper_->addFd(uevent_fd_.get(), 0, Looper::EVENT_INPUT, nullptr, nullptr);
+        polling_delay_ = kDefaultPollingDelay;
+        last_update_time_ = boot_clock::now();
+    }
     is_polling_ = false;
-    thermal_triggered_ = true;
-    last_update_time_ = boot_clock::now();
 }
 
 bool ThermalWatcher::startWatchingDeviceFiles() {
@@ -70,14 +74,20 @@ bool ThermalWatcher::startWatchingDeviceFiles() {
     }
     return false;
 }
-void ThermalWatcher::parseUevent(std::set<std::string> *sensors_set) {
+
+void ThermalWatcher::stopWatchingDeviceFiles() {
+    requestExit();
+    wake();
+    join();
+}
+void ThermalWatcher::parseUevent(int uevent_fd, std::set<std::string> *sensors_set) {
     bool thermal_event = false;
     constexpr int kUeventMsgLen = 2048;
     char msg[kUeventMsgLen + 2];
     char *cp;
 
     while (true) {
-        int n = uevent_kernel_multicast_recv(uevent_fd_.get(), msg, kUeventMsgLen);
+        int n = uevent_kernel_multicast_recv(uevent_fd, msg, kUeventMsgLen);
         if (n <= 0) {
             if (errno != EAGAIN && errno != EWOULDBLOCK) {
                 LOG(ERROR) << "Error reading from Uevent Fd";
@@ -109,6 +119,7 @@ void ThermalWatcher::parseUevent(std::set<std::string> *sensors_set) {
                 if (start_pos != std::string::npos) {
                     start_pos += 5;
                     std::string name = uevent.substr(start_pos);
+                    std::lock_guard<std::mutex> _lock(monitored_sensors_mutex_);
                     if (std::find(monitored_sensors_.begin(), monitored_sensors_.end(), name) !=
                         monitored_sensors_.end()) {
                         sensors_set->insert(name);
@@ -128,28 +139,43 @@ void ThermalWatcher::wake() {
 
 bool ThermalWatcher::threadLoop() {
     LOG(VERBOSE) << "ThermalWatcher polling...";
-    // Polling interval 2s
-    static constexpr int kMinPollIntervalMs = 2000;
     // Max uevent timeout 5mins
-    static constexpr int kUeventPollTimeoutMs = 300000;
+    static constexpr std::chrono::milliseconds kUeventPollTimeoutMs(300000);
     int fd;
     std::set<std::string> sensors;
 
+    bool is_polling;
+    int uevent_fd;
+    std::chrono::milliseconds polling_delay;
+    boot_clock::time_point last_update_time;
+    {
+        std::lock_guard<std::mutex> _lock(monitored_sensors_mutex_);
+        is_polling = is_polling_;
+        uevent_fd = uevent_fd_.get();
+        polling_delay = polling_delay_;
+        last_update_time = last_update_time_;
+    }
     auto time_elapsed_ms = std::chrono::duration_cast<std::chrono::milliseconds>(boot_clock::now() -
-                                                                                 last_update_time_)
+                                                                                 last_update_time)
                                    .count();
-    int timeout = (thermal_triggered_ || is_polling_) ? kMinPollIntervalMs : kUeventPollTimeoutMs;
+    if (polling_delay == kNoPollingDelay) {
+        polling_delay = is_polling ? kDefaultPollingDelay : kUeventPollTimeoutMs;
+    }
+    int timeout = std::min(polling_delay, kUeventPollTimeoutMs).count();
     if (time_elapsed_ms < timeout && looper_->pollOnce(timeout, &fd, nullptr, nullptr) >= 0) {
-        if (fd != uevent_fd_.get()) {
+        if (fd != uevent_fd) {
             return true;
         }
-        parseUevent(&sensors);
+        parseUevent(uevent_fd, &sensors);
         // Ignore cb_ if uevent is not from monitored sensors
         if (sensors.size() == 0) {
             return true;
         }
     }
-    thermal_triggered_ = cb_(sensors);
+    // Not under the lock, the callback may re-register the files to watch
+    polling_delay = cb_(sensors);
+    std::lock_guard<std::mutex> _lock(monitored_sensors_mutex_);
+    polling_delay_ = polling_delay;
     last_update_time_ = boot_clock::now();
     return true;
 }
@@ -32,6 +32,8 @@
 #include <utils/Looper.h>
 #include <utils/Thread.h>
 
+#include "config_parser.h"
+
 namespace android {
 namespace hardware {
 namespace thermal {
@@ -40,13 +42,13 @@ namespace implementation {
 
 using android::base::boot_clock;
 using android::base::unique_fd;
-using WatcherCallback = std::function<bool(const std::set<std::string> &name)>;
+using WatcherCallback = std::function<std::chrono::milliseconds(const std::set<std::string> &name)>;
 
 // A helper class for monitoring thermal files changes.
 class ThermalWatcher : public ::android::Thread {
   public:
     ThermalWatcher(const WatcherCallback &cb)
-        : Thread(false), cb_(cb), looper_(new Looper(true)) {}
+        : Thread(false), cb_(cb), looper_(new Looper(true)), polling_delay_(kNoPollingDelay) {}
     ~ThermalWatcher() = default;
 
     // Disallow copy and assign.
@@ -55,9 +57,12 @@ class ThermalWatcher : public ::android::Thread {
 
     // Start the thread and return true if it succeeds.
     bool startWatchingDeviceFiles();
+    // Stop the thread and wait for the ongoing callback to return.
+    void stopWatchingDeviceFiles();
     // Give the file watcher a list of files to start watching. This helper
     // class will by default wait for modifications to the file with a looper.
-    // This should be called before starting watcher thread.
+    // This should be called before starting watcher thread, calling it again
+    // replaces the watched list, e.g. after thermal config is reloaded.
     void registerFilesToWatch(const std::set<std::string> &sensors_to_watch, bool uevent_monitor);
     // Wake up the looper thus the worker thread, immediately. This can be called
     // in any thread.
@@ -70,8 +75,8 @@ class ThermalWatcher : public ::android::Thread {
     // modified file.
     bool threadLoop() override;
 
-    // Parse uevent message
-    void parseUevent(std::set<std::string> *sensor_name);
+    // Parse uevent message read from uevent_fd
+    void parseUevent(int uevent_fd, std::set<std::string> *sensor_name);
 
     // Maps watcher filer descriptor to watched file path.
     std::unordered_map<int, std::string> watch_to_file_path_map_;
@@ -79,17 +84,21 @@ class ThermalWatcher : public ::android::Thread {
     // The callback function. Called whenever thermal uevent is seen.
     // The function passed in should expect a string in the form (type).
     // Where type is the name of the thermal zone that trigger a uevent notification.
-    // Callback will return thermal trigger status for next polling decision.
+    // Callback will return the delay until the next polling, kNoPollingDelay if no
+    // sensor needs periodic reading.
     const WatcherCallback cb_;
 
     sp<Looper> looper_;
 
+    // Protect the members below, they are updated by re-registration from
+    // other threads while the watcher thread reads them.
+    std::mutex monitored_sensors_mutex_;
     // For uevent socket registration.
     android::base::unique_fd uevent_fd_;
     // Sensor list which monitor flag is enabled.
     std::set<std::string> monitored_sensors_;
-    // Flag to point out if any sensor across the first threshold.
-    bool thermal_triggered_;
+    // Delay until the next polling requested by the last callback.
+    std::chrono::milliseconds polling_delay_;
     // Flag to point out if device can support uevent notify.
     bool is_polling_;
     // Timestamp for last thermal update